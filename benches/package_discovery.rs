@@ -0,0 +1,108 @@
+// Package discovery on synthetic large images
+#[macro_use]
+extern crate criterion;
+extern crate ifrextractor;
+
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+use ifrextractor::{framework_parser, package_discovery, uefi_parser};
+
+// UEFI string package with a single string
+fn uefi_string_package() -> Vec<u8> {
+    let mut package = vec![0x00, 0x00, 0x00, 0x04]; // Length and type
+    package.extend_from_slice(&[0x34, 0x00, 0x00, 0x00]); // HdrSize
+    package.extend_from_slice(&[0x34, 0x00, 0x00, 0x00]); // StringInfoOffset
+    package.extend_from_slice(&[0x00; 32]); // LanguageWindow
+    package.extend_from_slice(&[0x01, 0x00]); // LanguageName
+    package.extend_from_slice(b"en-US\0");
+    package.extend_from_slice(&[0x14, b'A', 0x00, 0x00, 0x00]); // StringUcs2
+    package.push(0x00); // End
+    let length = package.len();
+    package[0] = length as u8;
+    package
+}
+
+// UEFI form package with an empty form set
+fn uefi_form_package() -> Vec<u8> {
+    let mut package = vec![0x00, 0x00, 0x00, 0x02]; // Length and type
+    package.extend_from_slice(&[0x0E, 0x97]); // FormSet with a scope
+    package.extend_from_slice(&[0x11; 16]); // Guid
+    package.extend_from_slice(&[0x01, 0x00, 0x01, 0x00, 0x00]); // FormSetTitle, Help and Flags
+    package.extend_from_slice(&[0x29, 0x02]); // End
+    let length = package.len();
+    package[0] = length as u8;
+    package
+}
+
+// Pseudo-random filler with packages scattered over it, so every image of the same size is identical
+fn synthetic_image(size: usize) -> Vec<u8> {
+    let packages = [uefi_string_package(), uefi_form_package()];
+    let mut image = Vec::with_capacity(size);
+    let mut state: u32 = 0x1234_5678;
+    while image.len() < size {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        image.push((state >> 16) as u8);
+        if image.len() % 0x10000 == 0 {
+            for package in &packages {
+                image.extend_from_slice(package);
+            }
+        }
+    }
+    image
+}
+
+// Candidate accepted by strict or relaxed heuristics
+type CandidateCheck = fn(&[u8], bool) -> Option<&[u8]>;
+
+// Discovery as done before the single pass: strict parsers at every offset, one sweep per package kind
+fn byte_by_byte(data: &[u8]) -> usize {
+    let scans: [CandidateCheck; 4] = [
+        uefi_parser::string_package_candidate,
+        uefi_parser::form_package_candidate,
+        framework_parser::string_package_candidate,
+        framework_parser::form_package_candidate,
+    ];
+    let mut found = 0;
+    for scan in &scans {
+        let mut i = 0;
+        while i < data.len() {
+            if let Some(candidate) = scan(&data[i..], false) {
+                found += 1;
+                i += candidate.len();
+            } else {
+                i += 1;
+            }
+        }
+    }
+    found
+}
+
+fn single_pass(data: &[u8]) -> usize {
+    let candidates = package_discovery::candidates(data, false);
+    candidates.uefi_strings.len()
+        + candidates.uefi_forms.len()
+        + candidates.framework_strings.len()
+        + candidates.framework_forms.len()
+}
+
+fn discovery(c: &mut Criterion) {
+    let mut group = c.benchmark_group("package_discovery");
+    for size in [4 << 20, 32 << 20].iter() {
+        let image = synthetic_image(*size);
+        assert_eq!(byte_by_byte(&image), single_pass(&image));
+        group.throughput(Throughput::Bytes(*size as u64));
+        group.bench_with_input(BenchmarkId::new("byte_by_byte", size >> 20), &image, |b, image| {
+            b.iter(|| byte_by_byte(black_box(image)))
+        });
+        group.bench_with_input(BenchmarkId::new("single_pass", size >> 20), &image, |b, image| {
+            b.iter(|| single_pass(black_box(image)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = discovery
+}
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+#![no_main]
+// HII package headers of both formats, and the package candidates the scanners accept
+use ifrextractor::{framework_parser, uefi_parser};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = uefi_parser::hii_package(data);
+    let _ = framework_parser::hii_package(data);
+    for relaxed in [false, true] {
+        let _ = uefi_parser::form_package_candidate(data, relaxed);
+        let _ = uefi_parser::string_package_candidate(data, relaxed);
+        let _ = framework_parser::form_package_candidate(data, relaxed);
+        let _ = framework_parser::string_package_candidate(data, relaxed);
+    }
+});
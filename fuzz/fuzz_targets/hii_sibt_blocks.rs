@@ -0,0 +1,21 @@
+#![no_main]
+// UEFI string blocks, and the strings inside every block that could be parsed
+use ifrextractor::uefi_parser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = uefi_parser::hii_string_package(data);
+    if let Ok((_, blocks)) = uefi_parser::hii_sibt_blocks(data) {
+        for block in &blocks {
+            let data = block.Data.unwrap_or_default();
+            let _ = uefi_parser::sibt_string_scsu(data);
+            let _ = uefi_parser::sibt_string_scsu_font(data);
+            let _ = uefi_parser::sibt_strings_scsu(data);
+            let _ = uefi_parser::sibt_strings_scsu_font(data);
+            let _ = uefi_parser::sibt_string_ucs2(data);
+            let _ = uefi_parser::sibt_string_ucs2_font(data);
+            let _ = uefi_parser::sibt_strings_ucs2(data);
+            let _ = uefi_parser::sibt_strings_ucs2_font(data);
+        }
+    }
+});
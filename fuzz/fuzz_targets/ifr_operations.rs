@@ -0,0 +1,16 @@
+#![no_main]
+// IFR operations of both formats, and the form set models built from them
+use ifrextractor::{framework_model, framework_parser, uefi_model, uefi_parser};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = uefi_parser::ifr_operations(data);
+    let parsed = uefi_parser::ifr_operations_resilient(data);
+    let offsets = parsed.file_offsets(0);
+    uefi_model::form_sets(&parsed.operations, &offsets);
+
+    let _ = framework_parser::ifr_operations(data);
+    let parsed = framework_parser::ifr_operations_resilient(data);
+    let offsets = parsed.file_offsets(0);
+    framework_model::form_sets(&parsed.operations, &offsets);
+});
@@ -0,0 +1,23 @@
+#![no_main]
+// Full extraction of UEFI HII data: package discovery in both modes, string and form packages,
+// and every form package rendered with every string package as text and JSON
+use ifrextractor::{ifr_extract, model_json, package_discovery};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    for relaxed in [false, true] {
+        let candidates = package_discovery::candidates(data, relaxed);
+        let (strings, forms) = ifr_extract::uefi_find_string_and_form_packages(&candidates);
+        for form in &forms {
+            let parsed = ifr_extract::uefi_parse_form_package(data, form);
+            for string in &strings {
+                for verbose in [false, true] {
+                    ifr_extract::uefi_ifr_extract(parsed.as_ref().ok(), string, verbose, &mut Vec::new());
+                }
+                if let Ok(parsed) = &parsed {
+                    model_json::form_sets_json(&parsed.form_sets, &string.string_id_map);
+                }
+            }
+        }
+    }
+});
@@ -0,0 +1,362 @@
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use error;
+use serde_json;
+use uefi_parser::Guid;
+
+//
+// Batch processing of many images
+//
+pub struct ImageRecord {
+    pub input: String,
+    pub output: String,
+    pub status: String, // "ok" or the reason nothing was extracted
+    pub exit_code: i32, // 0 if IFR data was extracted, otherwise the exit code for the failure
+    pub uefi_form_packages: usize,
+    pub framework_form_packages: usize,
+    pub languages: Vec<String>,
+    pub form_sets: Vec<(Guid, String)>, // GUID and title
+    pub variables: Vec<(String, Guid)>, // Varstore name and GUID
+}
+
+impl ImageRecord {
+    pub fn new(input: &Path, output: &Path) -> ImageRecord {
+        ImageRecord::failed(input, output, 0, "ok")
+    }
+
+    pub fn failed(input: &Path, output: &Path, exit_code: i32, status: &str) -> ImageRecord {
+        ImageRecord {
+            input: input.to_string_lossy().into_owned(),
+            output: output.to_string_lossy().into_owned(),
+            status: status.to_string(),
+            exit_code,
+            uefi_form_packages: 0,
+            framework_form_packages: 0,
+            languages: Vec::new(),
+            form_sets: Vec::new(),
+            variables: Vec::new(),
+        }
+    }
+}
+
+// Exit code of the whole batch, I/O errors take precedence over images without IFR data
+pub fn exit_code(records: &[ImageRecord]) -> i32 {
+    if records.iter().any(|r| r.exit_code == error::EXIT_IO) {
+        error::EXIT_IO
+    } else {
+        records.iter().map(|r| r.exit_code).find(|&code| code != 0).unwrap_or(0)
+    }
+}
+
+// Matches a file name against a pattern with * and ? wildcards
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|i| wildcard_match(rest, &name[i..])),
+        Some(('?', rest)) => !name.is_empty() && wildcard_match(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && wildcard_match(rest, &name[1..]),
+    }
+}
+
+fn walk_directory(directory: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            walk_directory(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+// Input file and the path of its output directory relative to the batch output directory
+#[derive(Debug, PartialEq)]
+pub struct Input {
+    pub path: PathBuf,
+    pub output_name: PathBuf, // Path below a given directory, or the file name for files and globs
+}
+
+fn file_input(path: PathBuf) -> Input {
+    let output_name = PathBuf::from(path.file_name().unwrap_or_else(|| OsStr::new("input")));
+    Input { path, output_name }
+}
+
+// Input files given as files, directories searched recursively, or globs with wildcards in the file name,
+// each file is used once even if it's given several times or by different paths
+pub fn expand_inputs(args: &[String]) -> Vec<Input> {
+    let mut inputs = Vec::new();
+    for arg in args {
+        let path = Path::new(arg);
+        if path.is_dir() {
+            let mut files = Vec::new();
+            walk_directory(path, &mut files);
+            for file in files {
+                let output_name = file.strip_prefix(path).unwrap_or(&file).to_path_buf();
+                inputs.push(Input { path: file, output_name });
+            }
+        } else if arg.contains('*') || arg.contains('?') {
+            let directory = match path.parent() {
+                Some(p) if !p.as_os_str().is_empty() => p,
+                _ => Path::new("."),
+            };
+            let pattern: Vec<char> = path
+                .file_name()
+                .map(|n| n.to_string_lossy().chars().collect())
+                .unwrap_or_default();
+            let mut matched: Vec<PathBuf> = fs::read_dir(directory)
+                .map(|entries| {
+                    entries
+                        .filter_map(|e| e.ok())
+                        .map(|e| e.path())
+                        .filter(|p| p.is_file())
+                        .filter(|p| {
+                            let name: Vec<char> = p.file_name().unwrap().to_string_lossy().chars().collect();
+                            wildcard_match(&pattern, &name)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            matched.sort();
+            inputs.extend(matched.into_iter().map(file_input));
+        } else {
+            inputs.push(file_input(path.to_path_buf()));
+        }
+    }
+
+    // Files that can't be canonicalized are kept by their given path, reading them reports the error
+    let mut seen = HashSet::new();
+    inputs.retain(|input| seen.insert(fs::canonicalize(&input.path).unwrap_or_else(|_| input.path.clone())));
+    inputs
+}
+
+// Paths of per-input output directories, inputs with the same path get a number appended
+pub fn output_names(inputs: &[Input]) -> Vec<PathBuf> {
+    let mut names: Vec<PathBuf> = Vec::new();
+    for input in inputs {
+        let mut name = input.output_name.clone();
+        let mut number = 2;
+        while names.contains(&name) {
+            let mut numbered = input.output_name.clone().into_os_string();
+            numbered.push(format!(".{number}"));
+            name = PathBuf::from(numbered);
+            number += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+// Runs a job for every item on all available threads, results are in the order of the items
+pub fn run_parallel<T: Sync, R: Send, F: Fn(&T) -> R + Sync>(items: &[T], job: F) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..items.len()).map(|_| None).collect());
+    let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(items.len());
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                if index >= items.len() {
+                    break;
+                }
+                let result = job(&items[index]);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    results.into_inner().unwrap().into_iter().flatten().collect()
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// One line per image, lists are separated by semicolons
+pub fn csv_index(records: &[ImageRecord]) -> String {
+    let mut csv = String::from("input,output,status,uefi_form_packages,framework_form_packages,languages,form_sets,variables\n");
+    for record in records {
+        let form_sets: Vec<String> = record
+            .form_sets
+            .iter()
+            .map(|(guid, title)| format!("{guid} {title}"))
+            .collect();
+        let variables: Vec<String> = record
+            .variables
+            .iter()
+            .map(|(name, guid)| format!("{name} {guid}"))
+            .collect();
+        writeln!(
+            &mut csv,
+            "{},{},{},{},{},{},{},{}",
+            csv_field(&record.input),
+            csv_field(&record.output),
+            csv_field(&record.status),
+            record.uefi_form_packages,
+            record.framework_form_packages,
+            csv_field(&record.languages.join(";")),
+            csv_field(&form_sets.join(";")),
+            csv_field(&variables.join(";"))
+        )
+        .unwrap();
+    }
+    csv
+}
+
+pub fn json_index(records: &[ImageRecord]) -> String {
+    let index: Vec<serde_json::Value> = records
+        .iter()
+        .map(|record| {
+            let form_sets: Vec<serde_json::Value> = record
+                .form_sets
+                .iter()
+                .map(|(guid, title)| json!({"guid": guid.to_string(), "title": title}))
+                .collect();
+            let variables: Vec<serde_json::Value> = record
+                .variables
+                .iter()
+                .map(|(name, guid)| json!({"name": name, "guid": guid.to_string()}))
+                .collect();
+            json!({
+                "input": record.input,
+                "output": record.output,
+                "status": record.status,
+                "uefi_form_packages": record.uefi_form_packages,
+                "framework_form_packages": record.framework_form_packages,
+                "languages": record.languages,
+                "form_sets": form_sets,
+                "variables": variables,
+            })
+        })
+        .collect();
+    let mut json = serde_json::to_string_pretty(&index).unwrap();
+    json.push('\n');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, name: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+        wildcard_match(&pattern, &name)
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(matches("*.bin", "image.bin"));
+        assert!(matches("*.bin", ".bin"));
+        assert!(!matches("*.bin", "image.bin.txt"));
+        assert!(matches("image?.*", "image1.rom"));
+        assert!(!matches("image?.*", "image.rom"));
+        assert!(matches("*a*b*", "xaybz"));
+        assert!(!matches("*a*b*", "xbya"));
+        assert!(matches("", ""));
+        assert!(!matches("", "a"));
+    }
+
+    #[test]
+    fn globs_match_file_names_in_their_directory() {
+        let dir = std::env::temp_dir().join(format!("ifrextractor-batch-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub.bin")).unwrap();
+        for name in ["b.bin", "a.bin", "a.rom", "sub.bin/c.bin"] {
+            fs::write(dir.join(name), []).unwrap();
+        }
+
+        let glob = dir.join("*.bin").to_string_lossy().into_owned();
+        let paths = |inputs: Vec<Input>| -> Vec<PathBuf> { inputs.into_iter().map(|i| i.path).collect() };
+        assert_eq!(paths(expand_inputs(std::slice::from_ref(&glob))), vec![dir.join("a.bin"), dir.join("b.bin")]);
+        // Directories are searched recursively, files given again by any path are kept once
+        let directory = dir.to_string_lossy().into_owned();
+        let file = dir.join("sub.bin/../a.rom").to_string_lossy().into_owned();
+        let inputs = expand_inputs(&[directory, glob, file]);
+        assert_eq!(
+            paths(inputs),
+            vec![dir.join("a.bin"), dir.join("a.rom"), dir.join("b.bin"), dir.join("sub.bin/c.bin")]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn output_names_are_unique() {
+        let inputs: Vec<Input> = ["a/bios.bin", "b/bios.bin", "c/bios.bin"]
+            .iter()
+            .map(|p| file_input(PathBuf::from(p)))
+            .collect();
+        assert_eq!(
+            output_names(&inputs),
+            vec![PathBuf::from("bios.bin"), PathBuf::from("bios.bin.2"), PathBuf::from("bios.bin.3")]
+        );
+    }
+
+    #[test]
+    fn directories_are_mirrored() {
+        let dir = std::env::temp_dir().join(format!("ifrextractor-mirror-{}", std::process::id()));
+        fs::create_dir_all(dir.join("vendor/board")).unwrap();
+        for name in ["bios.bin", "vendor/board/bios.bin"] {
+            fs::write(dir.join(name), []).unwrap();
+        }
+
+        let inputs = expand_inputs(&[dir.to_string_lossy().into_owned()]);
+        assert_eq!(
+            output_names(&inputs),
+            vec![PathBuf::from("bios.bin"), PathBuf::from("vendor/board/bios.bin")]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn batch_exit_code() {
+        let record = |exit_code| ImageRecord::failed(Path::new("in"), Path::new("out"), exit_code, "");
+        assert_eq!(exit_code(&[record(0), record(0)]), 0);
+        assert_eq!(exit_code(&[record(0), record(error::EXIT_NO_IFR)]), error::EXIT_NO_IFR);
+        assert_eq!(
+            exit_code(&[record(error::EXIT_NO_IFR), record(error::EXIT_IO), record(0)]),
+            error::EXIT_IO
+        );
+    }
+
+    #[test]
+    fn json_index_round_trips() {
+        let mut record = ImageRecord::new(Path::new("dir/say \"hi\".bin"), Path::new("out"));
+        let guid = Guid {
+            data1: 0xF0,
+            data2: 0,
+            data3: 0,
+            data4: [0; 8],
+        };
+        record.form_sets.push((guid, String::from("Main\tSetup")));
+        let index: serde_json::Value = serde_json::from_str(&json_index(&[record])).unwrap();
+        assert_eq!(index[0]["input"], "dir/say \"hi\".bin");
+        assert_eq!(index[0]["status"], "ok");
+        assert_eq!(index[0]["form_sets"][0]["title"], "Main\tSetup");
+        assert_eq!(index[0]["variables"], json!([]));
+    }
+
+    #[test]
+    fn csv_fields_are_quoted() {
+        assert_eq!(csv_field("Setup"), "Setup");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt;
+
+//
+// Parse errors
+//
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ParseErrorKind {
+    Truncated { needed: usize, available: usize }, // Not enough bytes left for the field
+    InvalidValue,                                  // The field has a value the parser doesn't accept
+}
+
+// Field that can't be parsed, offset is relative to the start of the parsed input
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct ParseError {
+    pub offset: usize,
+    pub field: &'static str,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::Truncated { needed, available } => write!(
+                f,
+                "{} at offset 0x{:X} needs {} byte(s), {} left",
+                self.field, self.offset, needed, available
+            ),
+            ParseErrorKind::InvalidValue => write!(f, "{} at offset 0x{:X} has an invalid value", self.field, self.offset),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Parsed value and the input left after it
+pub type IResult<I, O> = Result<(I, O), ParseError>;
+
+//
+// Zero-copy little-endian reader
+//
+pub struct Reader<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(input: &'a [u8]) -> Reader<'a> {
+        Reader { input, position: 0 }
+    }
+
+    // Offset of the next field from the start of the input
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // Bytes left without consuming them
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.input[self.position..]
+    }
+
+    pub fn bytes(&mut self, length: usize, field: &'static str) -> Result<&'a [u8], ParseError> {
+        let available = self.input.len() - self.position;
+        if length > available {
+            return Err(ParseError {
+                offset: self.position,
+                field,
+                kind: ParseErrorKind::Truncated {
+                    needed: length,
+                    available,
+                },
+            });
+        }
+        let bytes = &self.input[self.position..self.position + length];
+        self.position += length;
+        Ok(bytes)
+    }
+
+    // All bytes left
+    pub fn rest(&mut self) -> &'a [u8] {
+        let rest = self.remaining();
+        self.position = self.input.len();
+        rest
+    }
+
+    pub fn u8(&mut self, field: &'static str) -> Result<u8, ParseError> {
+        Ok(self.bytes(1, field)?[0])
+    }
+
+    pub fn u16(&mut self, field: &'static str) -> Result<u16, ParseError> {
+        let b = self.bytes(2, field)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    pub fn u32(&mut self, field: &'static str) -> Result<u32, ParseError> {
+        let b = self.bytes(4, field)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub fn u64(&mut self, field: &'static str) -> Result<u64, ParseError> {
+        let b = self.bytes(8, field)?;
+        Ok(u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+    }
+
+    // Bytes up to a zero byte, the zero byte is consumed but not returned
+    pub fn zero_terminated(&mut self, field: &'static str) -> Result<&'a [u8], ParseError> {
+        match self.remaining().iter().position(|&b| b == 0) {
+            Some(length) => {
+                let bytes = self.bytes(length, field)?;
+                self.position += 1;
+                Ok(bytes)
+            }
+            None => Err(ParseError {
+                offset: self.position,
+                field,
+                kind: ParseErrorKind::Truncated {
+                    needed: self.input.len() - self.position + 1,
+                    available: self.input.len() - self.position,
+                },
+            }),
+        }
+    }
+
+    // Value that was just read by a field starting at the given offset, if it's accepted
+    pub fn verify<T>(&self, start: usize, field: &'static str, value: T, valid: bool) -> Result<T, ParseError> {
+        if valid {
+            Ok(value)
+        } else {
+            Err(ParseError {
+                offset: start,
+                field,
+                kind: ParseErrorKind::InvalidValue,
+            })
+        }
+    }
+
+    // Structure read by another parser, its error offsets become relative to this input
+    pub fn parse<T>(&mut self, parser: fn(&'a [u8]) -> IResult<&'a [u8], T>) -> Result<T, ParseError> {
+        match parser(self.remaining()) {
+            Ok((rest, value)) => {
+                self.position = self.input.len() - rest.len();
+                Ok(value)
+            }
+            Err(e) => Err(ParseError {
+                offset: self.position + e.offset,
+                ..e
+            }),
+        }
+    }
+
+    // Field that is only present if the condition is met
+    pub fn cond<T>(
+        &mut self,
+        condition: bool,
+        read: impl FnOnce(&mut Reader<'a>) -> Result<T, ParseError>,
+    ) -> Result<Option<T>, ParseError> {
+        if condition {
+            read(self).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    // Fixed number of fields of the same kind
+    pub fn count<T>(
+        &mut self,
+        count: usize,
+        mut read: impl FnMut(&mut Reader<'a>) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut values = Vec::new();
+        for _ in 0..count {
+            values.push(read(self)?);
+        }
+        Ok(values)
+    }
+
+    // One or more structures read by another parser, up to the first one that can't be parsed
+    pub fn many1<T>(&mut self, parser: fn(&'a [u8]) -> IResult<&'a [u8], T>) -> Result<Vec<T>, ParseError> {
+        let mut values = vec![self.parse(parser)?];
+        while self.position < self.input.len() {
+            let position = self.position;
+            match self.parse(parser) {
+                Ok(value) if self.position > position => values.push(value),
+                _ => break,
+            }
+        }
+        Ok(values)
+    }
+
+    pub fn finish<T>(self, value: T) -> IResult<&'a [u8], T> {
+        Ok((self.remaining(), value))
+    }
+}
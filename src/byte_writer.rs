@@ -0,0 +1,83 @@
+use std::fmt;
+
+//
+// Encode errors
+//
+// Length or count that doesn't fit into the field that stores it
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct EncodeError {
+    pub field: &'static str,
+    pub value: usize,
+    pub max: usize,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} of {} doesn't fit, at most {} is possible", self.field, self.value, self.max)
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+// Encoded bytes of a structure
+pub type EncodeResult = Result<Vec<u8>, EncodeError>;
+
+//
+// Little-endian writer
+//
+#[derive(Default)]
+pub struct Writer {
+    output: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new() -> Writer {
+        Writer { output: Vec::new() }
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) {
+        self.output.extend_from_slice(bytes);
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.output.push(value);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    // Bytes followed by a zero byte
+    pub fn zero_terminated(&mut self, bytes: &[u8]) {
+        self.bytes(bytes);
+        self.u8(0);
+    }
+
+    // Length or count to be stored in a field that can hold at most the given value
+    pub fn length(&self, field: &'static str, value: usize, max: usize) -> Result<usize, EncodeError> {
+        if value <= max {
+            Ok(value)
+        } else {
+            Err(EncodeError { field, value, max })
+        }
+    }
+
+    // Structure written by another encoder
+    pub fn encode<T: ?Sized>(&mut self, encoder: fn(&T) -> EncodeResult, value: &T) -> Result<(), EncodeError> {
+        let bytes = encoder(value)?;
+        self.bytes(&bytes);
+        Ok(())
+    }
+
+    pub fn finish(self) -> EncodeResult {
+        Ok(self.output)
+    }
+}
@@ -0,0 +1,233 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use clap;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+//
+// Command line interface
+//
+#[derive(Parser)]
+#[command(
+    name = "ifrextractor",
+    version,
+    about = "Extracts HII string and form packages in UEFI Internal Form Representation (IFR) from a binary file into human-readable text",
+    after_help = "The positional syntax of older versions is still accepted, i.e. \"ifrextractor file.bin\", \"ifrextractor file.bin verbose\", \"ifrextractor file.bin lang <language>\", \"ifrextractor file.bin single <form_package_number> <string_package_number>\" and \"ifrextractor file.bin <command> [arguments]\"\n\nExit codes: 0 success, 1 no arguments, 2 no IFR data found, 3 not enough IFR data to complete the command, 4 invalid arguments, 5 lint errors or settings that can't be applied, 6 input or output file can't be read or written"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+    #[arg(
+        long,
+        global = true,
+        help = "Also accept packages with padding or operations after the final End, or non-standard string package header sizes"
+    )]
+    pub relaxed: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    #[command(about = "Extract form packages into human-readable text or JSON")]
+    Extract(ExtractArgs),
+    #[command(about = "List all string and form packages in the input file")]
+    List(InputArgs),
+    #[command(about = "Report questions sharing storage in the same varstore and their conflicts")]
+    Overlaps(InputArgs),
+    #[command(about = "Show byte-level layout maps and approximate C structs of all varstores with known size")]
+    Layout(InputArgs),
+    #[command(about = "Merge varstores with the same name and GUID from all form packages and list their questions")]
+    Varstores(InputArgs),
+    #[command(about = "Evaluate conditions of all questions using default values or given variable files")]
+    Visibility(VariablesArgs),
+    #[command(about = "List questions that are always suppressed, placed in forms no Ref leads to, or hidden at defaults")]
+    Hidden(InputArgs),
+    #[command(about = "Print a graph of all forms linked by Refs, forms no Ref leads to are highlighted")]
+    Graph(GraphArgs),
+    #[command(about = "Show the setup menu as a tree following Refs, with current values from given variable files or defaults")]
+    Tree(VariablesArgs),
+    #[command(about = "Browse form sets interactively like a setup screen")]
+    Browse(VariablesArgs),
+    #[command(about = "Search prompts, help strings and option strings of all questions in all languages")]
+    Search(SearchArgs),
+    #[command(about = "Check IFR consistency and print issues as file:offset: severity[check]: message, exits with 5 on errors")]
+    Lint(InputArgs),
+    #[command(about = "Summarize packages, languages, opcodes, questions, varstores and parse errors of the input file")]
+    Stats(InputArgs),
+    #[command(about = "Apply a TOML or JSON settings profile to variable files, written as *.modified")]
+    Apply(ApplyArgs),
+    #[command(about = "Compare settings of two images, matched by varstore storage and prompt")]
+    Diff(DiffArgs),
+    #[command(about = "Extract all images in parallel into per-file subdirectories, mirroring given directories, and write an index of all of them")]
+    Batch(BatchArgs),
+    #[command(about = "List package candidates rejected by strict discovery and how they differ from accepted ones")]
+    Candidates(InputArgs),
+}
+
+#[derive(Args)]
+pub struct InputArgs {
+    #[arg(help = "Input file")]
+    pub file: PathBuf,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ExtractFormat {
+    Text,
+    Json,
+}
+
+#[derive(Args)]
+pub struct ExtractArgs {
+    #[arg(help = "Input file")]
+    pub file: PathBuf,
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with = "stdout",
+        help = "Write output files into DIR instead of next to the input file"
+    )]
+    pub output_dir: Option<PathBuf>,
+    #[arg(long, help = "Write output to stdout instead of files, progress messages go to stderr")]
+    pub stdout: bool,
+    #[arg(long, value_enum, default_value = "text", help = "Output format, JSON contains form set models")]
+    pub format: ExtractFormat,
+    #[arg(
+        long = "lang",
+        value_name = "LANGUAGE",
+        help = "Use string packages in this language, can be repeated [default: en-US and eng]"
+    )]
+    pub languages: Vec<String>,
+    #[arg(long, conflicts_with = "languages", help = "Use string packages in all languages")]
+    pub all_languages: bool,
+    #[arg(long, help = "Add raw bytes to all opcodes of text output")]
+    pub verbose: bool,
+    #[arg(long, value_name = "INDEX", help = "Extract only the form package with this index (use list command to obtain it)")]
+    pub form_package: Option<usize>,
+    #[arg(
+        long,
+        value_name = "INDEX",
+        conflicts_with_all = ["languages", "all_languages"],
+        help = "Use only the string package with this index (use list command to obtain it)"
+    )]
+    pub string_package: Option<usize>,
+    #[arg(long, conflicts_with = "framework_only", help = "Extract only UEFI HII form packages")]
+    pub uefi_only: bool,
+    #[arg(long, help = "Extract only Framework HII form packages")]
+    pub framework_only: bool,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write parse errors of all packages into FILE as JSON instead of printing them to stderr"
+    )]
+    pub diagnostics: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct VariablesArgs {
+    #[arg(help = "Input file")]
+    pub file: PathBuf,
+    #[arg(
+        value_name = "VARIABLE",
+        help = "Variable files as VarName=var.bin or var.bin, use =var.bin for the NV data of a Framework form set"
+    )]
+    pub variables: Vec<String>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+#[derive(Args)]
+pub struct GraphArgs {
+    #[arg(help = "Input file")]
+    pub file: PathBuf,
+    #[arg(long, value_enum, default_value = "dot", help = "Graphviz DOT or Mermaid")]
+    pub format: GraphFormat,
+}
+
+#[derive(Args)]
+pub struct SearchArgs {
+    #[arg(help = "Input file")]
+    pub file: PathBuf,
+    #[arg(short = 'i', long, help = "Ignore case")]
+    pub ignore_case: bool,
+    #[arg(allow_hyphen_values = true, help = "Regular expression")]
+    pub pattern: String,
+}
+
+#[derive(Args)]
+pub struct ApplyArgs {
+    #[arg(help = "Input file")]
+    pub file: PathBuf,
+    #[arg(help = "TOML or JSON settings profile")]
+    pub profile: PathBuf,
+    #[arg(required = true, value_name = "VARIABLE", help = "Variable files as VarName=var.bin or var.bin")]
+    pub variables: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct DiffArgs {
+    #[arg(help = "Old input file")]
+    pub old: PathBuf,
+    #[arg(help = "New input file")]
+    pub new: PathBuf,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum IndexFormat {
+    Csv,
+    Json,
+    Both,
+}
+
+#[derive(Args)]
+pub struct BatchArgs {
+    #[arg(help = "Output directory")]
+    pub output_dir: PathBuf,
+    #[arg(required = true, value_name = "INPUT", help = "Files, directories searched recursively, or globs with * and ? in file names")]
+    pub inputs: Vec<String>,
+    #[arg(long, value_enum, default_value = "both", help = "Format of the index file")]
+    pub format: IndexFormat,
+}
+
+const COMMANDS: [&str; 18] = [
+    "extract", "list", "overlaps", "layout", "varstores", "visibility", "hidden", "graph", "tree", "browse", "search",
+    "lint", "stats", "apply", "diff", "batch", "candidates", "help",
+];
+
+// Translates the positional syntax of older versions, "file.bin [mode] [arguments]", into subcommands
+pub fn legacy_args(args: Vec<OsString>) -> Vec<OsString> {
+    let first = match args.get(1).and_then(|a| a.to_str()) {
+        Some(first) => first,
+        None if args.len() > 1 => "", // Not valid UTF-8, can only be a file name
+        None => return args,
+    };
+    if first.starts_with('-') || COMMANDS.contains(&first) {
+        return args;
+    }
+
+    let mode = args.get(2).and_then(|a| a.to_str()).map(|s| s.to_string());
+    let (command, flag) = match mode.as_deref() {
+        None => ("extract", None),
+        Some("verbose") => ("extract", Some("--verbose")),
+        Some("all") => ("extract", Some("--all-languages")),
+        Some("lang") => ("extract", Some("--lang")),
+        Some("single") => ("extract", None),
+        Some("graph") if args.len() > 3 => ("graph", Some("--format")),
+        Some(mode) => (mode, None),
+    };
+
+    let mut result = vec![args[0].clone(), OsString::from(command), args[1].clone()];
+    let rest = if args.len() > 2 { &args[3..] } else { &args[2..] };
+    if mode.as_deref() == Some("single") && rest.len() == 2 {
+        result.push(OsString::from("--form-package"));
+        result.push(rest[0].clone());
+        result.push(OsString::from("--string-package"));
+        result.push(rest[1].clone());
+        return result;
+    }
+    result.extend(flag.map(OsString::from));
+    result.extend(rest.iter().cloned());
+    result
+}
@@ -0,0 +1,164 @@
+use std::error;
+use std::fmt;
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json;
+
+//
+// Errors and diagnostics
+//
+// Exit codes
+pub const EXIT_USAGE: i32 = 1; // No arguments given
+pub const EXIT_NO_IFR: i32 = 2; // No string or form packages found in the input file
+pub const EXIT_NOT_ENOUGH_DATA: i32 = 3; // Packages found, but not enough of them to complete the command
+pub const EXIT_INVALID_ARGUMENTS: i32 = 4;
+pub const EXIT_VALIDATION: i32 = 5; // Lint errors or a settings profile that can't be applied
+pub const EXIT_IO: i32 = 6; // Input or output file can't be read or written
+
+#[derive(Debug)]
+pub enum Error {
+    Io { path: PathBuf, error: io::Error },
+    NoIfrData, // No string or form packages found in the input file
+    InvalidPackage { offset: usize, length: usize }, // Package header that can't be parsed
+    TruncatedPackage { offset: usize, length: usize }, // Bytes at the end of a form package that can't be parsed into operations
+    DamagedData { offset: usize, length: usize },      // Bytes skipped to the next plausible operation
+    MalformedOpcode { offset: usize, opcode: String, message: String },
+    UnknownOpcode { offset: usize, opcode: u8 },
+    MissingString { offset: usize, string_id: u16 }, // StringId used by an operation is not in the string package
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn io(path: &Path, error: io::Error) -> Error {
+        Error::Io {
+            path: path.to_path_buf(),
+            error,
+        }
+    }
+
+    // Offset in the input file, None for errors not related to its data
+    pub fn offset(&self) -> Option<usize> {
+        match *self {
+            Error::Io { .. } | Error::NoIfrData => None,
+            Error::InvalidPackage { offset, .. }
+            | Error::TruncatedPackage { offset, .. }
+            | Error::DamagedData { offset, .. }
+            | Error::MalformedOpcode { offset, .. }
+            | Error::UnknownOpcode { offset, .. }
+            | Error::MissingString { offset, .. } => Some(offset),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            Error::Io { .. } => "io",
+            Error::NoIfrData => "no-ifr-data",
+            Error::InvalidPackage { .. } => "invalid-package",
+            Error::TruncatedPackage { .. } => "truncated-package",
+            Error::DamagedData { .. } => "damaged-data",
+            Error::MalformedOpcode { .. } => "malformed-opcode",
+            Error::UnknownOpcode { .. } => "unknown-opcode",
+            Error::MissingString { .. } => "missing-string",
+        }
+    }
+
+    // Exit code of the command that fails with this error
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            Error::Io { .. } => EXIT_IO,
+            Error::NoIfrData => EXIT_NO_IFR,
+            _ => EXIT_NOT_ENOUGH_DATA,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, error } => write!(f, "{}: {}", path.to_string_lossy(), error),
+            Error::NoIfrData => write!(f, "No IFR data found"),
+            Error::InvalidPackage { length, .. } => write!(f, "Package header of {length} byte(s) can't be parsed"),
+            Error::TruncatedPackage { length, .. } => {
+                write!(f, "{length} byte(s) of the form package can't be parsed as IFR operations")
+            }
+            Error::DamagedData { length, .. } => {
+                write!(f, "{length} byte(s) can't be parsed as IFR operations and were skipped")
+            }
+            Error::MalformedOpcode { opcode, message, .. } => write!(f, "{opcode} parse error: {message}"),
+            Error::UnknownOpcode { opcode, .. } => write!(f, "Unknown opcode 0x{opcode:X}"),
+            Error::MissingString { string_id, .. } => {
+                write!(f, "StringId 0x{string_id:X} is missing from the string package")
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// Errors found while extracting one form package using one string package
+pub struct Diagnostics {
+    pub format: &'static str, // UEFI or Framework
+    pub form_package_offset: usize,
+    pub string_package_offset: usize,
+    pub language: String,
+    pub errors: Vec<Error>,
+}
+
+// One line per error as path:offset: error[kind]: message
+pub fn diagnostics_text(path: &str, diagnostics: &Diagnostics) -> String {
+    let mut text = String::new();
+    for error in &diagnostics.errors {
+        write!(
+            &mut text,
+            "{}:0x{:X}: error[{}]: {}",
+            path,
+            error.offset().unwrap_or(diagnostics.form_package_offset),
+            error.kind(),
+            error
+        )
+        .unwrap();
+        if let Error::MissingString { .. } = error {
+            write!(
+                &mut text,
+                " ({} string package at 0x{:X})",
+                diagnostics.language, diagnostics.string_package_offset
+            )
+            .unwrap();
+        }
+        writeln!(&mut text).unwrap();
+    }
+    text
+}
+
+pub fn diagnostics_json(path: &str, diagnostics: &[Diagnostics]) -> String {
+    let packages: Vec<serde_json::Value> = diagnostics
+        .iter()
+        .map(|package| {
+            let errors: Vec<serde_json::Value> = package
+                .errors
+                .iter()
+                .map(|e| json!({"kind": e.kind(), "offset": e.offset(), "message": e.to_string()}))
+                .collect();
+            json!({
+                "format": package.format,
+                "form_package_offset": package.form_package_offset,
+                "string_package_offset": package.string_package_offset,
+                "language": package.language,
+                "errors": errors,
+            })
+        })
+        .collect();
+    let mut json = serde_json::to_string_pretty(&json!({"input": path, "packages": packages})).unwrap();
+    json.push('\n');
+    json
+}
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use form_links;
+use form_links::Link;
+use uefi_model::FormSet;
+
+//
+// Form navigation graphs in Graphviz DOT and Mermaid formats
+//
+struct Graph<'a> {
+    links: Vec<Link<'a>>,
+    reachable: Vec<HashSet<u16>>,
+}
+
+fn graph<'a>(form_sets: &'a [(FormSet, &HashMap<u16, String>)]) -> Graph<'a> {
+    let models: Vec<&FormSet> = form_sets.iter().map(|(form_set, _)| form_set).collect();
+    let links = form_links::links(&models);
+    let reachable = form_links::reachable_forms(&models, &links, &|_| true);
+    Graph {
+        links,
+        reachable,
+    }
+}
+
+fn string(strings_map: &HashMap<u16, String>, string_id: u16) -> &str {
+    strings_map
+        .get(&string_id)
+        .map(|s| s.as_str())
+        .unwrap_or("InvalidId")
+}
+
+fn node_name(form_set_index: usize, form_id: u16) -> String {
+    format!("fs{form_set_index}_form{form_id:X}")
+}
+
+// Description of a Ref destination that isn't a known form
+fn unresolved_text(link: &Link, strings_map: &HashMap<u16, String>) -> String {
+    let target = match link.question.ref_target {
+        Some(ref t) => t,
+        None => return String::from("Unknown destination"),
+    };
+    let mut parts = Vec::new();
+    if let Some(device_path_id) = target.device_path_id {
+        if device_path_id != 0 {
+            parts.push(format!("DevicePath: {}", string(strings_map, device_path_id)));
+        }
+    }
+    if let Some(guid) = target.form_set_guid {
+        parts.push(format!("FormSet: {guid}"));
+    }
+    match target.form_id {
+        Some(form_id) => parts.push(format!("FormId: 0x{form_id:X}")),
+        None => parts.push(String::from("FormId from value")),
+    }
+    parts.join("\n")
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+pub fn dot_graph(form_sets: &[(FormSet, &HashMap<u16, String>)]) -> String {
+    let graph = graph(form_sets);
+    let mut text = String::new();
+    writeln!(&mut text, "digraph Forms {{").unwrap();
+    writeln!(&mut text, "  rankdir=LR;").unwrap();
+    writeln!(&mut text, "  node [shape=box];").unwrap();
+
+    for (index, (form_set, strings_map)) in form_sets.iter().enumerate() {
+        writeln!(&mut text, "  subgraph cluster_fs{index} {{").unwrap();
+        writeln!(
+            &mut text,
+            "    label=\"{}\";",
+            dot_escape(&format!("{}\n{}", string(strings_map, form_set.title_string_id), form_set.guid))
+        )
+        .unwrap();
+        for (form_index, form) in form_set.forms.iter().enumerate() {
+            let mut attributes = vec![format!(
+                "label=\"{}\"",
+                dot_escape(&format!("{}\nFormId: 0x{:X}", string(strings_map, form.title_string_id), form.form_id))
+            )];
+            if form_index == 0 {
+                attributes.push(String::from("penwidth=2"));
+            }
+            if !graph.reachable[index].contains(&form.form_id) {
+                attributes.push(String::from("style=\"filled,dashed\", fillcolor=\"#ffcccc\", color=red"));
+            }
+            writeln!(&mut text, "    {} [{}];", node_name(index, form.form_id), attributes.join(", ")).unwrap();
+        }
+        writeln!(&mut text, "  }}").unwrap();
+    }
+
+    for (link_index, link) in graph.links.iter().enumerate() {
+        let strings_map = form_sets[link.form_set_index].1;
+        let from = node_name(link.form_set_index, link.form_id);
+        let label = dot_escape(string(strings_map, link.question.prompt_string_id));
+        match link.target {
+            Some((form_set_index, form_id)) => writeln!(
+                &mut text,
+                "  {} -> {} [label=\"{}\"];",
+                from,
+                node_name(form_set_index, form_id),
+                label
+            )
+            .unwrap(),
+            None => {
+                writeln!(
+                    &mut text,
+                    "  unresolved{} [label=\"{}\", shape=note, style=dashed];",
+                    link_index,
+                    dot_escape(&unresolved_text(link, strings_map))
+                )
+                .unwrap();
+                writeln!(
+                    &mut text,
+                    "  {from} -> unresolved{link_index} [label=\"{label}\", style=dashed];"
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    writeln!(&mut text, "}}").unwrap();
+    text
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', "<br/>")
+}
+
+pub fn mermaid_graph(form_sets: &[(FormSet, &HashMap<u16, String>)]) -> String {
+    let graph = graph(form_sets);
+    let mut text = String::new();
+    let mut unreachable = Vec::new();
+    writeln!(&mut text, "flowchart LR").unwrap();
+
+    for (index, (form_set, strings_map)) in form_sets.iter().enumerate() {
+        writeln!(
+            &mut text,
+            "  subgraph fs{}[\"{}\"]",
+            index,
+            mermaid_escape(&format!("{}\n{}", string(strings_map, form_set.title_string_id), form_set.guid))
+        )
+        .unwrap();
+        for form in &form_set.forms {
+            let name = node_name(index, form.form_id);
+            writeln!(
+                &mut text,
+                "    {}[\"{}\"]",
+                name,
+                mermaid_escape(&format!("{}\nFormId: 0x{:X}", string(strings_map, form.title_string_id), form.form_id))
+            )
+            .unwrap();
+            if !graph.reachable[index].contains(&form.form_id) {
+                unreachable.push(name);
+            }
+        }
+        writeln!(&mut text, "  end").unwrap();
+    }
+
+    for (link_index, link) in graph.links.iter().enumerate() {
+        let strings_map = form_sets[link.form_set_index].1;
+        let from = node_name(link.form_set_index, link.form_id);
+        let label = mermaid_escape(string(strings_map, link.question.prompt_string_id));
+        match link.target {
+            Some((form_set_index, form_id)) => writeln!(
+                &mut text,
+                "  {} -->|\"{}\"| {}",
+                from,
+                label,
+                node_name(form_set_index, form_id)
+            )
+            .unwrap(),
+            None => writeln!(
+                &mut text,
+                "  {} -.->|\"{}\"| unresolved{}[/\"{}\"/]",
+                from,
+                label,
+                link_index,
+                mermaid_escape(&unresolved_text(link, strings_map))
+            )
+            .unwrap(),
+        }
+    }
+
+    if !unreachable.is_empty() {
+        writeln!(&mut text, "  classDef unreachable fill:#ffcccc,stroke:#cc0000,stroke-dasharray:5 5").unwrap();
+        writeln!(&mut text, "  class {} unreachable", unreachable.join(",")).unwrap();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uefi_model::test_models::{form, form_set, guid, reference};
+
+    // Forms 1 and 2 link to each other and form 2 to itself, form 3 is unreachable,
+    // form 1 also links to the second form set and to a form that doesn't exist
+    fn form_sets() -> Vec<FormSet> {
+        let mut to_other = reference(0x13, 1, 0);
+        to_other.ref_target.as_mut().unwrap().form_set_guid = Some(guid(0xF1));
+        let mut first = form_set(
+            Vec::new(),
+            vec![
+                reference(0x10, 1, 2),
+                reference(0x11, 2, 1),
+                reference(0x12, 2, 2),
+                to_other,
+                reference(0x14, 1, 9),
+            ],
+        );
+        first.forms.extend(vec![form(2, 3), form(3, 4)]);
+        let mut second = form_set(Vec::new(), Vec::new());
+        second.guid = guid(0xF1);
+        second.forms = vec![form(5, 5)];
+        vec![first, second]
+    }
+
+    fn strings() -> HashMap<u16, String> {
+        vec![(1, "Main"), (2, "Advanced"), (3, "Boot"), (4, "Debug"), (5, "Other"), (0x10, "Go \"Boot\"")]
+            .into_iter()
+            .map(|(id, s)| (id, s.to_string()))
+            .collect()
+    }
+
+    fn graph_lines(mermaid: bool) -> Vec<String> {
+        let strings = strings();
+        let form_sets: Vec<(FormSet, &HashMap<u16, String>)> = form_sets().into_iter().map(|fs| (fs, &strings)).collect();
+        let graph = if mermaid { mermaid_graph(&form_sets) } else { dot_graph(&form_sets) };
+        graph.lines().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn dot_graph_with_cycles() {
+        let lines = graph_lines(false);
+        assert_eq!(
+            lines[5..8].to_vec(),
+            vec![
+                "    fs0_form1 [label=\"Advanced\\nFormId: 0x1\", penwidth=2];",
+                "    fs0_form2 [label=\"Boot\\nFormId: 0x2\"];",
+                "    fs0_form3 [label=\"Debug\\nFormId: 0x3\", style=\"filled,dashed\", fillcolor=\"#ffcccc\", color=red];",
+            ]
+        );
+        assert_eq!(
+            lines[13..].to_vec(),
+            vec![
+                "  fs0_form1 -> fs0_form2 [label=\"Go \\\"Boot\\\"\"];",
+                "  fs0_form2 -> fs0_form1 [label=\"InvalidId\"];",
+                "  fs0_form2 -> fs0_form2 [label=\"InvalidId\"];",
+                "  fs0_form1 -> fs1_form5 [label=\"InvalidId\"];",
+                "  unresolved4 [label=\"FormId: 0x9\", shape=note, style=dashed];",
+                "  fs0_form1 -> unresolved4 [label=\"InvalidId\", style=dashed];",
+                "}",
+            ]
+        );
+    }
+
+    #[test]
+    fn mermaid_graph_with_cycles() {
+        let lines = graph_lines(true);
+        assert_eq!(
+            lines[9..].to_vec(),
+            vec![
+                "  fs0_form1 -->|\"Go #quot;Boot#quot;\"| fs0_form2",
+                "  fs0_form2 -->|\"InvalidId\"| fs0_form1",
+                "  fs0_form2 -->|\"InvalidId\"| fs0_form2",
+                "  fs0_form1 -->|\"InvalidId\"| fs1_form5",
+                "  fs0_form1 -.->|\"InvalidId\"| unresolved4[/\"FormId: 0x9\"/]",
+                "  classDef unreachable fill:#ffcccc,stroke:#cc0000,stroke-dasharray:5 5",
+                "  class fs0_form3 unreachable",
+            ]
+        );
+    }
+}
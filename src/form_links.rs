@@ -0,0 +1,124 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use uefi_model::{FormSet, Question, QuestionKind};
+use uefi_parser::Guid;
+
+//
+// Links between forms made by Ref questions
+//
+pub struct Link<'a> {
+    pub form_set_index: usize, // Index of the form set containing the Ref
+    pub form_id: u16,          // Form containing the Ref
+    pub question: &'a Question,
+    pub target: Option<(usize, u16)>, // Form set index and FormId of the destination, None if it can't be resolved
+}
+
+const ZERO_GUID: Guid = Guid {
+    data1: 0,
+    data2: 0,
+    data3: 0,
+    data4: [0; 8],
+};
+
+// Destination of a Ref, form sets referenced by GUID are searched in all given form sets
+fn resolve(form_sets: &[&FormSet], form_set_index: usize, question: &Question) -> Option<(usize, u16)> {
+    let target = question.ref_target.as_ref()?;
+    let form_id = target.form_id?;
+    let index = match target.form_set_guid {
+        Some(guid) if guid != ZERO_GUID && guid != form_sets[form_set_index].guid => {
+            form_sets.iter().position(|fs| fs.guid == guid)?
+        }
+        _ => form_set_index,
+    };
+    // FormId 0 in a Ref to another form set means its first form
+    let form_id = if form_id == 0 && index != form_set_index {
+        form_sets[index].forms.first()?.form_id
+    } else {
+        form_id
+    };
+    form_sets[index].form(form_id)?;
+    Some((index, form_id))
+}
+
+pub fn links<'a>(form_sets: &[&'a FormSet]) -> Vec<Link<'a>> {
+    let mut result = Vec::new();
+    for (form_set_index, form_set) in form_sets.iter().enumerate() {
+        for question in form_set.questions.iter().filter(|q| q.kind == QuestionKind::Ref) {
+            result.push(Link {
+                form_set_index,
+                form_id: question.form_id,
+                question,
+                target: resolve(form_sets, form_set_index, question),
+            });
+        }
+    }
+    result
+}
+
+// Forms of every form set that can be reached from the first form of any form set
+// using the links accepted by the given filter
+pub fn reachable_forms(form_sets: &[&FormSet], links: &[Link], follow: &dyn Fn(&Link) -> bool) -> Vec<HashSet<u16>> {
+    let mut reachable: Vec<HashSet<u16>> = vec![HashSet::new(); form_sets.len()];
+    let mut pending: Vec<(usize, u16)> = Vec::new();
+    for (index, form_set) in form_sets.iter().enumerate() {
+        if let Some(form) = form_set.forms.first() {
+            pending.push((index, form.form_id));
+        }
+    }
+
+    while let Some((index, form_id)) = pending.pop() {
+        if !reachable[index].insert(form_id) {
+            continue;
+        }
+        for link in links {
+            if link.form_set_index == index && link.form_id == form_id && follow(link) {
+                if let Some(target) = link.target {
+                    pending.push(target);
+                }
+            }
+        }
+    }
+
+    reachable
+}
+
+// Shortest chain of forms that leads from the first form of any form set to each reachable form,
+// the chain starts with the first form and ends with the form itself
+pub fn form_paths(form_sets: &[&FormSet], links: &[Link]) -> HashMap<(usize, u16), Vec<(usize, u16)>> {
+    let mut parents: HashMap<(usize, u16), Option<(usize, u16)>> = HashMap::new();
+    let mut pending: VecDeque<(usize, u16)> = VecDeque::new();
+    for (index, form_set) in form_sets.iter().enumerate() {
+        if let Some(form) = form_set.forms.first() {
+            parents.entry((index, form.form_id)).or_insert(None);
+            pending.push_back((index, form.form_id));
+        }
+    }
+
+    while let Some(form) = pending.pop_front() {
+        for link in links {
+            if (link.form_set_index, link.form_id) != form {
+                continue;
+            }
+            if let Some(target) = link.target {
+                if let Entry::Vacant(entry) = parents.entry(target) {
+                    entry.insert(Some(form));
+                    pending.push_back(target);
+                }
+            }
+        }
+    }
+
+    let mut paths = HashMap::new();
+    for &form in parents.keys() {
+        let mut path = vec![form];
+        while let Some(&Some(parent)) = parents.get(path.last().unwrap()) {
+            path.push(parent);
+        }
+        path.reverse();
+        paths.insert(form, path);
+    }
+    paths
+}
@@ -0,0 +1,510 @@
+use framework_parser;
+use framework_parser::{IfrOpcode, IfrOperation};
+use uefi_model::{
+    Condition, ConditionKind, ExpressionOp, Form, FormSet, Question, QuestionDefault, QuestionKind,
+    QuestionOption, RefTarget, Statement, StatementKind, VarStore, VarStoreKind,
+};
+use uefi_parser::Guid;
+
+//
+// Framework HII form packages converted into the UEFI model
+//
+// Framework questions have no separate storage info, their QuestionId is an offset in the currently selected varstore,
+// and the default varstore with VarStoreId 0 is the NV data of the form set
+
+// Default flags of OneOfOption and CheckBox
+const FRAMEWORK_FLAG_DEFAULT: u8 = 0x01;
+const FRAMEWORK_FLAG_MANUFACTURING: u8 = 0x02;
+
+fn convert_guid(guid: &framework_parser::Guid) -> Guid {
+    Guid {
+        data1: guid.data1,
+        data2: guid.data2,
+        data3: guid.data3,
+        data4: guid.data4,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn new_question(
+    kind: QuestionKind,
+    form_id: u16,
+    prompt_string_id: u16,
+    help_string_id: u16,
+    question_id: u16,
+    var_store_id: u16,
+    width: usize,
+    offset: usize,
+) -> Question {
+    Question {
+        kind,
+        form_id,
+        prompt_string_id,
+        help_string_id,
+        question_id,
+        var_store_id,
+        var_offset: question_id,
+        bit_width: None,
+        question_flags: 0,
+        flags: 0,
+        width,
+        minimum: None,
+        maximum: None,
+        step: None,
+        options: Vec::new(),
+        defaults: Vec::new(),
+        conditions: Vec::new(),
+        ref_target: None,
+        offset,
+    }
+}
+
+// Framework expressions are evaluated left to right, And and Or are placed between their operands
+// and Not before its operand, so they are converted into postfix order used by the model
+fn postfix_expression(operations: Vec<ExpressionOp>) -> Vec<ExpressionOp> {
+    let mut result = Vec::new();
+    let mut pending: Option<ExpressionOp> = None;
+    let mut negate = false;
+    let mut operands = 0;
+    let mut binaries = 0;
+    for op in operations {
+        match op {
+            ExpressionOp::And | ExpressionOp::Or => pending = Some(op),
+            ExpressionOp::Not => negate = !negate,
+            operand => {
+                result.push(operand);
+                if negate {
+                    result.push(ExpressionOp::Not);
+                    negate = false;
+                }
+                operands += 1;
+                if let Some(binary) = pending.take() {
+                    if operands > 1 {
+                        result.push(binary);
+                        binaries += 1;
+                    }
+                }
+            }
+        }
+    }
+    // Operands without an operation between them are combined with And
+    while binaries + 1 < operands {
+        result.push(ExpressionOp::And);
+        binaries += 1;
+    }
+    result
+}
+
+fn expression_op(opcode: IfrOpcode, data: &[u8]) -> Option<ExpressionOp> {
+    let op = match opcode {
+        IfrOpcode::EqIdVal => match framework_parser::ifr_eq_id_val(data) {
+            Ok((_, x)) => ExpressionOp::EqIdVal {
+                question_id: x.QuestionId,
+                value: x.Value as u64,
+            },
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::EqIdId => match framework_parser::ifr_eq_id_id(data) {
+            Ok((_, x)) => ExpressionOp::EqIdId {
+                question_id: x.QuestionId1,
+                other_question_id: x.QuestionId2,
+            },
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::EqIdList => match framework_parser::ifr_eq_id_list(data) {
+            Ok((_, x)) => ExpressionOp::EqIdValList {
+                question_id: x.QuestionId,
+                values: x.List.iter().map(|v| *v as u64).collect(),
+            },
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::True => ExpressionOp::Boolean(true),
+        IfrOpcode::False => ExpressionOp::Boolean(false),
+        IfrOpcode::And => ExpressionOp::And,
+        IfrOpcode::Or => ExpressionOp::Or,
+        IfrOpcode::Not => ExpressionOp::Not,
+        // Values of special variables and comparisons with them are unknown
+        IfrOpcode::EqVarVal | IfrOpcode::Greater | IfrOpcode::GreaterEqual => ExpressionOp::Undefined,
+        _ => return None,
+    };
+    Some(op)
+}
+
+// Build the model of all form sets found in a list of Framework IFR operations
+// offsets are the offsets of the operations in the input file
+pub fn form_sets(operations: &[IfrOperation], offsets: &[usize]) -> Vec<FormSet> {
+    let mut form_sets: Vec<FormSet> = Vec::new();
+    let mut current_form_id: u16 = 0;
+    let mut current_var_store_id: u16 = 0;
+    let mut open_conditions: Vec<usize> = Vec::new();
+    let mut expression: Option<Vec<ExpressionOp>> = None; // Expression of the last condition being collected
+    let mut current_question: Option<usize> = None; // Question that receives OneOfOptions
+
+    for (operation, &operation_offset) in operations.iter().zip(offsets) {
+        let data = operation.Data.unwrap_or(&[]);
+
+        // Expressions follow the condition operation and end with the first statement
+        if expression.is_some() {
+            if let Some(op) = expression_op(operation.OpCode, data) {
+                if let Some(ref mut ops) = expression {
+                    ops.push(op);
+                }
+                continue;
+            }
+            if let (Some(ops), Some(fs)) = (expression.take(), form_sets.last_mut()) {
+                if let Some(&index) = open_conditions.last() {
+                    fs.conditions[index].expression = postfix_expression(ops);
+                }
+            }
+        }
+
+        let fs = match form_sets.last_mut() {
+            Some(fs) => fs,
+            None => {
+                if operation.OpCode == IfrOpcode::FormSet {
+                    if let Ok((_, x)) = framework_parser::ifr_form_set(data) {
+                        form_sets.push(FormSet {
+                            guid: convert_guid(&x.Guid),
+                            title_string_id: x.TitleStringId,
+                            help_string_id: x.HelpStringId,
+                            offset: operation_offset,
+                            var_stores: vec![VarStore {
+                                kind: VarStoreKind::Buffer,
+                                var_store_id: 0,
+                                guid: convert_guid(&x.Guid),
+                                name: String::new(),
+                                size: Some(x.NvDataSize),
+                                offset: operation_offset,
+                            }],
+                            forms: Vec::new(),
+                            questions: Vec::new(),
+                            statements: Vec::new(),
+                            conditions: Vec::new(),
+                        });
+                    }
+                }
+                continue;
+            }
+        };
+
+        let mut question: Option<Question> = None;
+        match operation.OpCode {
+            IfrOpcode::FormSet => {
+                // Only one form set is allowed in a Framework form package
+            }
+            IfrOpcode::Form => {
+                if let Ok((_, x)) = framework_parser::ifr_form(data) {
+                    current_form_id = x.FormId;
+                    fs.forms.push(Form {
+                        form_id: x.FormId,
+                        title_string_id: x.TitleStringId,
+                        offset: operation_offset,
+                    });
+                }
+            }
+            IfrOpcode::EndForm => {
+                current_form_id = 0;
+                open_conditions.clear();
+            }
+            IfrOpcode::VarStore => {
+                if let Ok((_, x)) = framework_parser::ifr_var_store(data) {
+                    fs.var_stores.push(VarStore {
+                        kind: VarStoreKind::Buffer,
+                        var_store_id: x.VarStoreId,
+                        guid: convert_guid(&x.Guid),
+                        name: x.Name,
+                        size: Some(x.Size),
+                        offset: operation_offset,
+                    });
+                }
+            }
+            IfrOpcode::VarStoreSelect => {
+                if let Ok((_, x)) = framework_parser::ifr_var_store_select(data) {
+                    current_var_store_id = x.VarStoreId;
+                }
+            }
+            IfrOpcode::VarStoreSelectPair => {
+                if let Ok((_, x)) = framework_parser::ifr_var_store_select_pair(data) {
+                    current_var_store_id = x.VarStoreId;
+                }
+            }
+            IfrOpcode::SuppressIf | IfrOpcode::GrayOutIf => {
+                fs.conditions.push(Condition {
+                    kind: if operation.OpCode == IfrOpcode::SuppressIf {
+                        ConditionKind::SuppressIf
+                    } else {
+                        ConditionKind::GrayOutIf
+                    },
+                    expression: Vec::new(),
+                    offset: operation_offset,
+                });
+                open_conditions.push(fs.conditions.len() - 1);
+                expression = Some(Vec::new());
+            }
+            IfrOpcode::EndIf => {
+                open_conditions.pop();
+            }
+            IfrOpcode::Subtitle => {
+                if let Ok((_, x)) = framework_parser::ifr_subtitle(data) {
+                    fs.statements.push(Statement {
+                        kind: StatementKind::Subtitle,
+                        form_id: current_form_id,
+                        prompt_string_id: x.SubtitleStringId,
+                        help_string_id: 0,
+                        text_string_id: None,
+                        conditions: open_conditions.clone(),
+                        offset: operation_offset,
+                    });
+                }
+            }
+            IfrOpcode::Text => {
+                if let Ok((_, x)) = framework_parser::ifr_text(data) {
+                    fs.statements.push(Statement {
+                        kind: StatementKind::Text,
+                        form_id: current_form_id,
+                        prompt_string_id: x.TextStringId,
+                        help_string_id: x.HelpStringId,
+                        text_string_id: Some(x.TextTwoStringId),
+                        conditions: open_conditions.clone(),
+                        offset: operation_offset,
+                    });
+                }
+            }
+            IfrOpcode::OneOf => {
+                if let Ok((_, x)) = framework_parser::ifr_one_of(data) {
+                    question = Some(new_question(
+                        QuestionKind::OneOf,
+                        current_form_id,
+                        x.PromptStringId,
+                        x.HelpStringId,
+                        x.QuestionId,
+                        current_var_store_id,
+                        x.Width as usize,
+                        operation_offset,
+                    ));
+                }
+            }
+            IfrOpcode::OrderedList => {
+                if let Ok((_, x)) = framework_parser::ifr_ordered_list(data) {
+                    question = Some(new_question(
+                        QuestionKind::OrderedList,
+                        current_form_id,
+                        x.PromptStringId,
+                        x.HelpStringId,
+                        x.QuestionId,
+                        current_var_store_id,
+                        x.MaxEntries as usize,
+                        operation_offset,
+                    ));
+                }
+            }
+            IfrOpcode::OneOfOption => {
+                if let Ok((_, x)) = framework_parser::ifr_one_of_option(data) {
+                    if let Some(q) = current_question.and_then(|i| fs.questions.get_mut(i)) {
+                        if x.Flags & FRAMEWORK_FLAG_DEFAULT != 0 {
+                            q.defaults.push(QuestionDefault {
+                                default_id: 0,
+                                value: x.Value as u64,
+                            });
+                        }
+                        if x.Flags & FRAMEWORK_FLAG_MANUFACTURING != 0 {
+                            q.defaults.push(QuestionDefault {
+                                default_id: 1,
+                                value: x.Value as u64,
+                            });
+                        }
+                        q.options.push(QuestionOption {
+                            string_id: x.OptionStringId,
+                            value: x.Value as u64,
+                            flags: x.Flags,
+                        });
+                    }
+                }
+            }
+            IfrOpcode::End => {
+                current_question = None;
+            }
+            IfrOpcode::CheckBox => {
+                if let Ok((_, x)) = framework_parser::ifr_check_box(data) {
+                    let mut new = new_question(
+                        QuestionKind::CheckBox,
+                        current_form_id,
+                        x.PromptStringId,
+                        x.HelpStringId,
+                        x.QuestionId,
+                        current_var_store_id,
+                        x.Width as usize,
+                        operation_offset,
+                    );
+                    new.flags = x.Flags;
+                    new.defaults.push(QuestionDefault {
+                        default_id: 0,
+                        value: (x.Flags & FRAMEWORK_FLAG_DEFAULT != 0) as u64,
+                    });
+                    new.defaults.push(QuestionDefault {
+                        default_id: 1,
+                        value: (x.Flags & FRAMEWORK_FLAG_MANUFACTURING != 0) as u64,
+                    });
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::Numeric => {
+                if let Ok((_, x)) = framework_parser::ifr_numeric(data) {
+                    let mut new = new_question(
+                        QuestionKind::Numeric,
+                        current_form_id,
+                        x.PromptStringId,
+                        x.HelpStringId,
+                        x.QuestionId,
+                        current_var_store_id,
+                        x.Width as usize,
+                        operation_offset,
+                    );
+                    new.flags = x.Flags;
+                    new.minimum = Some(x.Min as u64);
+                    new.maximum = Some(x.Max as u64);
+                    new.step = Some(x.Step as u64);
+                    new.defaults.push(QuestionDefault {
+                        default_id: 0,
+                        value: x.Default as u64,
+                    });
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::Password => {
+                if let Ok((_, x)) = framework_parser::ifr_password(data) {
+                    question = Some(new_question(
+                        QuestionKind::Password,
+                        current_form_id,
+                        x.PromptStringId,
+                        x.HelpStringId,
+                        x.QuestionId,
+                        current_var_store_id,
+                        x.Width as usize,
+                        operation_offset,
+                    ));
+                }
+            }
+            IfrOpcode::String => {
+                if let Ok((_, x)) = framework_parser::ifr_string(data) {
+                    question = Some(new_question(
+                        QuestionKind::String,
+                        current_form_id,
+                        x.PromptStringId,
+                        x.HelpStringId,
+                        x.QuestionId,
+                        current_var_store_id,
+                        x.Width as usize,
+                        operation_offset,
+                    ));
+                }
+            }
+            IfrOpcode::Date | IfrOpcode::Time => {
+                let parsed = if operation.OpCode == IfrOpcode::Date {
+                    framework_parser::ifr_date(data).map(|(_, x)| {
+                        (QuestionKind::Date, x.QuestionId, x.Width, x.PromptStringId, x.HelpStringId)
+                    })
+                } else {
+                    framework_parser::ifr_time(data).map(|(_, x)| {
+                        (QuestionKind::Time, x.QuestionId, x.Width, x.PromptStringId, x.HelpStringId)
+                    })
+                };
+                if let Ok((kind, question_id, width, prompt_string_id, help_string_id)) = parsed {
+                    question = Some(new_question(
+                        kind,
+                        current_form_id,
+                        prompt_string_id,
+                        help_string_id,
+                        question_id,
+                        current_var_store_id,
+                        width as usize,
+                        operation_offset,
+                    ));
+                }
+            }
+            IfrOpcode::Ref => {
+                if let Ok((_, x)) = framework_parser::ifr_ref(data) {
+                    // Refs have no QuestionId and no storage
+                    let mut new = new_question(
+                        QuestionKind::Ref,
+                        current_form_id,
+                        x.PromptStringId,
+                        x.HelpStringId,
+                        0,
+                        current_var_store_id,
+                        0,
+                        operation_offset,
+                    );
+                    new.flags = x.Flags;
+                    new.ref_target = Some(RefTarget {
+                        form_id: Some(x.FormId),
+                        question_id: None,
+                        form_set_guid: None,
+                        device_path_id: None,
+                    });
+                    question = Some(new);
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(mut q) = question {
+            q.conditions = open_conditions.clone();
+            let has_options = q.kind == QuestionKind::OneOf || q.kind == QuestionKind::OrderedList;
+            fs.questions.push(q);
+            current_question = if has_options {
+                Some(fs.questions.len() - 1)
+            } else {
+                None
+            };
+        }
+    }
+
+    // Expression that ends the package
+    if let (Some(ops), Some(fs)) = (expression, form_sets.last_mut()) {
+        if let Some(&index) = open_conditions.last() {
+            fs.conditions[index].expression = postfix_expression(ops);
+        }
+    }
+
+    form_sets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uefi_model::ExpressionOp as Op;
+
+    fn eq(question_id: u16) -> Op {
+        Op::EqIdVal { question_id, value: 1 }
+    }
+
+    #[test]
+    fn infix_operations_become_postfix() {
+        assert_eq!(postfix_expression(vec![eq(1), Op::And, eq(2)]), vec![eq(1), eq(2), Op::And]);
+        assert_eq!(
+            postfix_expression(vec![eq(1), Op::Or, eq(2), Op::And, eq(3)]),
+            vec![eq(1), eq(2), Op::Or, eq(3), Op::And]
+        );
+    }
+
+    #[test]
+    fn not_applies_to_the_next_operand() {
+        assert_eq!(postfix_expression(vec![Op::Not, eq(1)]), vec![eq(1), Op::Not]);
+        assert_eq!(
+            postfix_expression(vec![eq(1), Op::Or, Op::Not, eq(2)]),
+            vec![eq(1), eq(2), Op::Not, Op::Or]
+        );
+        assert_eq!(postfix_expression(vec![Op::Not, Op::Not, eq(1)]), vec![eq(1)]);
+    }
+
+    #[test]
+    fn operands_without_operations_are_combined_with_and() {
+        assert_eq!(
+            postfix_expression(vec![eq(1), eq(2), eq(3)]),
+            vec![eq(1), eq(2), eq(3), Op::And, Op::And]
+        );
+        // An operation before the first operand has nothing to combine
+        assert_eq!(postfix_expression(vec![Op::Or, eq(1), eq(2)]), vec![eq(1), eq(2), Op::And]);
+    }
+}
@@ -1,11 +1,10 @@
 #![allow(non_snake_case)]
-#![allow(deprecated)]
 
-extern crate nom;
-
-use nom::{le_u16, le_u32, le_u64, le_u8, IResult};
+use byte_reader::{IResult, Reader};
 use std::fmt;
 
+use uefi_parser::{is_padding, DamagedRegion, IfrRegion};
+
 //
 // Common data types
 //
@@ -18,19 +17,18 @@ pub struct Guid {
 }
 
 pub fn guid(input: &[u8]) -> IResult<&[u8], Guid> {
-    do_parse!(
-        input,
-        d1: le_u32
-            >> d2: le_u16
-            >> d3: le_u16
-            >> d4: count_fixed!(u8, le_u8, 8)
-            >> (Guid {
-                data1: d1,
-                data2: d2,
-                data3: d3,
-                data4: d4,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let d1 = reader.u32("Data1")?;
+    let d2 = reader.u16("Data2")?;
+    let d3 = reader.u16("Data3")?;
+    let mut d4 = [0u8; 8];
+    d4.copy_from_slice(reader.bytes(8, "Data4")?);
+    reader.finish(Guid {
+        data1: d1,
+        data2: d2,
+        data3: d3,
+        data4: d4,
+    })
 }
 
 impl fmt::Display for Guid {
@@ -90,60 +88,59 @@ impl From<u16> for HiiPackageType {
     }
 }
 
-pub fn hii_package(input: &[u8]) -> IResult<&[u8], HiiPackage> {
-    do_parse!(
-        input,
-        len: le_u32
-            >> typ: le_u16
-            >> verify!(value!(len), |val: u32| val >= 6)
-            >> data: cond_with_error!(len > 6, take!(len - 6))
-            >> (HiiPackage {
-                Length: len as usize,
-                Type: HiiPackageType::from(typ),
-                Data: data,
-            })
-    )
+pub fn hii_package(input: &[u8]) -> IResult<&[u8], HiiPackage<'_>> {
+    let mut reader = Reader::new(input);
+    let len = reader.u32("Length")?;
+    let typ = reader.u16("Type")?;
+    reader.verify(0, "Length", len, len >= 6)?;
+    let data = reader.cond(len > 6, |r| r.bytes(len as usize - 6, "Data"))?;
+    reader.finish(HiiPackage {
+        Length: len as usize,
+        Type: HiiPackageType::from(typ),
+        Data: data,
+    })
 }
 
 pub fn hii_string_package_candidate(input: &[u8]) -> IResult<&[u8], &[u8]> {
-    do_parse!(
-        input,
-        len: peek!(hii_string_package_candidate_helper) >> dat: take!(len) >> (dat)
-    )
+    let (_, len) = hii_string_package_candidate_helper(input)?;
+    let mut reader = Reader::new(input);
+    let dat = reader.bytes(len, "Package")?;
+    reader.finish(dat)
 }
 
 fn hii_string_package_candidate_helper(input: &[u8]) -> IResult<&[u8], usize> {
-    do_parse!(
-        input,
-        len: le_u32 >>
-        typ: le_u16 >>
-        verify!(value!(len), |val: u32| val > 0x06 + 0x20) >> // Total length of the package is sane
-        verify!(value!(typ), |val: u16| val == 0x02) >> // Package type is 0x02 
-        take!(len - 0x06 - 0x02) >> // Skip the rest up to the last 2 bytes
-        verify!(le_u16, |val: u16| val == 0) >> // Last 2 bytes must be zeroes
-        ( len as usize )
-    )
+    let mut reader = Reader::new(input);
+    let len = reader.u32("Length")?;
+    let typ = reader.u16("Type")?;
+    reader.verify(0, "Length", len, len > 0x06 + 0x20)?; // Total length of the package is sane
+    reader.verify(4, "Type", typ, typ == 0x02)?; // Package type is 0x02
+    reader.bytes(len as usize - 0x06 - 0x02, "Data")?; // Skip the rest up to the last 2 bytes
+    let start = reader.position();
+    let last = reader.u16("End")?;
+    reader.verify(start, "End", last, last == 0)?; // Last 2 bytes must be zeroes
+    reader.finish(len as usize)
 }
 
 pub fn hii_form_package_candidate(input: &[u8]) -> IResult<&[u8], &[u8]> {
-    do_parse!(
-        input,
-        len: peek!(hii_form_package_candidate_helper) >> dat: take!(len) >> (dat)
-    )
+    let (_, len) = hii_form_package_candidate_helper(input)?;
+    let mut reader = Reader::new(input);
+    let dat = reader.bytes(len, "Package")?;
+    reader.finish(dat)
 }
 
 fn hii_form_package_candidate_helper(input: &[u8]) -> IResult<&[u8], usize> {
-    do_parse!(
-        input,
-        len: le_u32 >>
-        verify!(value!(len), |val: u32| val > 0x06) >> // Total length of the package is sane
-        typ: le_u16 >>
-        verify!(value!(typ), |val: u16| val == 0x03) >> // Package type is 0x03
-        verify!(le_u8, |val: u8| val == 0x0E) >> // Must start with IfrOpCode::FormSet
-        take!(len - 0x06 - 0x01 - 0x02) >> // Skip the rest up to the last 2 bytes
-        verify!(le_u16, |val: u16| val == 0x020D) >> // Last 2 bytes must be IfrOpCode::EndFormSet
-        ( len as usize )
-    )
+    let mut reader = Reader::new(input);
+    let len = reader.u32("Length")?;
+    reader.verify(0, "Length", len, len >= 0x06 + 0x01 + 0x02)?; // Total length of the package is sane
+    let typ = reader.u16("Type")?;
+    reader.verify(4, "Type", typ, typ == 0x03)?; // Package type is 0x03
+    let opcode = reader.u8("OpCode")?;
+    reader.verify(6, "OpCode", opcode, opcode == 0x0E)?; // Must start with IfrOpCode::FormSet
+    reader.bytes(len as usize - 0x06 - 0x01 - 0x02, "Data")?; // Skip the rest up to the last 2 bytes
+    let start = reader.position();
+    let last = reader.u16("End")?;
+    reader.verify(start, "End", last, last == 0x020D)?; // Last 2 bytes must be IfrOpCode::EndFormSet
+    reader.finish(len as usize)
 }
 
 //
@@ -159,42 +156,42 @@ pub struct HiiStringPackage {
     pub Strings: Vec<String>,
 }
 
-named!(
-    ucs2_string<Vec<u16>>,
-    map!(
-        many_till!(le_u16, verify!(le_u16, |n: u16| n == 0)),
-        |(mut v, n)| {
-            v.push(n);
-            v
+// UCS2 string without its terminating zero, which is consumed
+fn ucs2_string(input: &[u8]) -> IResult<&[u8], Vec<u16>> {
+    let mut reader = Reader::new(input);
+    let mut s = Vec::new();
+    loop {
+        let c = reader.u16("String")?;
+        if c == 0 {
+            break;
         }
-    )
-);
+        s.push(c);
+    }
+    reader.finish(s)
+}
 
 pub fn string_ucs2(input: &[u8]) -> IResult<&[u8], String> {
-    do_parse!(
-        input,
-        s: ucs2_string >> (String::from_utf16_lossy(&s[..s.len() - 1]))
-    )
+    let mut reader = Reader::new(input);
+    let s = reader.parse(ucs2_string)?;
+    reader.finish(String::from_utf16_lossy(&s))
 }
 
 pub fn hii_string_package(input: &[u8]) -> IResult<&[u8], HiiStringPackage> {
-    do_parse!(
-        input,
-        lnso: le_u32
-            >> plno: le_u32
-            >> nsp: le_u32
-            >> attr: le_u32
-            >> sp: count!(le_u32, nsp as usize)
-            >> s: count!(string_ucs2, nsp as usize)
-            >> (HiiStringPackage {
-                LanguageNameStringOffset: lnso,
-                PrintableLanguageNameOffset: plno,
-                NumStringPointers: nsp,
-                Attributes: attr,
-                StringPointers: sp,
-                Strings: s,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let lnso = reader.u32("LanguageNameStringOffset")?;
+    let plno = reader.u32("PrintableLanguageNameOffset")?;
+    let nsp = reader.u32("NumStringPointers")?;
+    let attr = reader.u32("Attributes")?;
+    let sp = reader.count(nsp as usize, |r| r.u32("StringPointers"))?;
+    let s = reader.count(nsp as usize, |r| r.parse(string_ucs2))?;
+    reader.finish(HiiStringPackage {
+        LanguageNameStringOffset: lnso,
+        PrintableLanguageNameOffset: plno,
+        NumStringPointers: nsp,
+        Attributes: attr,
+        StringPointers: sp,
+        Strings: s,
+    })
 }
 
 //
@@ -207,23 +204,213 @@ pub struct IfrOperation<'a> {
     pub Data: Option<&'a [u8]>,
 }
 
-pub fn ifr_operation(input: &[u8]) -> IResult<&[u8], IfrOperation> {
-    do_parse!(
-        input,
-        opcode: le_u8
-            >> len: le_u8
-            >> verify!(value!(len), |val: u8| val >= 2)
-            >> data: cond_with_error!(len > 2, take!((len - 2) as usize))
-            >> (IfrOperation {
-                OpCode: IfrOpcode::from(opcode),
-                Length: len,
-                Data: data
-            })
-    )
+pub fn ifr_operation(input: &[u8]) -> IResult<&[u8], IfrOperation<'_>> {
+    let mut reader = Reader::new(input);
+    let opcode = reader.u8("OpCode")?;
+    let len = reader.u8("Length")?;
+    reader.verify(1, "Length", len, len >= 2)?;
+    let data = reader.cond(len > 2, |r| r.bytes((len - 2) as usize, "Data"))?;
+    reader.finish(IfrOperation {
+        OpCode: IfrOpcode::from(opcode),
+        Length: len,
+        Data: data,
+    })
+}
+
+pub fn ifr_operations(input: &[u8]) -> IResult<&[u8], Vec<IfrOperation<'_>>> {
+    let mut reader = Reader::new(input);
+    let v = reader.many1(ifr_operation)?;
+    reader.finish(v)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct IfrOperations<'a> {
+    pub operations: Vec<IfrOperation<'a>>,
+    pub offsets: Vec<usize>, // Offset of every operation relative to the start of the operations
+    pub damaged: Vec<DamagedRegion>,
+}
+
+impl<'a> IfrOperations<'a> {
+    // Offsets of all operations in the input file
+    pub fn file_offsets(&self, base_offset: usize) -> Vec<usize> {
+        self.offsets.iter().map(|offset| base_offset + offset).collect()
+    }
+
+    pub fn damaged_length(&self) -> usize {
+        self.damaged.iter().map(|region| region.length).sum()
+    }
+
+    // Runs of operations and the damaged regions between them in the order they appear in the form package,
+    // offsets of the operations are taken from the given list, i.e. their offsets in the input file
+    pub fn regions<'s>(&'s self, offsets: &'s [usize]) -> Vec<IfrRegion<'s, IfrOperation<'a>>> {
+        let mut regions = Vec::new();
+        let mut start = 0;
+        for damaged in &self.damaged {
+            let end = start + self.offsets[start..].iter().take_while(|&&offset| offset < damaged.offset).count();
+            if end > start {
+                regions.push(IfrRegion::Operations(&self.operations[start..end], &offsets[start..end]));
+            }
+            regions.push(IfrRegion::Damaged(damaged));
+            start = end;
+        }
+        if start < self.operations.len() {
+            regions.push(IfrRegion::Operations(&self.operations[start..], &offsets[start..]));
+        }
+        regions
+    }
+}
+
+// A few known operations in a row or the end of the package, a likely start of valid IFR after damaged bytes
+fn ifr_operations_plausible(input: &[u8]) -> bool {
+    let mut rest = input;
+    for _ in 0..3 {
+        if rest.is_empty() {
+            return true;
+        }
+        match ifr_operation(rest) {
+            Ok((r, operation)) if !matches!(operation.OpCode, IfrOpcode::Unknown(_)) => rest = r,
+            _ => return false,
+        }
+    }
+    true
 }
 
-pub fn ifr_operations(input: &[u8]) -> IResult<&[u8], Vec<IfrOperation>> {
-    do_parse!(input, v: many1!(complete!(ifr_operation)) >> (v))
+// Operations starting at offset end exactly at the end offset
+fn ifr_operations_reach(input: &[u8], mut offset: usize, end: usize) -> bool {
+    while offset < end {
+        match ifr_operation(&input[offset..]) {
+            Ok((_, operation)) => offset += operation.Length as usize,
+            Err(_) => return false,
+        }
+    }
+    offset == end
+}
+
+// Parses all operations of a form package, an operation with invalid length is skipped up to the next plausible
+// operation boundary and parsing continues, an operation that doesn't fit into the package ends it
+pub fn ifr_operations_resilient(input: &[u8]) -> IfrOperations<'_> {
+    let mut result = IfrOperations {
+        operations: Vec::new(),
+        offsets: Vec::new(),
+        damaged: Vec::new(),
+    };
+    let mut offset = 0;
+    while offset < input.len() {
+        // Padding at the end of the package is not damage
+        if is_padding(&input[offset..]) {
+            break;
+        }
+        if let Ok((_, operation)) = ifr_operation(&input[offset..]) {
+            result.offsets.push(offset);
+            offset += operation.Length as usize;
+            result.operations.push(operation);
+            continue;
+        }
+        let rest = &input[offset..];
+        if rest.len() < 2 || rest[1] >= 2 {
+            result.damaged.push(DamagedRegion {
+                offset,
+                length: rest.len(),
+                truncated: true,
+            });
+            break;
+        }
+        let mut next = (offset + 1..input.len())
+            .find(|&next| ifr_operations_plausible(&input[next..]))
+            .unwrap_or(input.len());
+        // Damaged bytes can look like the start of a long operation that covers valid ones,
+        // prefer a later boundary whose operations end exactly where the long one does
+        while next < input.len() {
+            let end = next + (input[next + 1]) as usize;
+            match (next + 1..end).find(|&p| ifr_operations_plausible(&input[p..]) && ifr_operations_reach(input, p, end)) {
+                Some(p) => next = p,
+                None => break,
+            }
+        }
+        result.damaged.push(DamagedRegion {
+            offset,
+            length: next - offset,
+            truncated: false,
+        });
+        offset = next;
+    }
+    result
+}
+
+//
+// Relaxed package discovery
+//
+// Known operations up to padding or the end of the input, None if there is anything else
+fn trailing_data(input: &[u8], padding: &'static str, operations: &'static str) -> Option<&'static str> {
+    let mut rest = input;
+    let mut found = false;
+    while !rest.is_empty() && !is_padding(rest) {
+        match ifr_operation(rest) {
+            Ok((r, operation)) if !matches!(operation.OpCode, IfrOpcode::Unknown(_)) => rest = r,
+            _ => return None,
+        }
+        found = true;
+    }
+    Some(if found { operations } else { padding })
+}
+
+// Form package accepted by relaxed discovery: known operations from a FormSet up to EndFormSet,
+// followed by padding or more operations, returns the package and how it differs from a strict candidate
+pub fn hii_form_package_candidate_relaxed(input: &[u8]) -> Option<(&[u8], String)> {
+    let (_, package) = hii_package(input).ok()?;
+    if package.Type != HiiPackageType::Ifr {
+        return None;
+    }
+    let data = package.Data?;
+    let (mut rest, form_set) = ifr_operation(data).ok()?;
+    if form_set.OpCode != IfrOpcode::FormSet {
+        return None;
+    }
+    let mut last = form_set;
+    while last.OpCode != IfrOpcode::EndFormSet {
+        let (r, operation) = ifr_operation(rest).ok()?;
+        if let IfrOpcode::Unknown(_) = operation.OpCode {
+            return None;
+        }
+        rest = r;
+        last = operation;
+    }
+    let deviation = if rest.is_empty() {
+        format!("last operation is an EndFormSet of length 0x{:X}", last.Length)
+    } else {
+        String::from(trailing_data(rest, "padding after EndFormSet", "operations after EndFormSet")?)
+    };
+    Some((&input[..package.Length], deviation))
+}
+
+// String package accepted by relaxed discovery: all strings can be parsed, followed by anything
+pub fn hii_string_package_candidate_relaxed(input: &[u8]) -> Option<(&[u8], String)> {
+    let (_, package) = hii_package(input).ok()?;
+    if package.Type != HiiPackageType::String {
+        return None;
+    }
+    let (_, string_package) = hii_string_package(package.Data?).ok()?;
+    if string_package.NumStringPointers == 0 {
+        return None;
+    }
+    Some((&input[..package.Length], String::from("no zero bytes at the end")))
+}
+
+// Candidate accepted by strict heuristics, or by relaxed ones if requested
+pub fn form_package_candidate(input: &[u8], relaxed: bool) -> Option<&[u8]> {
+    match hii_form_package_candidate(input) {
+        Ok((_, candidate)) => Some(candidate),
+        Err(_) if relaxed => hii_form_package_candidate_relaxed(input).map(|(candidate, _)| candidate),
+        Err(_) => None,
+    }
+}
+
+pub fn string_package_candidate(input: &[u8], relaxed: bool) -> Option<&[u8]> {
+    match hii_string_package_candidate(input) {
+        Ok((_, candidate)) => Some(candidate),
+        Err(_) if relaxed => hii_string_package_candidate_relaxed(input).map(|(candidate, _)| candidate),
+        Err(_) => None,
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -419,15 +606,13 @@ pub struct IfrForm {
 }
 
 pub fn ifr_form(input: &[u8]) -> IResult<&[u8], IfrForm> {
-    do_parse!(
-        input,
-        fid: le_u16
-            >> tsid: le_u16
-            >> (IfrForm {
-                FormId: fid,
-                TitleStringId: tsid,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let fid = reader.u16("FormId")?;
+    let tsid = reader.u16("TitleStringId")?;
+    reader.finish(IfrForm {
+        FormId: fid,
+        TitleStringId: tsid,
+    })
 }
 
 //
@@ -439,13 +624,11 @@ pub struct IfrSubtitle {
 }
 
 pub fn ifr_subtitle(input: &[u8]) -> IResult<&[u8], IfrSubtitle> {
-    do_parse!(
-        input,
-        s: le_u16
-            >> (IfrSubtitle {
-                SubtitleStringId: s,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let s = reader.u16("SubtitleStringId")?;
+    reader.finish(IfrSubtitle {
+        SubtitleStringId: s,
+    })
 }
 
 //
@@ -461,21 +644,19 @@ pub struct IfrText {
 }
 
 pub fn ifr_text(input: &[u8]) -> IResult<&[u8], IfrText> {
-    do_parse!(
-        input,
-        h: le_u16
-            >> t: le_u16
-            >> t2: le_u16
-            >> f: le_u8
-            >> k: le_u16
-            >> (IfrText {
-                HelpStringId: h,
-                TextStringId: t,
-                TextTwoStringId: t2,
-                Flags: f,
-                Key: k,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let h = reader.u16("HelpStringId")?;
+    let t = reader.u16("TextStringId")?;
+    let t2 = reader.u16("TextTwoStringId")?;
+    let f = reader.u8("Flags")?;
+    let k = reader.u16("Key")?;
+    reader.finish(IfrText {
+        HelpStringId: h,
+        TextStringId: t,
+        TextTwoStringId: t2,
+        Flags: f,
+        Key: k,
+    })
 }
 
 //0x04 => IfrOpcode::Graphic
@@ -492,19 +673,17 @@ pub struct IfrOneOf {
 }
 
 pub fn ifr_one_of(input: &[u8]) -> IResult<&[u8], IfrOneOf> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> w: le_u8
-            >> psid: le_u16
-            >> hsid: le_u16
-            >> (IfrOneOf {
-                QuestionId: qid,
-                Width: w,
-                PromptStringId: psid,
-                HelpStringId: hsid,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let w = reader.u8("Width")?;
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    reader.finish(IfrOneOf {
+        QuestionId: qid,
+        Width: w,
+        PromptStringId: psid,
+        HelpStringId: hsid,
+    })
 }
 
 //
@@ -521,23 +700,21 @@ pub struct IfrCheckBox {
 }
 
 pub fn ifr_check_box(input: &[u8]) -> IResult<&[u8], IfrCheckBox> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> w: le_u8
-            >> psid: le_u16
-            >> hsid: le_u16
-            >> f: le_u8
-            >> k: le_u16
-            >> (IfrCheckBox {
-                QuestionId: qid,
-                Width: w,
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                Flags: f,
-                Key: k,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let w = reader.u8("Width")?;
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let f = reader.u8("Flags")?;
+    let k = reader.u16("Key")?;
+    reader.finish(IfrCheckBox {
+        QuestionId: qid,
+        Width: w,
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        Flags: f,
+        Key: k,
+    })
 }
 
 //
@@ -558,31 +735,29 @@ pub struct IfrNumeric {
 }
 
 pub fn ifr_numeric(input: &[u8]) -> IResult<&[u8], IfrNumeric> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> w: le_u8
-            >> psid: le_u16
-            >> hsid: le_u16
-            >> f: le_u8
-            >> k: le_u16
-            >> min: le_u16
-            >> max: le_u16
-            >> step: le_u16
-            >> def: le_u16
-            >> (IfrNumeric {
-                QuestionId: qid,
-                Width: w,
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                Flags: f,
-                Key: k,
-                Min: min,
-                Max: max,
-                Step: step,
-                Default: def,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let w = reader.u8("Width")?;
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let f = reader.u8("Flags")?;
+    let k = reader.u16("Key")?;
+    let min = reader.u16("Min")?;
+    let max = reader.u16("Max")?;
+    let step = reader.u16("Step")?;
+    let def = reader.u16("Default")?;
+    reader.finish(IfrNumeric {
+        QuestionId: qid,
+        Width: w,
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        Flags: f,
+        Key: k,
+        Min: min,
+        Max: max,
+        Step: step,
+        Default: def,
+    })
 }
 
 //
@@ -602,29 +777,27 @@ pub struct IfrPassword {
 }
 
 pub fn ifr_password(input: &[u8]) -> IResult<&[u8], IfrPassword> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> w: le_u8
-            >> psid: le_u16
-            >> hsid: le_u16
-            >> f: le_u8
-            >> k: le_u16
-            >> ms: le_u8
-            >> xs: le_u8
-            >> e: le_u16
-            >> (IfrPassword {
-                QuestionId: qid,
-                Width: w,
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                Flags: f,
-                Key: k,
-                MinSize: ms,
-                MaxSize: xs,
-                Encoding: e,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let w = reader.u8("Width")?;
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let f = reader.u8("Flags")?;
+    let k = reader.u16("Key")?;
+    let ms = reader.u8("MinSize")?;
+    let xs = reader.u8("MaxSize")?;
+    let e = reader.u16("Encoding")?;
+    reader.finish(IfrPassword {
+        QuestionId: qid,
+        Width: w,
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        Flags: f,
+        Key: k,
+        MinSize: ms,
+        MaxSize: xs,
+        Encoding: e,
+    })
 }
 
 //
@@ -639,19 +812,17 @@ pub struct IfrOneOfOption {
 }
 
 pub fn ifr_one_of_option(input: &[u8]) -> IResult<&[u8], IfrOneOfOption> {
-    do_parse!(
-        input,
-        osid: le_u16
-            >> val: le_u16
-            >> f: le_u8
-            >> k: le_u16
-            >> (IfrOneOfOption {
-                OptionStringId: osid,
-                Value: val,
-                Flags: f,
-                Key: k,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let osid = reader.u16("OptionStringId")?;
+    let val = reader.u16("Value")?;
+    let f = reader.u8("Flags")?;
+    let k = reader.u16("Key")?;
+    reader.finish(IfrOneOfOption {
+        OptionStringId: osid,
+        Value: val,
+        Flags: f,
+        Key: k,
+    })
 }
 
 //
@@ -663,7 +834,9 @@ pub struct IfrSuppressIf {
 }
 
 pub fn ifr_supress_if(input: &[u8]) -> IResult<&[u8], IfrSuppressIf> {
-    do_parse!(input, f: le_u8 >> (IfrSuppressIf { Flags: f }))
+    let mut reader = Reader::new(input);
+    let f = reader.u8("Flags")?;
+    reader.finish(IfrSuppressIf { Flags: f })
 }
 
 //0x0B => IfrOpcode::EndForm
@@ -678,10 +851,10 @@ pub struct IfrHidden {
 }
 
 pub fn ifr_hidden(input: &[u8]) -> IResult<&[u8], IfrHidden> {
-    do_parse!(
-        input,
-        val: le_u16 >> k: le_u16 >> (IfrHidden { Value: val, Key: k })
-    )
+    let mut reader = Reader::new(input);
+    let val = reader.u16("Value")?;
+    let k = reader.u16("Key")?;
+    reader.finish(IfrHidden { Value: val, Key: k })
 }
 
 //0x0D => IfrOpcode::EndFormSet
@@ -701,25 +874,23 @@ pub struct IfrFormSet {
 }
 
 pub fn ifr_form_set(input: &[u8]) -> IResult<&[u8], IfrFormSet> {
-    do_parse!(
-        input,
-        mg: guid
-            >> tsid: le_u16
-            >> hsid: le_u16
-            >> ch: le_u64
-            >> c: le_u16
-            >> sc: le_u16
-            >> nvds: le_u16
-            >> (IfrFormSet {
-                Guid: mg,
-                TitleStringId: tsid,
-                HelpStringId: hsid,
-                CallbackHandle: ch,
-                Class: c,
-                SubClass: sc,
-                NvDataSize: nvds,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let mg = reader.parse(guid)?;
+    let tsid = reader.u16("TitleStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let ch = reader.u64("CallbackHandle")?;
+    let c = reader.u16("Class")?;
+    let sc = reader.u16("SubClass")?;
+    let nvds = reader.u16("NvDataSize")?;
+    reader.finish(IfrFormSet {
+        Guid: mg,
+        TitleStringId: tsid,
+        HelpStringId: hsid,
+        CallbackHandle: ch,
+        Class: c,
+        SubClass: sc,
+        NvDataSize: nvds,
+    })
 }
 
 //
@@ -735,21 +906,19 @@ pub struct IfrRef {
 }
 
 pub fn ifr_ref(input: &[u8]) -> IResult<&[u8], IfrRef> {
-    do_parse!(
-        input,
-        fid: le_u16
-            >> psid: le_u16
-            >> hsid: le_u16
-            >> f: le_u8
-            >> k: le_u16
-            >> (IfrRef {
-                FormId: fid,
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                Flags: f,
-                Key: k,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let fid = reader.u16("FormId")?;
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let f = reader.u8("Flags")?;
+    let k = reader.u16("Key")?;
+    reader.finish(IfrRef {
+        FormId: fid,
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        Flags: f,
+        Key: k,
+    })
 }
 
 //0x10 => IfrOpcode::End
@@ -764,15 +933,13 @@ pub struct IfrInconsistentIf {
 }
 
 pub fn ifr_inconsistent_if(input: &[u8]) -> IResult<&[u8], IfrInconsistentIf> {
-    do_parse!(
-        input,
-        psid: le_u16
-            >> f: le_u8
-            >> (IfrInconsistentIf {
-                PopupStringId: psid,
-                Flags: f,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PopupStringId")?;
+    let f = reader.u8("Flags")?;
+    reader.finish(IfrInconsistentIf {
+        PopupStringId: psid,
+        Flags: f,
+    })
 }
 
 //
@@ -785,15 +952,13 @@ pub struct IfrEqIdVal {
 }
 
 pub fn ifr_eq_id_val(input: &[u8]) -> IResult<&[u8], IfrEqIdVal> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> val: le_u16
-            >> (IfrEqIdVal {
-                QuestionId: qid,
-                Value: val,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let val = reader.u16("Value")?;
+    reader.finish(IfrEqIdVal {
+        QuestionId: qid,
+        Value: val,
+    })
 }
 
 //
@@ -806,15 +971,13 @@ pub struct IfrEqIdId {
 }
 
 pub fn ifr_eq_id_id(input: &[u8]) -> IResult<&[u8], IfrEqIdId> {
-    do_parse!(
-        input,
-        qid1: le_u16
-            >> qid2: le_u16
-            >> (IfrEqIdId {
-                QuestionId1: qid1,
-                QuestionId2: qid2,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid1 = reader.u16("QuestionId1")?;
+    let qid2 = reader.u16("QuestionId2")?;
+    reader.finish(IfrEqIdId {
+        QuestionId1: qid1,
+        QuestionId2: qid2,
+    })
 }
 
 //
@@ -829,19 +992,17 @@ pub struct IfrEqIdList {
 }
 
 pub fn ifr_eq_id_list(input: &[u8]) -> IResult<&[u8], IfrEqIdList> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> w: le_u8
-            >> len: le_u16
-            >> l: count!(le_u16, len as usize)
-            >> (IfrEqIdList {
-                QuestionId: qid,
-                Width: w,
-                ListLength: len,
-                List: l,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let w = reader.u8("Width")?;
+    let len = reader.u16("ListLength")?;
+    let l = reader.count(len as usize, |r| r.u16("List"))?;
+    reader.finish(IfrEqIdList {
+        QuestionId: qid,
+        Width: w,
+        ListLength: len,
+        List: l,
+    })
 }
 
 //0x15 => IfrOpcode::And
@@ -858,7 +1019,9 @@ pub struct IfrGrayOutIf {
 }
 
 pub fn ifr_grayout_if(input: &[u8]) -> IResult<&[u8], IfrGrayOutIf> {
-    do_parse!(input, f: le_u8 >> (IfrGrayOutIf { Flags: f }))
+    let mut reader = Reader::new(input);
+    let f = reader.u8("Flags")?;
+    reader.finish(IfrGrayOutIf { Flags: f })
 }
 
 //
@@ -879,31 +1042,29 @@ pub struct IfrDate {
 }
 
 pub fn ifr_date(input: &[u8]) -> IResult<&[u8], IfrDate> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> w: le_u8
-            >> psid: le_u16
-            >> hsid: le_u16
-            >> f: le_u8
-            >> k: le_u16
-            >> min: le_u16
-            >> max: le_u16
-            >> step: le_u16
-            >> def: le_u16
-            >> (IfrDate {
-                QuestionId: qid,
-                Width: w,
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                Flags: f,
-                Key: k,
-                Min: min,
-                Max: max,
-                Step: step,
-                Default: def,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let w = reader.u8("Width")?;
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let f = reader.u8("Flags")?;
+    let k = reader.u16("Key")?;
+    let min = reader.u16("Min")?;
+    let max = reader.u16("Max")?;
+    let step = reader.u16("Step")?;
+    let def = reader.u16("Default")?;
+    reader.finish(IfrDate {
+        QuestionId: qid,
+        Width: w,
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        Flags: f,
+        Key: k,
+        Min: min,
+        Max: max,
+        Step: step,
+        Default: def,
+    })
 }
 
 //
@@ -924,31 +1085,29 @@ pub struct IfrTime {
 }
 
 pub fn ifr_time(input: &[u8]) -> IResult<&[u8], IfrTime> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> w: le_u8
-            >> psid: le_u16
-            >> hsid: le_u16
-            >> f: le_u8
-            >> k: le_u16
-            >> min: le_u16
-            >> max: le_u16
-            >> step: le_u16
-            >> def: le_u16
-            >> (IfrTime {
-                QuestionId: qid,
-                Width: w,
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                Flags: f,
-                Key: k,
-                Min: min,
-                Max: max,
-                Step: step,
-                Default: def,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let w = reader.u8("Width")?;
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let f = reader.u8("Flags")?;
+    let k = reader.u16("Key")?;
+    let min = reader.u16("Min")?;
+    let max = reader.u16("Max")?;
+    let step = reader.u16("Step")?;
+    let def = reader.u16("Default")?;
+    reader.finish(IfrTime {
+        QuestionId: qid,
+        Width: w,
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        Flags: f,
+        Key: k,
+        Min: min,
+        Max: max,
+        Step: step,
+        Default: def,
+    })
 }
 
 //
@@ -967,27 +1126,25 @@ pub struct IfrString {
 }
 
 pub fn ifr_string(input: &[u8]) -> IResult<&[u8], IfrString> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> w: le_u8
-            >> psid: le_u16
-            >> hsid: le_u16
-            >> f: le_u8
-            >> k: le_u16
-            >> ms: le_u8
-            >> xs: le_u8
-            >> (IfrString {
-                QuestionId: qid,
-                Width: w,
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                Flags: f,
-                Key: k,
-                MinSize: ms,
-                MaxSize: xs,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let w = reader.u8("Width")?;
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let f = reader.u8("Flags")?;
+    let k = reader.u16("Key")?;
+    let ms = reader.u8("MinSize")?;
+    let xs = reader.u8("MaxSize")?;
+    reader.finish(IfrString {
+        QuestionId: qid,
+        Width: w,
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        Flags: f,
+        Key: k,
+        MinSize: ms,
+        MaxSize: xs,
+    })
 }
 
 //
@@ -999,7 +1156,9 @@ pub struct IfrLabel {
 }
 
 pub fn ifr_label(input: &[u8]) -> IResult<&[u8], IfrLabel> {
-    do_parse!(input, l: le_u16 >> (IfrLabel { LabelId: l }))
+    let mut reader = Reader::new(input);
+    let l = reader.u16("LabelId")?;
+    reader.finish(IfrLabel { LabelId: l })
 }
 
 //
@@ -1015,21 +1174,19 @@ pub struct IfrSaveDefaults {
 }
 
 pub fn ifr_save_defaults(input: &[u8]) -> IResult<&[u8], IfrSaveDefaults> {
-    do_parse!(
-        input,
-        fid: le_u16
-            >> psid: le_u16
-            >> hsid: le_u16
-            >> f: le_u8
-            >> k: le_u16
-            >> (IfrSaveDefaults {
-                FormId: fid,
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                Flags: f,
-                Key: k,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let fid = reader.u16("FormId")?;
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let f = reader.u8("Flags")?;
+    let k = reader.u16("Key")?;
+    reader.finish(IfrSaveDefaults {
+        FormId: fid,
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        Flags: f,
+        Key: k,
+    })
 }
 
 //
@@ -1045,21 +1202,19 @@ pub struct IfrRestoreDefaults {
 }
 
 pub fn ifr_restore_defaults(input: &[u8]) -> IResult<&[u8], IfrRestoreDefaults> {
-    do_parse!(
-        input,
-        fid: le_u16
-            >> psid: le_u16
-            >> hsid: le_u16
-            >> f: le_u8
-            >> k: le_u16
-            >> (IfrRestoreDefaults {
-                FormId: fid,
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                Flags: f,
-                Key: k,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let fid = reader.u16("FormId")?;
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let f = reader.u8("Flags")?;
+    let k = reader.u16("Key")?;
+    reader.finish(IfrRestoreDefaults {
+        FormId: fid,
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        Flags: f,
+        Key: k,
+    })
 }
 
 //
@@ -1072,17 +1227,15 @@ pub struct IfrBanner {
 }
 
 pub fn ifr_banner(input: &[u8]) -> IResult<&[u8], IfrBanner> {
-    do_parse!(
-        input,
-        tsid: le_u16
-            >> ln: le_u16
-            >> a: le_u8
-            >> (IfrBanner {
-                TitleStringId: tsid,
-                LineNumber: ln,
-                Alignment: a,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let tsid = reader.u16("TitleStringId")?;
+    let ln = reader.u16("LineNumber")?;
+    let a = reader.u8("Alignment")?;
+    reader.finish(IfrBanner {
+        TitleStringId: tsid,
+        LineNumber: ln,
+        Alignment: a,
+    })
 }
 
 //
@@ -1096,17 +1249,15 @@ pub struct IfrInventory {
 }
 
 pub fn ifr_inventory(input: &[u8]) -> IResult<&[u8], IfrInventory> {
-    do_parse!(
-        input,
-        h: le_u16
-            >> t: le_u16
-            >> t2: le_u16
-            >> (IfrInventory {
-                HelpStringId: h,
-                TextStringId: t,
-                TextTwoStringId: t2,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let h = reader.u16("HelpStringId")?;
+    let t = reader.u16("TextStringId")?;
+    let t2 = reader.u16("TextTwoStringId")?;
+    reader.finish(IfrInventory {
+        HelpStringId: h,
+        TextStringId: t,
+        TextTwoStringId: t2,
+    })
 }
 
 //
@@ -1119,15 +1270,13 @@ pub struct IfrEqVarVal {
 }
 
 pub fn ifr_eq_var_val(input: &[u8]) -> IResult<&[u8], IfrEqVarVal> {
-    do_parse!(
-        input,
-        var: le_u16
-            >> val: le_u16
-            >> (IfrEqVarVal {
-                VariableId: var,
-                Value: val,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let var = reader.u16("VariableId")?;
+    let val = reader.u16("Value")?;
+    reader.finish(IfrEqVarVal {
+        VariableId: var,
+        Value: val,
+    })
 }
 
 //
@@ -1142,40 +1291,33 @@ pub struct IfrOrderedList {
 }
 
 pub fn ifr_ordered_list(input: &[u8]) -> IResult<&[u8], IfrOrderedList> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> me: le_u8
-            >> psid: le_u16
-            >> hsid: le_u16
-            >> (IfrOrderedList {
-                QuestionId: qid,
-                MaxEntries: me,
-                PromptStringId: psid,
-                HelpStringId: hsid,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let me = reader.u8("MaxEntries")?;
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    reader.finish(IfrOrderedList {
+        QuestionId: qid,
+        MaxEntries: me,
+        PromptStringId: psid,
+        HelpStringId: hsid,
+    })
 }
 
 //
 //0x24 => IfrOpcode::VarStore
 //
-named!(
-    ascii_string<Vec<u8>>,
-    map!(many_till!(le_u8, verify!(le_u8, |n: u8| n == 0)), |(
-        mut v,
-        n,
-    )| {
-        v.push(n);
-        v
-    })
-);
+// ASCII string without its terminating zero, which is consumed
+fn ascii_string(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    let mut reader = Reader::new(input);
+    let s = reader.zero_terminated("String")?;
+    reader.finish(s)
+}
 
 pub fn string_ascii(input: &[u8]) -> IResult<&[u8], String> {
-    do_parse!(
-        input,
-        s: ascii_string >> (String::from_utf8_lossy(&s[..s.len() - 1]).to_string())
-    )
+    let mut reader = Reader::new(input);
+    let s = reader.parse(ascii_string)?;
+    reader.finish(String::from_utf8_lossy(s).to_string())
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -1187,19 +1329,17 @@ pub struct IfrVarStore {
 }
 
 pub fn ifr_var_store(input: &[u8]) -> IResult<&[u8], IfrVarStore> {
-    do_parse!(
-        input,
-        g: guid
-            >> vsid: le_u16
-            >> size: le_u16
-            >> name: string_ascii
-            >> (IfrVarStore {
-                Guid: g,
-                VarStoreId: vsid,
-                Size: size,
-                Name: name,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let g = reader.parse(guid)?;
+    let vsid = reader.u16("VarStoreId")?;
+    let size = reader.u16("Size")?;
+    let name = reader.parse(string_ascii)?;
+    reader.finish(IfrVarStore {
+        Guid: g,
+        VarStoreId: vsid,
+        Size: size,
+        Name: name,
+    })
 }
 
 //
@@ -1211,10 +1351,9 @@ pub struct IfrVarStoreSelect {
 }
 
 pub fn ifr_var_store_select(input: &[u8]) -> IResult<&[u8], IfrVarStoreSelect> {
-    do_parse!(
-        input,
-        vsid: le_u16 >> (IfrVarStoreSelect { VarStoreId: vsid })
-    )
+    let mut reader = Reader::new(input);
+    let vsid = reader.u16("VarStoreId")?;
+    reader.finish(IfrVarStoreSelect { VarStoreId: vsid })
 }
 
 //
@@ -1227,15 +1366,13 @@ pub struct IfrVarStoreSelectPair {
 }
 
 pub fn ifr_var_store_select_pair(input: &[u8]) -> IResult<&[u8], IfrVarStoreSelectPair> {
-    do_parse!(
-        input,
-        vsid: le_u16
-            >> vsid2: le_u16
-            >> (IfrVarStoreSelectPair {
-                VarStoreId: vsid,
-                SecondaryVarStoreId: vsid2,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let vsid = reader.u16("VarStoreId")?;
+    let vsid2 = reader.u16("SecondaryVarStoreId")?;
+    reader.finish(IfrVarStoreSelectPair {
+        VarStoreId: vsid,
+        SecondaryVarStoreId: vsid2,
+    })
 }
 
 //0x27 => IfrOpcode::True
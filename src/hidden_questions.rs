@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use form_links;
+use form_links::Link;
+use storage_overlaps;
+use uefi_model::{ConditionKind, ExpressionOp, FormSet, Question, VarStoreKind};
+use visibility;
+use visibility::Context;
+
+//
+// Questions a user can't see in the setup browser
+//
+pub enum HiddenReason {
+    Unconditional(usize), // Index of a SuppressIf or DisableIf condition over a constant true expression
+    UnreachableForm,      // No Ref leads to the form of the question
+    HiddenRefsOnly,       // All Refs that lead to the form of the question are hidden at defaults
+    AtDefaults(usize),    // Index of a SuppressIf or DisableIf condition that is true at defaults
+}
+
+pub struct HiddenQuestion<'a> {
+    pub question: &'a Question,
+    pub reasons: Vec<HiddenReason>,
+}
+
+// Expressions that don't depend on any question or variable
+fn is_constant(expression: &[ExpressionOp]) -> bool {
+    !expression.iter().any(|op| {
+        matches!(
+            *op,
+            ExpressionOp::EqIdVal { .. }
+                | ExpressionOp::EqIdId { .. }
+                | ExpressionOp::EqIdValList { .. }
+                | ExpressionOp::QuestionRef(_)
+                | ExpressionOp::QuestionRefFromStack
+                | ExpressionOp::Get { .. }
+                | ExpressionOp::Other { .. }
+        )
+    })
+}
+
+// Index of the first condition of a question that hides it, constant or not
+fn hiding_condition(context: &Context, question: &Question, constant: bool) -> Option<usize> {
+    question.conditions.iter().cloned().find(|&index| {
+        let condition = match context.form_set.conditions.get(index) {
+            Some(c) => c,
+            None => return false,
+        };
+        condition.kind != ConditionKind::GrayOutIf
+            && is_constant(&condition.expression) == constant
+            && visibility::evaluate(context, &condition.expression).as_bool() == Some(true)
+    })
+}
+
+// Finds hidden questions in all form sets, Refs to other form sets are resolved among them
+pub fn hidden_questions<'a>(form_sets: &[&'a FormSet]) -> Vec<Vec<HiddenQuestion<'a>>> {
+    let buffers = HashMap::new();
+    let contexts: Vec<Context> = form_sets
+        .iter()
+        .map(|form_set| Context {
+            form_set,
+            buffers: &buffers,
+            default_id: 0,
+        })
+        .collect();
+
+    let links = form_links::links(form_sets);
+    let reachable = form_links::reachable_forms(form_sets, &links, &|_| true);
+    let visibly_reachable = form_links::reachable_forms(form_sets, &links, &|link: &Link| {
+        let context = &contexts[link.form_set_index];
+        hiding_condition(context, link.question, true).is_none()
+            && hiding_condition(context, link.question, false).is_none()
+    });
+
+    let mut result = Vec::new();
+    for (index, context) in contexts.iter().enumerate() {
+        let form_set: &'a FormSet = form_sets[index];
+        let mut hidden = Vec::new();
+        for question in &form_set.questions {
+            let mut reasons = Vec::new();
+            if let Some(condition) = hiding_condition(context, question, true) {
+                reasons.push(HiddenReason::Unconditional(condition));
+            }
+            if !reachable[index].contains(&question.form_id) {
+                reasons.push(HiddenReason::UnreachableForm);
+            } else if !visibly_reachable[index].contains(&question.form_id) {
+                reasons.push(HiddenReason::HiddenRefsOnly);
+            }
+            if let Some(condition) = hiding_condition(context, question, false) {
+                reasons.push(HiddenReason::AtDefaults(condition));
+            }
+            if !reasons.is_empty() {
+                hidden.push(HiddenQuestion { question, reasons });
+            }
+        }
+        result.push(hidden);
+    }
+    result
+}
+
+pub fn storage_text(form_set: &FormSet, question: &Question, strings_map: &HashMap<u16, String>) -> String {
+    let var_store = match form_set.var_store(question.var_store_id) {
+        Some(v) => v,
+        None => return String::from("no storage"),
+    };
+    let location = if var_store.kind == VarStoreKind::NameValue {
+        format!(
+            "name \"{}\"",
+            strings_map
+                .get(&question.var_offset)
+                .unwrap_or(&String::from("InvalidId"))
+        )
+    } else if question.width == 0 {
+        return String::from("no storage");
+    } else {
+        storage_overlaps::storage_text(question)
+    };
+    format!(
+        "VarStore \"{}\" (VarStoreId: 0x{:X}) at {}",
+        var_store.name, var_store.var_store_id, location
+    )
+}
+
+fn reason_text(form_set: &FormSet, question: &Question, reason: &HiddenReason, strings_map: &HashMap<u16, String>) -> String {
+    let condition_text = |index: usize| match form_set.conditions.get(index) {
+        Some(c) => format!("{:?} at offset 0x{:X}", c.kind, c.offset),
+        None => String::from("unknown condition"),
+    };
+    let form_title = form_set
+        .form(question.form_id)
+        .and_then(|f| strings_map.get(&f.title_string_id))
+        .map(|s| s.as_str())
+        .unwrap_or("InvalidId");
+    match *reason {
+        HiddenReason::Unconditional(index) => format!("always hidden by {}", condition_text(index)),
+        HiddenReason::UnreachableForm => format!(
+            "form 0x{:X} \"{}\" is not reachable by any Ref",
+            question.form_id, form_title
+        ),
+        HiddenReason::HiddenRefsOnly => format!(
+            "form 0x{:X} \"{}\" is only reachable by Refs hidden at defaults",
+            question.form_id, form_title
+        ),
+        HiddenReason::AtDefaults(index) => format!("hidden at defaults by {}", condition_text(index)),
+    }
+}
+
+pub fn hidden_report(form_set: &FormSet, hidden: &[HiddenQuestion], strings_map: &HashMap<u16, String>) -> String {
+    let mut report = String::new();
+    writeln!(
+        &mut report,
+        "FormSet Guid: {}, Title: \"{}\", Hidden questions: {}",
+        form_set.guid,
+        strings_map
+            .get(&form_set.title_string_id)
+            .unwrap_or(&String::from("InvalidId")),
+        hidden.len()
+    )
+    .unwrap();
+
+    for entry in hidden {
+        let question = entry.question;
+        writeln!(
+            &mut report,
+            "\t{:?} \"{}\", QuestionId: 0x{:X}, FormId: 0x{:X}, {}",
+            question.kind,
+            strings_map
+                .get(&question.prompt_string_id)
+                .unwrap_or(&String::from("InvalidId")),
+            question.question_id,
+            question.form_id,
+            storage_text(form_set, question, strings_map)
+        )
+        .unwrap();
+        for reason in &entry.reasons {
+            writeln!(&mut report, "\t\tReason: {}", reason_text(form_set, question, reason, strings_map)).unwrap();
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uefi_model::test_models::{default, form, form_set, question, reference};
+    use uefi_model::{Condition, QuestionKind};
+
+    fn conditional(question_id: u16, form_id: u16, condition: usize) -> Question {
+        let mut question = question(QuestionKind::CheckBox, question_id, 0, 0, 0);
+        question.form_id = form_id;
+        question.conditions.push(condition);
+        question
+    }
+
+    fn reason_names(hidden: &HiddenQuestion) -> Vec<&'static str> {
+        hidden
+            .reasons
+            .iter()
+            .map(|r| match *r {
+                HiddenReason::Unconditional(_) => "Unconditional",
+                HiddenReason::UnreachableForm => "UnreachableForm",
+                HiddenReason::HiddenRefsOnly => "HiddenRefsOnly",
+                HiddenReason::AtDefaults(_) => "AtDefaults",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hidden_questions_with_their_reasons() {
+        let mut selector = question(QuestionKind::OneOf, 1, 0, 0, 0);
+        selector.defaults.push(default(0, 1));
+        let mut hidden_ref = reference(3, 1, 3);
+        hidden_ref.conditions.push(2);
+        let mut form_set = form_set(
+            Vec::new(),
+            vec![
+                selector,
+                reference(2, 1, 2),
+                hidden_ref,
+                conditional(4, 1, 0),
+                conditional(5, 1, 1),
+                conditional(6, 1, 2),
+                conditional(7, 2, 3),
+                conditional(8, 3, 3),
+                conditional(9, 4, 3),
+            ],
+        );
+        form_set.forms.extend(vec![form(2, 0), form(3, 0), form(4, 0)]);
+        let condition = |kind, expression| Condition {
+            kind,
+            expression,
+            offset: 0,
+        };
+        form_set.conditions = vec![
+            // Any non-zero constant is true
+            condition(ConditionKind::SuppressIf, vec![ExpressionOp::Uint(2)]),
+            condition(ConditionKind::GrayOutIf, vec![ExpressionOp::Boolean(true)]),
+            condition(ConditionKind::DisableIf, vec![ExpressionOp::EqIdVal { question_id: 1, value: 1 }]),
+            condition(ConditionKind::SuppressIf, vec![ExpressionOp::Boolean(false)]),
+        ];
+
+        let hidden = hidden_questions(&[&form_set]);
+        let hidden: Vec<(u16, Vec<&str>)> = hidden[0]
+            .iter()
+            .map(|h| (h.question.question_id, reason_names(h)))
+            .collect();
+        assert_eq!(
+            hidden,
+            vec![
+                (3, vec!["AtDefaults"]),
+                (4, vec!["Unconditional"]),
+                (6, vec!["AtDefaults"]),
+                (8, vec!["HiddenRefsOnly"]),
+                (9, vec!["UnreachableForm"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn constant_expressions() {
+        assert!(is_constant(&[ExpressionOp::Uint(1), ExpressionOp::Uint(1), ExpressionOp::Equal]));
+        assert!(!is_constant(&[ExpressionOp::QuestionRef(1), ExpressionOp::Not]));
+        assert!(!is_constant(&[ExpressionOp::Other { opcode: 0x50, operands: 0 }]));
+    }
+}
@@ -0,0 +1,567 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use form_links;
+use framework_parser;
+use uefi_model::{FormSet, QuestionKind, VarStoreKind};
+use uefi_parser;
+use uefi_parser::Guid;
+
+//
+// Consistency checks of IFR data
+//
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+pub struct Issue {
+    pub severity: Severity,
+    pub offset: usize, // Offset of the operation in the input file
+    pub check: &'static str,
+    pub message: String,
+}
+
+impl Issue {
+    pub fn new(severity: Severity, offset: usize, check: &'static str, message: String) -> Issue {
+        Issue {
+            severity,
+            offset,
+            check,
+            message,
+        }
+    }
+}
+
+// One issue per line as path:offset: severity[check]: message
+pub fn issue_text(path: &str, issue: &Issue) -> String {
+    format!(
+        "{}:0x{:X}: {}[{}]: {}",
+        path, issue.offset, issue.severity, issue.check, issue.message
+    )
+}
+
+const ZERO_GUID: Guid = Guid {
+    data1: 0,
+    data2: 0,
+    data3: 0,
+    data4: [0; 8],
+};
+
+// Bytes of a form package the parser skipped, base_offset is the offset of the first operation
+pub fn damaged_issue(base_offset: usize, region: &uefi_parser::DamagedRegion) -> Issue {
+    let message = if region.truncated {
+        format!("{} byte(s) at the end of the form package can't be parsed as IFR operations", region.length)
+    } else {
+        format!("{} byte(s) of the form package can't be parsed as IFR operations and were skipped", region.length)
+    };
+    Issue::new(Severity::Error, base_offset + region.offset, "parse", message)
+}
+
+// Every operation with ScopeStart set must be closed by an End operation
+pub fn uefi_scope_issues(
+    operations: &[uefi_parser::IfrOperation],
+    offsets: &[usize],
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut scopes: Vec<(uefi_parser::IfrOpcode, usize)> = Vec::new();
+
+    for (operation, &offset) in operations.iter().zip(offsets) {
+        if operation.OpCode == uefi_parser::IfrOpcode::End {
+            if scopes.pop().is_none() {
+                issues.push(Issue::new(
+                    Severity::Error,
+                    offset,
+                    "scope",
+                    String::from("End without an open scope"),
+                ));
+            }
+        } else if operation.ScopeStart {
+            scopes.push((operation.OpCode, offset));
+        }
+    }
+
+    for (opcode, scope_offset) in scopes {
+        issues.push(Issue::new(
+            Severity::Error,
+            scope_offset,
+            "scope",
+            format!("Scope of {opcode:?} is never closed"),
+        ));
+    }
+    issues
+}
+
+// Framework IFR has no scopes, but forms, form sets, conditions and option lists have their own end operations
+pub fn framework_scope_issues(
+    operations: &[framework_parser::IfrOperation],
+    offsets: &[usize],
+) -> Vec<Issue> {
+    use framework_parser::IfrOpcode;
+
+    let mut issues = Vec::new();
+    let mut scopes: Vec<(IfrOpcode, usize)> = Vec::new();
+
+    for (operation, &offset) in operations.iter().zip(offsets) {
+        let opener = match operation.OpCode {
+            IfrOpcode::EndFormSet => Some(&[IfrOpcode::FormSet][..]),
+            IfrOpcode::EndForm => Some(&[IfrOpcode::Form][..]),
+            IfrOpcode::EndIf => Some(
+                &[
+                    IfrOpcode::SuppressIf,
+                    IfrOpcode::GrayOutIf,
+                    IfrOpcode::InconsistentIf,
+                ][..],
+            ),
+            IfrOpcode::End => Some(&[IfrOpcode::OneOf, IfrOpcode::OrderedList][..]),
+            _ => None,
+        };
+        match opener {
+            Some(openers) => match scopes.last() {
+                Some(&(opcode, _)) if openers.contains(&opcode) => {
+                    scopes.pop();
+                }
+                Some(&(opcode, scope_offset)) => issues.push(Issue::new(
+                    Severity::Error,
+                    offset,
+                    "scope",
+                    format!(
+                        "{:?} closes {:?} at 0x{:X}",
+                        operation.OpCode, opcode, scope_offset
+                    ),
+                )),
+                None => issues.push(Issue::new(
+                    Severity::Error,
+                    offset,
+                    "scope",
+                    format!("{:?} without an open scope", operation.OpCode),
+                )),
+            },
+            None => match operation.OpCode {
+                IfrOpcode::FormSet
+                | IfrOpcode::Form
+                | IfrOpcode::SuppressIf
+                | IfrOpcode::GrayOutIf
+                | IfrOpcode::InconsistentIf
+                | IfrOpcode::OneOf
+                | IfrOpcode::OrderedList => scopes.push((operation.OpCode, offset)),
+                _ => {}
+            },
+        }
+    }
+
+    for (opcode, scope_offset) in scopes {
+        issues.push(Issue::new(
+            Severity::Error,
+            scope_offset,
+            "scope",
+            format!("{opcode:?} is never closed"),
+        ));
+    }
+    issues
+}
+
+fn string_issue(
+    strings_map: &HashMap<u16, String>,
+    string_id: u16,
+    offset: usize,
+    what: &str,
+) -> Option<Issue> {
+    if string_id == 0 || strings_map.contains_key(&string_id) {
+        return None;
+    }
+    Some(Issue::new(
+        Severity::Warning,
+        offset,
+        "string",
+        format!("StringId 0x{string_id:X} of {what} is missing from the string package"),
+    ))
+}
+
+// Checks the models of all form sets, Framework form sets are marked in the second slice
+// because their QuestionIds are offsets and can repeat
+pub fn model_issues(
+    form_sets: &[(FormSet, &HashMap<u16, String>)],
+    framework: &[bool],
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let models: Vec<&FormSet> = form_sets.iter().map(|(form_set, _)| form_set).collect();
+
+    // Refs to forms that don't exist, form sets that aren't in the image may be in another driver
+    for link in form_links::links(&models) {
+        let target = match (link.target, link.question.ref_target.as_ref()) {
+            (None, Some(t)) => t,
+            _ => continue,
+        };
+        let form_id = match target.form_id {
+            Some(id) => id,
+            None => continue,
+        };
+        if target.device_path_id.unwrap_or(0) != 0 {
+            continue;
+        }
+        let form_set = models[link.form_set_index];
+        match target.form_set_guid {
+            Some(guid)
+                if guid != ZERO_GUID
+                    && guid != form_set.guid
+                    && !models.iter().any(|fs| fs.guid == guid) =>
+            {
+                issues.push(Issue::new(
+                    Severity::Warning,
+                    link.question.offset,
+                    "ref",
+                    format!(
+                        "Ref to FormId 0x{form_id:X} of form set {guid} not found in this image"
+                    ),
+                ))
+            }
+            _ => issues.push(Issue::new(
+                Severity::Error,
+                link.question.offset,
+                "ref",
+                format!("Ref to non-existent FormId 0x{form_id:X}"),
+            )),
+        }
+    }
+
+    for (index, (form_set, strings_map)) in form_sets.iter().enumerate() {
+        let framework = framework.get(index).cloned().unwrap_or(false);
+
+        issues.extend(string_issue(
+            strings_map,
+            form_set.title_string_id,
+            form_set.offset,
+            "form set title",
+        ));
+        issues.extend(string_issue(
+            strings_map,
+            form_set.help_string_id,
+            form_set.offset,
+            "form set help",
+        ));
+        for form in &form_set.forms {
+            issues.extend(string_issue(
+                strings_map,
+                form.title_string_id,
+                form.offset,
+                "form title",
+            ));
+        }
+        for statement in &form_set.statements {
+            issues.extend(string_issue(
+                strings_map,
+                statement.prompt_string_id,
+                statement.offset,
+                "statement prompt",
+            ));
+            issues.extend(string_issue(
+                strings_map,
+                statement.help_string_id,
+                statement.offset,
+                "statement help",
+            ));
+            if let Some(id) = statement.text_string_id {
+                issues.extend(string_issue(
+                    strings_map,
+                    id,
+                    statement.offset,
+                    "statement text",
+                ));
+            }
+        }
+
+        for (question_index, question) in form_set.questions.iter().enumerate() {
+            let offset = question.offset;
+            issues.extend(string_issue(
+                strings_map,
+                question.prompt_string_id,
+                offset,
+                "question prompt",
+            ));
+            issues.extend(string_issue(
+                strings_map,
+                question.help_string_id,
+                offset,
+                "question help",
+            ));
+            for option in &question.options {
+                issues.extend(string_issue(
+                    strings_map,
+                    option.string_id,
+                    offset,
+                    "option",
+                ));
+            }
+
+            if !framework
+                && question.question_id != 0
+                && form_set.questions[..question_index]
+                    .iter()
+                    .any(|q| q.question_id == question.question_id)
+            {
+                issues.push(Issue::new(
+                    Severity::Error,
+                    offset,
+                    "question-id",
+                    format!("Duplicate QuestionId 0x{:X}", question.question_id),
+                ));
+            }
+
+            // Storage
+            match form_set.var_store(question.var_store_id) {
+                None if question.var_store_id != 0 => issues.push(Issue::new(
+                    Severity::Error,
+                    offset,
+                    "varstore",
+                    format!("Undefined VarStoreId 0x{:X}", question.var_store_id),
+                )),
+                Some(var_store) if var_store.kind == VarStoreKind::NameValue => {
+                    issues.extend(string_issue(
+                        strings_map,
+                        question.var_offset,
+                        offset,
+                        "variable name",
+                    ));
+                }
+                Some(var_store) => {
+                    if let Some(size) = var_store.size {
+                        if question.byte_offset() + question.width > size as usize {
+                            issues.push(Issue::new(
+                                Severity::Error,
+                                offset,
+                                "varstore",
+                                format!(
+                                    "Storage at 0x{:X}, {} byte(s) is beyond the size 0x{:X} of VarStore \"{}\"",
+                                    question.byte_offset(),
+                                    question.width,
+                                    size,
+                                    var_store.name
+                                ),
+                            ));
+                        }
+                    }
+                }
+                None => {}
+            }
+
+            // Defaults
+            let mut default_ids: Vec<u16> =
+                question.defaults.iter().map(|d| d.default_id).collect();
+            default_ids.sort_unstable();
+            let mut duplicates: Vec<u16> = default_ids
+                .windows(2)
+                .filter(|pair| pair[0] == pair[1])
+                .map(|pair| pair[0])
+                .collect();
+            duplicates.dedup();
+            for default_id in duplicates {
+                issues.push(Issue::new(
+                    Severity::Error,
+                    offset,
+                    "default",
+                    format!("Multiple defaults for DefaultId 0x{default_id:X}"),
+                ));
+            }
+            if question.kind == QuestionKind::OneOf {
+                if question.defaults.is_empty() {
+                    issues.push(Issue::new(
+                        Severity::Warning,
+                        offset,
+                        "default",
+                        String::from("OneOf has no default"),
+                    ));
+                }
+                for default in &question.defaults {
+                    if !question.options.iter().any(|o| o.value == default.value) {
+                        issues.push(Issue::new(
+                            Severity::Warning,
+                            offset,
+                            "default",
+                            format!(
+                                "Default 0x{:X} for DefaultId 0x{:X} is not an option",
+                                default.value, default.default_id
+                            ),
+                        ));
+                    }
+                }
+            }
+            if let (QuestionKind::Numeric, Some(min), Some(max)) =
+                (question.kind, question.minimum, question.maximum)
+            {
+                for default in &question.defaults {
+                    if default.value < min || default.value > max {
+                        issues.push(Issue::new(
+                            Severity::Error,
+                            offset,
+                            "default",
+                            format!(
+                                "Default 0x{:X} for DefaultId 0x{:X} is outside of Min 0x{:X}, Max 0x{:X}",
+                                default.value, default.default_id, min, max
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uefi_model::test_models::{buffer, default, form_set, guid, option, question, reference};
+
+    fn messages(issues: &[Issue]) -> Vec<String> {
+        issues.iter().map(|i| issue_text("file", i)).collect()
+    }
+
+    fn uefi_operation(opcode: uefi_parser::IfrOpcode, scope_start: bool) -> uefi_parser::IfrOperation<'static> {
+        uefi_parser::IfrOperation {
+            OpCode: opcode,
+            Length: 2,
+            ScopeStart: scope_start,
+            Data: None,
+        }
+    }
+
+    fn framework_operation(opcode: framework_parser::IfrOpcode) -> framework_parser::IfrOperation<'static> {
+        framework_parser::IfrOperation {
+            OpCode: opcode,
+            Length: 2,
+            Data: None,
+        }
+    }
+
+    #[test]
+    fn uefi_scopes() {
+        use uefi_parser::IfrOpcode;
+
+        let operations = vec![
+            uefi_operation(IfrOpcode::FormSet, true),
+            uefi_operation(IfrOpcode::Form, true),
+            uefi_operation(IfrOpcode::Subtitle, true),
+            uefi_operation(IfrOpcode::End, false),
+            uefi_operation(IfrOpcode::End, false),
+        ];
+        assert_eq!(
+            messages(&uefi_scope_issues(&operations, &[0, 2, 4, 6, 8])),
+            vec!["file:0x0: error[scope]: Scope of FormSet is never closed"]
+        );
+        let operations = vec![uefi_operation(IfrOpcode::End, false)];
+        assert_eq!(
+            messages(&uefi_scope_issues(&operations, &[0x10])),
+            vec!["file:0x10: error[scope]: End without an open scope"]
+        );
+    }
+
+    #[test]
+    fn framework_scopes() {
+        use framework_parser::IfrOpcode;
+
+        let operations = vec![
+            framework_operation(IfrOpcode::FormSet),
+            framework_operation(IfrOpcode::Form),
+            framework_operation(IfrOpcode::SuppressIf),
+            framework_operation(IfrOpcode::OneOf),
+            framework_operation(IfrOpcode::End),
+            framework_operation(IfrOpcode::EndForm),
+            framework_operation(IfrOpcode::EndIf),
+            framework_operation(IfrOpcode::EndFormSet),
+        ];
+        assert_eq!(
+            messages(&framework_scope_issues(&operations, &[0, 2, 4, 6, 8, 10, 12, 14])),
+            vec![
+                "file:0xA: error[scope]: EndForm closes SuppressIf at 0x4",
+                "file:0xE: error[scope]: EndFormSet closes Form at 0x2",
+                "file:0x0: error[scope]: FormSet is never closed",
+                "file:0x2: error[scope]: Form is never closed",
+            ]
+        );
+        let operations = vec![framework_operation(IfrOpcode::EndIf)];
+        assert_eq!(
+            messages(&framework_scope_issues(&operations, &[0])),
+            vec!["file:0x0: error[scope]: EndIf without an open scope"]
+        );
+    }
+
+    #[test]
+    fn damaged_regions() {
+        let region = uefi_parser::DamagedRegion {
+            offset: 4,
+            length: 3,
+            truncated: true,
+        };
+        assert_eq!(
+            issue_text("file", &damaged_issue(0x100, &region)),
+            "file:0x104: error[parse]: 3 byte(s) at the end of the form package can't be parsed as IFR operations"
+        );
+    }
+
+    #[test]
+    fn refs() {
+        let mut to_other = reference(3, 1, 1);
+        to_other.ref_target.as_mut().unwrap().form_set_guid = Some(guid(0xF1));
+        let mut to_device = reference(4, 1, 1);
+        to_device.ref_target.as_mut().unwrap().device_path_id = Some(1);
+        let mut form_set = form_set(Vec::new(), vec![reference(1, 1, 1), reference(2, 1, 5), to_other, to_device]);
+        for (offset, question) in form_set.questions.iter_mut().enumerate() {
+            question.offset = offset;
+        }
+        let strings: HashMap<u16, String> = (1..5).map(|id| (id, String::new())).collect();
+        assert_eq!(
+            messages(&model_issues(&[(form_set, &strings)], &[false])),
+            vec![
+                "file:0x1: error[ref]: Ref to non-existent FormId 0x5",
+                "file:0x2: warning[ref]: Ref to FormId 0x1 of form set 000000F1-0000-0000-0000-000000000000 not found in this image",
+            ]
+        );
+    }
+
+    #[test]
+    fn questions() {
+        let mut one_of = question(QuestionKind::OneOf, 1, 1, 0, 1);
+        one_of.options = vec![option(1, 0), option(9, 1)];
+        one_of.defaults = vec![default(0, 1), default(0, 2)];
+        let mut numeric = question(QuestionKind::Numeric, 2, 1, 7, 2);
+        numeric.minimum = Some(1);
+        numeric.maximum = Some(10);
+        numeric.defaults = vec![default(0, 0)];
+        let mut duplicate = question(QuestionKind::OneOf, 1, 3, 0, 1);
+        duplicate.defaults = vec![];
+        let questions = vec![one_of, numeric, duplicate];
+        let strings: HashMap<u16, String> = vec![(1, String::new()), (2, String::new())].into_iter().collect();
+
+        let uefi = form_set(vec![buffer(1, "Setup", 8)], questions.clone());
+        assert_eq!(
+            messages(&model_issues(&[(uefi, &strings)], &[false])),
+            vec![
+                "file:0x0: warning[string]: StringId 0x9 of option is missing from the string package",
+                "file:0x0: error[default]: Multiple defaults for DefaultId 0x0",
+                "file:0x0: warning[default]: Default 0x2 for DefaultId 0x0 is not an option",
+                "file:0x0: error[varstore]: Storage at 0x7, 2 byte(s) is beyond the size 0x8 of VarStore \"Setup\"",
+                "file:0x0: error[default]: Default 0x0 for DefaultId 0x0 is outside of Min 0x1, Max 0xA",
+                "file:0x0: error[question-id]: Duplicate QuestionId 0x1",
+                "file:0x0: error[varstore]: Undefined VarStoreId 0x3",
+                "file:0x0: warning[default]: OneOf has no default",
+            ]
+        );
+
+        // QuestionIds of Framework questions are offsets and can repeat
+        let framework = form_set(vec![buffer(1, "Setup", 8)], questions);
+        let issues = messages(&model_issues(&[(framework, &strings)], &[true]));
+        assert!(!issues.iter().any(|i| i.contains("question-id")));
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use form_links;
+use hidden_questions;
+use setting_search;
+use storage_overlaps;
+use uefi_model::{FormSet, Question, QuestionKind, VarStoreKind};
+use uefi_parser::Guid;
+use visibility;
+use visibility::{Context, Visibility};
+
+//
+// Setting-level differences between two images
+//
+pub struct Setting<'a> {
+    pub form_set: &'a FormSet,
+    pub strings_map: &'a HashMap<u16, String>,
+    pub question: &'a Question,
+    pub prompt: String,
+    pub storage: Option<String>, // Varstore name, GUID and location used to match questions, None without storage
+    pub path: String,
+    pub visibility: Visibility, // At defaults
+}
+
+pub struct Change<'a> {
+    pub old: Setting<'a>,
+    pub new: Setting<'a>,
+    pub differences: Vec<String>,
+}
+
+pub struct Diff<'a> {
+    pub added: Vec<Setting<'a>>,
+    pub removed: Vec<Setting<'a>>,
+    pub changed: Vec<Change<'a>>,
+    pub unchanged: usize,
+}
+
+fn string(strings_map: &HashMap<u16, String>, string_id: u16) -> &str {
+    strings_map
+        .get(&string_id)
+        .map(|s| s.as_str())
+        .unwrap_or("InvalidId")
+}
+
+fn storage_key(form_set: &FormSet, question: &Question, strings_map: &HashMap<u16, String>) -> Option<String> {
+    let var_store = form_set.var_store(question.var_store_id)?;
+    if var_store.kind == VarStoreKind::NameValue {
+        return Some(format!(
+            "\"{}\" {} name \"{}\"",
+            var_store.name,
+            var_store.guid,
+            string(strings_map, question.var_offset)
+        ));
+    }
+    if question.width == 0 {
+        return None;
+    }
+    Some(format!(
+        "\"{}\" {} {}",
+        var_store.name,
+        var_store.guid,
+        storage_overlaps::storage_text(question)
+    ))
+}
+
+// All questions of all form sets of an image, Refs without storage are included to track menu changes
+pub fn settings<'a>(form_sets: &'a [(FormSet, &'a HashMap<u16, String>)]) -> Vec<Setting<'a>> {
+    let models: Vec<&FormSet> = form_sets.iter().map(|(form_set, _)| form_set).collect();
+    let links = form_links::links(&models);
+    let paths = form_links::form_paths(&models, &links);
+    let buffers = HashMap::new();
+
+    let mut result = Vec::new();
+    for (index, (form_set, strings_map)) in form_sets.iter().enumerate() {
+        let context = Context {
+            form_set,
+            buffers: &buffers,
+            default_id: 0,
+        };
+        for question in &form_set.questions {
+            result.push(Setting {
+                form_set,
+                strings_map,
+                question,
+                prompt: string(strings_map, question.prompt_string_id).to_string(),
+                storage: storage_key(form_set, question, strings_map),
+                path: setting_search::form_path(form_sets, &paths, index, question.form_id),
+                visibility: visibility::question_visibility(&context, question).visibility,
+            });
+        }
+    }
+    result
+}
+
+fn option_map(setting: &Setting) -> Vec<(u64, String)> {
+    setting
+        .question
+        .options
+        .iter()
+        .map(|o| (o.value, string(setting.strings_map, o.string_id).to_string()))
+        .collect()
+}
+
+fn differences(old: &Setting, new: &Setting) -> Vec<String> {
+    let mut result = Vec::new();
+
+    if old.prompt != new.prompt {
+        result.push(format!("prompt: \"{}\" -> \"{}\"", old.prompt, new.prompt));
+    }
+    if old.question.kind != new.question.kind {
+        result.push(format!("kind: {:?} -> {:?}", old.question.kind, new.question.kind));
+    }
+    if old.storage != new.storage {
+        result.push(format!(
+            "storage: {} -> {}",
+            hidden_questions::storage_text(old.form_set, old.question, old.strings_map),
+            hidden_questions::storage_text(new.form_set, new.question, new.strings_map)
+        ));
+    }
+    if old.path != new.path {
+        result.push(format!("form: {} -> {}", old.path, new.path));
+    }
+    if old.visibility != new.visibility {
+        result.push(format!(
+            "visibility at defaults: {:?} -> {:?}",
+            old.visibility, new.visibility
+        ));
+    }
+
+    let old_defaults = storage_overlaps::default_text(old.question);
+    let new_defaults = storage_overlaps::default_text(new.question);
+    if old_defaults != new_defaults {
+        result.push(format!(
+            "defaults: [{}] -> [{}]",
+            old_defaults.join(", "),
+            new_defaults.join(", ")
+        ));
+    }
+
+    let old_options = option_map(old);
+    let new_options = option_map(new);
+    for (value, text) in &old_options {
+        match new_options.iter().find(|(v, _)| v == value) {
+            None => result.push(format!("option removed: 0x{value:X} \"{text}\"")),
+            Some((_, new_text)) if new_text != text => {
+                result.push(format!("option 0x{value:X}: \"{text}\" -> \"{new_text}\""))
+            }
+            _ => {}
+        }
+    }
+    for (value, text) in &new_options {
+        if !old_options.iter().any(|(v, _)| v == value) {
+            result.push(format!("option added: 0x{value:X} \"{text}\""));
+        }
+    }
+
+    let old_range = storage_overlaps::range_text(old.question);
+    let new_range = storage_overlaps::range_text(new.question);
+    if old_range != new_range {
+        result.push(format!("range: {old_range} -> {new_range}"));
+    }
+
+    if old.question.question_flags != new.question.question_flags || old.question.flags != new.question.flags {
+        result.push(format!(
+            "flags: QuestionFlags 0x{:X}, Flags 0x{:X} -> QuestionFlags 0x{:X}, Flags 0x{:X}",
+            old.question.question_flags, old.question.flags, new.question.question_flags, new.question.flags
+        ));
+    }
+
+    result
+}
+
+// Varstore name and GUID of a question, None without a varstore
+fn var_store_key<'a>(setting: &Setting<'a>) -> Option<(&'a str, Guid)> {
+    let var_store = setting.form_set.var_store(setting.question.var_store_id)?;
+    Some((var_store.name.as_str(), var_store.guid))
+}
+
+// Condition of a matching pass
+type Matches = fn(&Setting, &Setting) -> bool;
+
+// Matches questions of two images, first by storage and prompt, then by storage only,
+// then by prompt and kind within the same varstore or form to find settings that were moved
+pub fn diff<'a>(old: Vec<Setting<'a>>, new: Vec<Setting<'a>>) -> Diff<'a> {
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    let mut old_matched = vec![false; old.len()];
+    let mut new_matched = vec![false; new.len()];
+
+    // New settings indexed by storage and by prompt, candidates keep their order so the first one that matches is used
+    let mut by_storage: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut by_prompt: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (j, b) in new.iter().enumerate() {
+        if let Some(storage) = &b.storage {
+            by_storage.entry(storage.as_str()).or_default().push(j);
+        }
+        by_prompt.entry(b.prompt.as_str()).or_default().push(j);
+    }
+
+    // Each pass looks up candidates by storage or by prompt and checks them with its condition
+    let passes: [(bool, Matches); 3] = [
+        (true, |a, b| a.prompt == b.prompt),
+        (true, |_, _| true),
+        (false, |a, b| {
+            a.question.kind == b.question.kind
+                && ((var_store_key(a).is_some() && var_store_key(a) == var_store_key(b)) || a.path == b.path)
+        }),
+    ];
+    for &(use_storage, matches) in passes.iter() {
+        for (i, a) in old.iter().enumerate() {
+            if old_matched[i] {
+                continue;
+            }
+            let candidates = if use_storage {
+                a.storage.as_ref().and_then(|s| by_storage.get(s.as_str()))
+            } else {
+                by_prompt.get(a.prompt.as_str())
+            };
+            let found = candidates.and_then(|c| {
+                c.iter()
+                    .copied()
+                    .find(|&j| !new_matched[j] && matches(a, &new[j]))
+            });
+            if let Some(j) = found {
+                old_matched[i] = true;
+                new_matched[j] = true;
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs.sort_unstable();
+
+    let mut old: Vec<Option<Setting>> = old.into_iter().map(Some).collect();
+    let mut new: Vec<Option<Setting>> = new.into_iter().map(Some).collect();
+    let mut changed = Vec::new();
+    let mut unchanged = 0;
+    for (i, j) in pairs {
+        let (a, b) = (old[i].take().unwrap(), new[j].take().unwrap());
+        let differences = differences(&a, &b);
+        if differences.is_empty() {
+            unchanged += 1;
+        } else {
+            changed.push(Change {
+                old: a,
+                new: b,
+                differences,
+            });
+        }
+    }
+
+    Diff {
+        added: new.into_iter().flatten().collect(),
+        removed: old.into_iter().flatten().collect(),
+        changed,
+        unchanged,
+    }
+}
+
+fn setting_text(setting: &Setting) -> String {
+    let mut text = format!(
+        "{:?} \"{}\", QuestionId: 0x{:X}, {}",
+        setting.question.kind, setting.prompt, setting.question.question_id, setting.path
+    );
+    if setting.question.kind != QuestionKind::Ref {
+        write!(
+            &mut text,
+            ", {}",
+            hidden_questions::storage_text(setting.form_set, setting.question, setting.strings_map)
+        )
+        .unwrap();
+    }
+    text
+}
+
+pub fn diff_report(diff: &Diff) -> String {
+    let mut report = String::new();
+
+    writeln!(&mut report, "Added: {}", diff.added.len()).unwrap();
+    for setting in &diff.added {
+        writeln!(&mut report, "\t+ {}", setting_text(setting)).unwrap();
+    }
+
+    writeln!(&mut report, "Removed: {}", diff.removed.len()).unwrap();
+    for setting in &diff.removed {
+        writeln!(&mut report, "\t- {}", setting_text(setting)).unwrap();
+    }
+
+    writeln!(&mut report, "Changed: {}", diff.changed.len()).unwrap();
+    for change in &diff.changed {
+        writeln!(&mut report, "\t* {}", setting_text(&change.new)).unwrap();
+        for difference in &change.differences {
+            writeln!(&mut report, "\t\t{difference}").unwrap();
+        }
+    }
+
+    writeln!(&mut report, "Unchanged: {}", diff.unchanged).unwrap();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uefi_model::test_models::{buffer, form, form_set, question, reference};
+
+    fn strings() -> HashMap<u16, String> {
+        vec![(1, "Setup"), (2, "Main"), (3, "Advanced"), (0x10, "Advanced"), (0x11, "Mode")]
+            .into_iter()
+            .map(|(id, s)| (id, s.to_string()))
+            .collect()
+    }
+
+    // Form set with a Ref from form 1 to form 2 and a OneOf "Mode" in a given form and varstore
+    fn mode_form_set(form_id: u16, var_store_id: u16, var_offset: u16) -> FormSet {
+        let mut mode = question(QuestionKind::OneOf, 0x11, var_store_id, var_offset, 1);
+        mode.form_id = form_id;
+        mode.offset = 1;
+        let mut form_set = form_set(
+            vec![buffer(1, "Setup", 8), buffer(2, "Other", 8)],
+            vec![reference(0x10, 1, 2), mode],
+        );
+        form_set.forms.push(form(2, 3));
+        form_set
+    }
+
+    fn diff_text(old: FormSet, new: FormSet) -> String {
+        let strings = strings();
+        let old = vec![(old, &strings)];
+        let new = vec![(new, &strings)];
+        diff_report(&diff(settings(&old), settings(&new)))
+    }
+
+    #[test]
+    fn settings_moved_in_their_varstore_are_changed() {
+        let report = diff_text(mode_form_set(1, 1, 0), mode_form_set(1, 1, 4));
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0..3].to_vec(), vec!["Added: 0", "Removed: 0", "Changed: 1"]);
+        assert!(lines[4].starts_with("\t\tstorage: VarStore \"Setup\" (VarStoreId: 0x1) at 0x0"));
+        assert_eq!(lines[5], "Unchanged: 1");
+    }
+
+    #[test]
+    fn settings_with_the_same_prompt_in_other_varstores_and_forms_are_different() {
+        let report = diff_text(mode_form_set(1, 1, 0), mode_form_set(2, 2, 4));
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "Added: 1");
+        assert_eq!(lines[2], "Removed: 1");
+        assert_eq!(lines[4..6].to_vec(), vec!["Changed: 0", "Unchanged: 1"]);
+
+        // The same form is enough
+        let report = diff_text(mode_form_set(1, 1, 0), mode_form_set(1, 2, 4));
+        assert!(report.starts_with("Added: 0\nRemoved: 0\nChanged: 1\n"));
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Write;
+
+use uefi_model::FormSet;
+use varstore_index;
+use visibility;
+use visibility::{Context, Visibility};
+
+//
+// Inventory of an image
+//
+pub struct PackageStats {
+    pub offset: usize,
+    pub length: usize,
+    pub opcodes: Vec<(String, usize)>, // Number of operations by opcode, most frequent first
+    pub unparsed: Option<usize>,       // Bytes left after the last operation, None if the package can't be parsed
+}
+
+impl PackageStats {
+    pub fn operations(&self) -> usize {
+        self.opcodes.iter().map(|(_, count)| count).sum()
+    }
+}
+
+// Counts of items by their Debug text, most frequent first, then by name
+pub fn histogram<T: Debug, I: IntoIterator<Item = T>>(items: I) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for item in items {
+        *counts.entry(format!("{item:?}")).or_insert(0) += 1;
+    }
+    let mut result: Vec<(String, usize)> = counts.into_iter().collect();
+    result.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    result
+}
+
+fn histogram_text(histogram: &[(String, usize)]) -> String {
+    histogram
+        .iter()
+        .map(|(name, count)| format!("{name}: {count}"))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn packages_report(report: &mut String, format: &str, packages: &[PackageStats]) {
+    for package in packages {
+        match package.unparsed {
+            Some(unparsed) => {
+                write!(
+                    report,
+                    "{} form package at 0x{:X}, length 0x{:X}: {} operation(s)",
+                    format,
+                    package.offset,
+                    package.length,
+                    package.operations()
+                )
+                .unwrap();
+                if unparsed > 0 {
+                    write!(report, ", {unparsed} unparsed byte(s)").unwrap();
+                }
+                writeln!(report).unwrap();
+                writeln!(report, "\t{}", histogram_text(&package.opcodes)).unwrap();
+            }
+            None => writeln!(
+                report,
+                "{} form package at 0x{:X}, length 0x{:X}: can't be parsed",
+                format, package.offset, package.length
+            )
+            .unwrap(),
+        }
+    }
+}
+
+// Summary of packages, languages, questions and varstores, languages are given with their number of string packages
+pub fn stats_report(
+    uefi_packages: &[PackageStats],
+    framework_packages: &[PackageStats],
+    uefi_languages: &[(String, usize)],
+    framework_languages: &[(String, usize)],
+    form_sets: &[FormSet],
+) -> String {
+    let mut report = String::new();
+
+    let languages_text = |languages: &[(String, usize)]| {
+        if languages.is_empty() {
+            return String::from("none");
+        }
+        languages
+            .iter()
+            .map(|(language, count)| format!("{language} ({count})"))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    let string_packages = |languages: &[(String, usize)]| languages.iter().map(|(_, count)| count).sum::<usize>();
+    writeln!(
+        &mut report,
+        "UEFI HII packages: {} form, {} string",
+        uefi_packages.len(),
+        string_packages(uefi_languages)
+    )
+    .unwrap();
+    writeln!(
+        &mut report,
+        "Framework HII packages: {} form, {} string",
+        framework_packages.len(),
+        string_packages(framework_languages)
+    )
+    .unwrap();
+    writeln!(&mut report, "UEFI languages: {}", languages_text(uefi_languages)).unwrap();
+    writeln!(&mut report, "Framework languages: {}", languages_text(framework_languages)).unwrap();
+
+    // Questions
+    let forms: usize = form_sets.iter().map(|fs| fs.forms.len()).sum();
+    let questions = histogram(form_sets.iter().flat_map(|fs| fs.questions.iter().map(|q| q.kind)));
+    let total: usize = questions.iter().map(|(_, count)| count).sum();
+    writeln!(&mut report, "Form sets: {}, forms: {}", form_sets.len(), forms).unwrap();
+    writeln!(&mut report, "Questions: {} ({})", total, histogram_text(&questions)).unwrap();
+
+    let buffers = HashMap::new();
+    let mut suppressed = 0;
+    for form_set in form_sets {
+        let context = Context {
+            form_set,
+            buffers: &buffers,
+            default_id: 0,
+        };
+        suppressed += form_set
+            .questions
+            .iter()
+            .filter(|q| {
+                let visibility = visibility::question_visibility(&context, q).visibility;
+                visibility == Visibility::Suppressed || visibility == Visibility::Disabled
+            })
+            .count();
+    }
+    writeln!(&mut report, "Suppressed or disabled at defaults: {suppressed}").unwrap();
+
+    // Varstores merged by name and GUID
+    let no_strings = HashMap::new();
+    let indexed: Vec<_> = form_sets.iter().map(|form_set| (0, form_set, &no_strings)).collect();
+    let variables = varstore_index::variables(&indexed);
+    writeln!(&mut report, "VarStores: {}", variables.len()).unwrap();
+    for variable in &variables {
+        write!(&mut report, "\t{:?} \"{}\" {}", variable.kind, variable.name, variable.guid).unwrap();
+        if let Some(size) = variable.size() {
+            write!(&mut report, ", size 0x{size:X}").unwrap();
+        }
+        writeln!(
+            &mut report,
+            ", {} form set(s), {} question(s)",
+            variable.var_stores.len(),
+            variable.questions.len()
+        )
+        .unwrap();
+    }
+
+    // Parse errors
+    let packages = || uefi_packages.iter().chain(framework_packages);
+    let failed = packages().filter(|p| p.unparsed.is_none()).count();
+    let partial = packages().filter(|p| p.unparsed.unwrap_or(0) > 0).count();
+    writeln!(
+        &mut report,
+        "Parse errors: {} ({} package(s) can't be parsed, {} package(s) with unparsed bytes)",
+        failed + partial,
+        failed,
+        partial
+    )
+    .unwrap();
+
+    packages_report(&mut report, "UEFI", uefi_packages);
+    packages_report(&mut report, "Framework", framework_packages);
+    report
+}
@@ -0,0 +1,30 @@
+// Parser
+extern crate memchr;
+extern crate regex;
+#[macro_use]
+extern crate serde_json;
+extern crate toml;
+pub mod byte_reader;
+pub mod byte_writer;
+pub mod error;
+pub mod form_graph;
+pub mod form_links;
+pub mod framework_model;
+pub mod framework_parser;
+pub mod hidden_questions;
+pub mod ifr_extract;
+pub mod ifr_lint;
+pub mod image_stats;
+pub mod image_diff;
+pub mod menu_tree;
+pub mod model_json;
+pub mod package_discovery;
+pub mod setting_search;
+pub mod settings_profile;
+pub mod storage_overlaps;
+pub mod uefi_encoder;
+pub mod uefi_model;
+pub mod uefi_parser;
+pub mod varstore_index;
+pub mod varstore_layout;
+pub mod visibility;
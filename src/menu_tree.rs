@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use form_links;
+use uefi_model::{FormSet, Question, QuestionKind, Statement, StatementKind};
+use uefi_parser::Guid;
+use visibility;
+use visibility::{Context, Value, Visibility};
+
+//
+// Setup menu tree that follows Refs like a setup browser
+//
+pub enum Item<'a> {
+    Statement(&'a Statement),
+    Question(&'a Question),
+}
+
+impl Item<'_> {
+    pub fn offset(&self) -> usize {
+        match *self {
+            Item::Statement(s) => s.offset,
+            Item::Question(q) => q.offset,
+        }
+    }
+}
+
+// Statements and questions of a form in the order of their operations
+pub fn form_items(form_set: &FormSet, form_id: u16) -> Vec<Item<'_>> {
+    let mut items: Vec<Item> = form_set
+        .statements
+        .iter()
+        .filter(|s| s.form_id == form_id)
+        .map(Item::Statement)
+        .chain(
+            form_set
+                .questions
+                .iter()
+                .filter(|q| q.form_id == form_id)
+                .map(Item::Question),
+        )
+        .collect();
+    items.sort_by_key(|i| i.offset());
+    items
+}
+
+struct Tree<'a> {
+    form_sets: &'a [(FormSet, &'a HashMap<u16, String>)],
+    contexts: Vec<Context<'a>>,
+    targets: HashMap<usize, Option<(usize, u16)>>, // Destinations of Refs by their offsets
+    expanded: HashSet<(usize, u16)>,
+    path: Vec<(usize, u16)>, // Forms being expanded, used to detect cycles
+    text: String,
+}
+
+pub fn string(strings_map: &HashMap<u16, String>, string_id: u16) -> &str {
+    strings_map
+        .get(&string_id)
+        .map(|s| s.as_str())
+        .unwrap_or("InvalidId")
+}
+
+fn indent(depth: usize) -> String {
+    "\t".repeat(depth)
+}
+
+// Current or default value of a question as a setup browser would show it
+pub fn value_text(context: &Context, question: &Question, strings_map: &HashMap<u16, String>) -> Option<String> {
+    let value = match question.kind {
+        QuestionKind::OneOf | QuestionKind::CheckBox | QuestionKind::Numeric => {
+            context.question_value(question.question_id)
+        }
+        _ => return None,
+    };
+    let value = match value {
+        Value::Uint(v) => v,
+        Value::Boolean(b) => b as u64,
+        Value::Undefined => return Some(String::from("no default")),
+    };
+    let source = match context.form_set.var_store(question.var_store_id) {
+        Some(v) if context.buffers.contains_key(&(v.name.clone(), v.guid)) => "current",
+        _ => "default",
+    };
+    let text = match question.kind {
+        QuestionKind::OneOf => match question.options.iter().find(|o| o.value == value) {
+            Some(o) => format!("\"{}\"", string(strings_map, o.string_id)),
+            None => format!("0x{value:X} (not an option)"),
+        },
+        QuestionKind::CheckBox => String::from(if value != 0 { "Enabled" } else { "Disabled" }),
+        _ => value.to_string(),
+    };
+    Some(format!("{text} ({source})"))
+}
+
+fn visibility_text(context: &Context, conditions: &[usize]) -> String {
+    let state = visibility::conditions_visibility(context, conditions);
+    match state.visibility {
+        Visibility::Visible => String::new(),
+        v => format!(" [{v:?}]"),
+    }
+}
+
+impl Tree<'_> {
+    fn form_text(&self, form_set_index: usize, form_id: u16) -> String {
+        let (form_set, strings_map) = &self.form_sets[form_set_index];
+        let title = form_set
+            .form(form_id)
+            .map(|f| string(strings_map, f.title_string_id))
+            .unwrap_or("InvalidId");
+        format!("Form \"{title}\", FormId: 0x{form_id:X}")
+    }
+
+    // Prints the items of a form, forms reached by Refs are expanded below them
+    fn expand(&mut self, form_set_index: usize, form_id: u16, depth: usize) {
+        self.expanded.insert((form_set_index, form_id));
+        self.path.push((form_set_index, form_id));
+
+        let form_sets = self.form_sets;
+        let (form_set, strings_map) = &form_sets[form_set_index];
+
+        for item in form_items(form_set, form_id) {
+            let context = &self.contexts[form_set_index];
+            match item {
+                Item::Statement(statement) => {
+                    let mut line = format!(
+                        "{}{:?} \"{}\"",
+                        indent(depth),
+                        statement.kind,
+                        string(strings_map, statement.prompt_string_id)
+                    );
+                    if let (StatementKind::Text, Some(text_id)) = (statement.kind, statement.text_string_id) {
+                        if text_id != 0 {
+                            write!(&mut line, " \"{}\"", string(strings_map, text_id)).unwrap();
+                        }
+                    }
+                    line += &visibility_text(context, &statement.conditions);
+                    writeln!(&mut self.text, "{line}").unwrap();
+                }
+                Item::Question(question) if question.kind == QuestionKind::Ref => {
+                    let target = self.targets.get(&question.offset).cloned().flatten();
+                    let mut line = format!(
+                        "{}Ref \"{}\"{} -> ",
+                        indent(depth),
+                        string(strings_map, question.prompt_string_id),
+                        visibility_text(context, &question.conditions)
+                    );
+                    match target {
+                        Some((index, id)) => {
+                            if index != form_set_index {
+                                let (other, other_strings) = &form_sets[index];
+                                write!(
+                                    &mut line,
+                                    "FormSet \"{}\", ",
+                                    string(other_strings, other.title_string_id)
+                                )
+                                .unwrap();
+                            }
+                            line += &self.form_text(index, id);
+                            if self.path.contains(&(index, id)) {
+                                writeln!(&mut self.text, "{line} (cycle)").unwrap();
+                            } else if self.expanded.contains(&(index, id)) {
+                                writeln!(&mut self.text, "{line} (shown above)").unwrap();
+                            } else {
+                                writeln!(&mut self.text, "{line}").unwrap();
+                                self.expand(index, id, depth + 1);
+                            }
+                        }
+                        None => {
+                            let form_id = question.ref_target.as_ref().and_then(|t| t.form_id);
+                            match form_id {
+                                Some(id) => writeln!(&mut self.text, "{line}unknown FormId: 0x{id:X}").unwrap(),
+                                None => writeln!(&mut self.text, "{line}destination from value").unwrap(),
+                            }
+                        }
+                    }
+                }
+                Item::Question(question) => {
+                    let mut line = format!(
+                        "{}{:?} \"{}\"",
+                        indent(depth),
+                        question.kind,
+                        string(strings_map, question.prompt_string_id)
+                    );
+                    if let Some(value) = value_text(context, question, strings_map) {
+                        write!(&mut line, ": {value}").unwrap();
+                    }
+                    line += &visibility_text(context, &question.conditions);
+                    writeln!(&mut self.text, "{line}").unwrap();
+                }
+            }
+        }
+
+        self.path.pop();
+    }
+}
+
+// Menu tree of all form sets starting at their first forms, forms no Ref leads to are added at the end of their form sets
+pub fn menu_tree(form_sets: &[(FormSet, &HashMap<u16, String>)], buffers: &HashMap<(String, Guid), Vec<u8>>) -> String {
+    let models: Vec<&FormSet> = form_sets.iter().map(|(form_set, _)| form_set).collect();
+    let links = form_links::links(&models);
+    let reachable = form_links::reachable_forms(&models, &links, &|_| true);
+
+    let mut tree = Tree {
+        form_sets,
+        contexts: models
+            .iter()
+            .map(|form_set| Context {
+                form_set,
+                buffers,
+                default_id: 0,
+            })
+            .collect(),
+        targets: links.iter().map(|l| (l.question.offset, l.target)).collect(),
+        expanded: HashSet::new(),
+        path: Vec::new(),
+        text: String::new(),
+    };
+
+    for (index, (form_set, strings_map)) in form_sets.iter().enumerate() {
+        writeln!(
+            &mut tree.text,
+            "FormSet \"{}\", Guid: {}",
+            string(strings_map, form_set.title_string_id),
+            form_set.guid
+        )
+        .unwrap();
+        for (form_index, form) in form_set.forms.iter().enumerate() {
+            let key = (index, form.form_id);
+            let form_text = tree.form_text(index, form.form_id);
+            if form_index == 0 {
+                if tree.expanded.contains(&key) {
+                    writeln!(&mut tree.text, "\t{form_text} (shown above)").unwrap();
+                } else {
+                    writeln!(&mut tree.text, "\t{form_text}").unwrap();
+                    tree.expand(index, form.form_id, 2);
+                }
+            } else if !reachable[index].contains(&form.form_id) && !tree.expanded.contains(&key) {
+                writeln!(&mut tree.text, "\t{form_text} (not reachable by any Ref)").unwrap();
+                tree.expand(index, form.form_id, 2);
+            }
+        }
+    }
+
+    tree.text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uefi_model::test_models::{buffer, default, form, form_set, guid, option, question, reference};
+    use uefi_model::{Condition, ConditionKind, ExpressionOp};
+
+    // Items are placed in the order of the given list, Refs are found by their offsets
+    fn at(mut question: Question, offset: usize) -> Question {
+        question.offset = offset;
+        question
+    }
+
+    fn test_form_set() -> FormSet {
+        let mut one_of = question(QuestionKind::OneOf, 0x11, 1, 0, 1);
+        one_of.options = vec![option(0x20, 0), option(0x21, 1)];
+        one_of.defaults.push(default(0, 1));
+        let mut check_box = question(QuestionKind::CheckBox, 0x15, 1, 1, 1);
+        check_box.form_id = 2;
+        check_box.defaults.push(default(0, 0));
+        check_box.conditions.push(0);
+        let mut numeric = question(QuestionKind::Numeric, 0x16, 1, 2, 1);
+        numeric.form_id = 3;
+
+        let mut form_set = form_set(
+            vec![buffer(1, "Setup", 3)],
+            vec![
+                at(reference(0x10, 1, 2), 2),
+                at(one_of, 3),
+                at(reference(0x12, 1, 2), 4),
+                at(reference(0x13, 1, 9), 5),
+                at(reference(0x14, 2, 1), 6),
+                at(check_box, 7),
+                at(numeric, 8),
+            ],
+        );
+        form_set.forms.extend(vec![form(2, 3), form(3, 4)]);
+        form_set.statements.push(Statement {
+            kind: StatementKind::Subtitle,
+            form_id: 1,
+            prompt_string_id: 5,
+            help_string_id: 0,
+            text_string_id: None,
+            conditions: Vec::new(),
+            offset: 1,
+        });
+        form_set.conditions.push(Condition {
+            kind: ConditionKind::SuppressIf,
+            expression: vec![ExpressionOp::EqIdVal { question_id: 0x11, value: 1 }],
+            offset: 0,
+        });
+        form_set
+    }
+
+    fn tree(buffers: &HashMap<(String, Guid), Vec<u8>>) -> String {
+        let strings: HashMap<u16, String> = vec![
+            (1, "Setup"),
+            (2, "Main"),
+            (3, "Advanced"),
+            (4, "Debug"),
+            (5, "System"),
+            (0x10, "Advanced"),
+            (0x11, "Mode"),
+            (0x14, "Back"),
+            (0x15, "Feature"),
+            (0x16, "Count"),
+            (0x20, "Off"),
+            (0x21, "On"),
+        ]
+        .into_iter()
+        .map(|(id, s)| (id, s.to_string()))
+        .collect();
+        menu_tree(&[(test_form_set(), &strings)], buffers)
+    }
+
+    #[test]
+    fn tree_at_defaults() {
+        assert_eq!(
+            tree(&HashMap::new()),
+            "FormSet \"Setup\", Guid: 000000F0-0000-0000-0000-000000000000\n\
+             \tForm \"Main\", FormId: 0x1\n\
+             \t\tSubtitle \"System\"\n\
+             \t\tRef \"Advanced\" -> Form \"Advanced\", FormId: 0x2\n\
+             \t\t\tRef \"Back\" -> Form \"Main\", FormId: 0x1 (cycle)\n\
+             \t\t\tCheckBox \"Feature\": Disabled (default) [Suppressed]\n\
+             \t\tOneOf \"Mode\": \"On\" (default)\n\
+             \t\tRef \"InvalidId\" -> Form \"Advanced\", FormId: 0x2 (shown above)\n\
+             \t\tRef \"InvalidId\" -> unknown FormId: 0x9\n\
+             \tForm \"Debug\", FormId: 0x3 (not reachable by any Ref)\n\
+             \t\tNumeric \"Count\": no default\n"
+        );
+    }
+
+    #[test]
+    fn tree_with_current_values() {
+        let mut buffers = HashMap::new();
+        buffers.insert((String::from("Setup"), guid(0x10)), vec![0, 1, 7]);
+        let tree = tree(&buffers);
+        let lines: Vec<&str> = tree.lines().collect();
+        assert_eq!(lines[5], "\t\t\tCheckBox \"Feature\": Enabled (current)");
+        assert_eq!(lines[6], "\t\tOneOf \"Mode\": \"Off\" (current)");
+        assert_eq!(lines[10], "\t\tNumeric \"Count\": 7 (current)");
+    }
+}
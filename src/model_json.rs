@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use serde_json;
+use uefi_model::FormSet;
+
+//
+// JSON output of form set models
+//
+fn string(strings_map: &HashMap<u16, String>, string_id: u16) -> &str {
+    strings_map.get(&string_id).map(|s| s.as_str()).unwrap_or("InvalidId")
+}
+
+// Form sets with their varstores, forms, statements, questions and conditions, strings are resolved
+pub fn form_sets_json(form_sets: &[FormSet], strings_map: &HashMap<u16, String>) -> String {
+    let form_sets: Vec<serde_json::Value> = form_sets
+        .iter()
+        .map(|form_set| {
+            let var_stores: Vec<serde_json::Value> = form_set
+                .var_stores
+                .iter()
+                .map(|v| {
+                    json!({
+                        "var_store_id": v.var_store_id,
+                        "kind": format!("{:?}", v.kind),
+                        "name": v.name,
+                        "guid": v.guid.to_string(),
+                        "size": v.size,
+                        "offset": v.offset,
+                    })
+                })
+                .collect();
+            let forms: Vec<serde_json::Value> = form_set
+                .forms
+                .iter()
+                .map(|f| {
+                    json!({
+                        "form_id": f.form_id,
+                        "title": string(strings_map, f.title_string_id),
+                        "offset": f.offset,
+                    })
+                })
+                .collect();
+            let statements: Vec<serde_json::Value> = form_set
+                .statements
+                .iter()
+                .map(|s| {
+                    json!({
+                        "kind": format!("{:?}", s.kind),
+                        "form_id": s.form_id,
+                        "prompt": string(strings_map, s.prompt_string_id),
+                        "help": string(strings_map, s.help_string_id),
+                        "text": s.text_string_id.map(|id| string(strings_map, id)),
+                        "conditions": s.conditions,
+                        "offset": s.offset,
+                    })
+                })
+                .collect();
+            let questions: Vec<serde_json::Value> = form_set
+                .questions
+                .iter()
+                .map(|q| {
+                    let options: Vec<serde_json::Value> = q
+                        .options
+                        .iter()
+                        .map(|o| json!({"value": o.value, "text": string(strings_map, o.string_id), "flags": o.flags}))
+                        .collect();
+                    let defaults: Vec<serde_json::Value> = q
+                        .defaults
+                        .iter()
+                        .map(|d| json!({"default_id": d.default_id, "value": d.value}))
+                        .collect();
+                    json!({
+                        "kind": format!("{:?}", q.kind),
+                        "form_id": q.form_id,
+                        "question_id": q.question_id,
+                        "prompt": string(strings_map, q.prompt_string_id),
+                        "help": string(strings_map, q.help_string_id),
+                        "var_store_id": q.var_store_id,
+                        "var_offset": q.var_offset,
+                        "bit_width": q.bit_width,
+                        "width": q.width,
+                        "minimum": q.minimum,
+                        "maximum": q.maximum,
+                        "step": q.step,
+                        "options": options,
+                        "defaults": defaults,
+                        "conditions": q.conditions,
+                        "offset": q.offset,
+                    })
+                })
+                .collect();
+            // Expressions are given as text of their operations in postfix order
+            let conditions: Vec<serde_json::Value> = form_set
+                .conditions
+                .iter()
+                .map(|c| {
+                    let expression: Vec<String> = c.expression.iter().map(|op| format!("{op:?}")).collect();
+                    json!({
+                        "kind": format!("{:?}", c.kind),
+                        "expression": expression,
+                        "offset": c.offset,
+                    })
+                })
+                .collect();
+            json!({
+                "guid": form_set.guid.to_string(),
+                "title": string(strings_map, form_set.title_string_id),
+                "help": string(strings_map, form_set.help_string_id),
+                "offset": form_set.offset,
+                "var_stores": var_stores,
+                "forms": forms,
+                "statements": statements,
+                "questions": questions,
+                "conditions": conditions,
+            })
+        })
+        .collect();
+    let mut json = serde_json::to_string_pretty(&form_sets).unwrap();
+    json.push('\n');
+    json
+}
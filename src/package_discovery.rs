@@ -0,0 +1,186 @@
+use memchr;
+use std::fmt::Write;
+
+use framework_parser;
+use uefi_parser;
+
+//
+// Package candidates of all kinds found in a single pass
+//
+pub struct Candidates<'a> {
+    // Offset and data of each candidate, in ascending order of offsets, candidates inside other candidates included
+    pub uefi_forms: Vec<(usize, &'a [u8])>,
+    pub uefi_strings: Vec<(usize, &'a [u8])>,
+    pub framework_forms: Vec<(usize, &'a [u8])>,
+    pub framework_strings: Vec<(usize, &'a [u8])>,
+}
+
+// Package length from the header at offset, if the package is longer than its header and fits into the data
+fn package_length(data: &[u8], offset: usize, mask: u32, header_size: usize) -> Option<usize> {
+    let header = data.get(offset..offset + 4)?;
+    let length = (u32::from_le_bytes([header[0], header[1], header[2], header[3]]) & mask) as usize;
+    if length > header_size && length <= data.len() - offset {
+        Some(length)
+    } else {
+        None
+    }
+}
+
+// Every package kind has a byte with a known value at a known position of its header,
+// so a single memchr sweep over these bytes yields all offsets worth passing to the parsers:
+// 0x0E is the FormSet opcode starting UEFI (at offset 4) and Framework (at offset 6) form packages,
+// 0x04 is the type of UEFI string packages (at offset 3),
+// 0x02 is the low byte of the type of Framework string packages (at offset 4)
+pub fn candidates<'a>(data: &'a [u8], relaxed: bool) -> Candidates<'a> {
+    let mut candidates = Candidates {
+        uefi_forms: Vec::new(),
+        uefi_strings: Vec::new(),
+        framework_forms: Vec::new(),
+        framework_strings: Vec::new(),
+    };
+    for position in memchr::memchr3_iter(0x0E, 0x04, 0x02, data) {
+        match data[position] {
+            0x0E => {
+                if position >= 4 && data[position - 1] == 0x02 {
+                    let offset = position - 4;
+                    if package_length(data, offset, 0x00FFFFFF, 4).is_some() {
+                        if let Some(candidate) = uefi_parser::form_package_candidate(&data[offset..], relaxed) {
+                            candidates.uefi_forms.push((offset, candidate));
+                        }
+                    }
+                }
+                if position >= 6 && data[position - 2] == 0x03 && data[position - 1] == 0x00 {
+                    let offset = position - 6;
+                    if package_length(data, offset, 0xFFFFFFFF, 6).is_some() {
+                        if let Some(candidate) = framework_parser::form_package_candidate(&data[offset..], relaxed) {
+                            candidates.framework_forms.push((offset, candidate));
+                        }
+                    }
+                }
+            }
+            0x04 => {
+                if position >= 3 {
+                    let offset = position - 3;
+                    if package_length(data, offset, 0x00FFFFFF, 4).is_some() {
+                        if let Some(candidate) = uefi_parser::string_package_candidate(&data[offset..], relaxed) {
+                            candidates.uefi_strings.push((offset, candidate));
+                        }
+                    }
+                }
+            }
+            _ => {
+                if position >= 4 && data.get(position + 1) == Some(&0x00) {
+                    let offset = position - 4;
+                    if package_length(data, offset, 0xFFFFFFFF, 6).is_some() {
+                        if let Some(candidate) = framework_parser::string_package_candidate(&data[offset..], relaxed) {
+                            candidates.framework_strings.push((offset, candidate));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    candidates
+}
+
+//
+// Package candidates rejected by strict discovery
+//
+pub struct NearMiss {
+    pub format: &'static str, // UEFI or Framework
+    pub kind: &'static str,   // form or string
+    pub offset: usize,
+    pub length: usize,
+    pub deviation: String, // How the candidate differs from what strict discovery accepts
+}
+
+// Candidate and how it differs from a strict one
+type RelaxedCheck = fn(&[u8]) -> Option<(&[u8], String)>;
+
+// Finds candidates accepted by relaxed heuristics only, candidates accepted by strict ones are skipped as a whole
+fn scan(
+    data: &[u8],
+    format: &'static str,
+    kind: &'static str,
+    strict: fn(&[u8]) -> Option<usize>,
+    relaxed: RelaxedCheck,
+    near_misses: &mut Vec<NearMiss>,
+) {
+    let mut i = 0;
+    while i < data.len() {
+        if let Some(length) = strict(&data[i..]) {
+            i += length;
+        } else if let Some((candidate, deviation)) = relaxed(&data[i..]) {
+            near_misses.push(NearMiss {
+                format,
+                kind,
+                offset: i,
+                length: candidate.len(),
+                deviation,
+            });
+            i += candidate.len();
+        } else {
+            i += 1;
+        }
+    }
+}
+
+pub fn near_misses(data: &[u8]) -> Vec<NearMiss> {
+    let mut near_misses = Vec::new();
+    scan(
+        data,
+        "UEFI",
+        "form",
+        |input| uefi_parser::form_package_candidate(input, false).map(|c| c.len()),
+        uefi_parser::hii_form_package_candidate_relaxed,
+        &mut near_misses,
+    );
+    scan(
+        data,
+        "UEFI",
+        "string",
+        |input| uefi_parser::string_package_candidate(input, false).map(|c| c.len()),
+        uefi_parser::hii_string_package_candidate_relaxed,
+        &mut near_misses,
+    );
+    scan(
+        data,
+        "Framework",
+        "form",
+        |input| framework_parser::form_package_candidate(input, false).map(|c| c.len()),
+        framework_parser::hii_form_package_candidate_relaxed,
+        &mut near_misses,
+    );
+    scan(
+        data,
+        "Framework",
+        "string",
+        |input| framework_parser::string_package_candidate(input, false).map(|c| c.len()),
+        framework_parser::hii_string_package_candidate_relaxed,
+        &mut near_misses,
+    );
+    near_misses
+}
+
+pub fn near_miss_report(near_misses: &[NearMiss]) -> String {
+    let mut report = String::new();
+    for near_miss in near_misses {
+        writeln!(
+            &mut report,
+            "{} {} package candidate at 0x{:X}, length 0x{:X}: {}",
+            near_miss.format, near_miss.kind, near_miss.offset, near_miss.length, near_miss.deviation
+        )
+        .unwrap();
+    }
+    if near_misses.is_empty() {
+        writeln!(&mut report, "No candidates rejected by strict discovery found").unwrap();
+    } else {
+        writeln!(
+            &mut report,
+            "{} candidate(s) rejected by strict discovery, use --relaxed to accept them",
+            near_misses.len()
+        )
+        .unwrap();
+    }
+    report
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use regex::Regex;
+
+use form_links;
+use hidden_questions;
+use uefi_model::{FormSet, Question};
+
+//
+// Search of questions by their strings
+//
+pub struct Hit<'a> {
+    pub form_set_index: usize,
+    pub question: &'a Question,
+    pub matches: Vec<String>, // Which strings matched, i.e. prompt, help or an option
+}
+
+fn string(strings_map: &HashMap<u16, String>, string_id: u16) -> &str {
+    strings_map
+        .get(&string_id)
+        .map(|s| s.as_str())
+        .unwrap_or("InvalidId")
+}
+
+// Finds questions with a prompt, help or option string that matches the regex
+pub fn search<'a>(form_sets: &'a [(FormSet, &HashMap<u16, String>)], regex: &Regex) -> Vec<Hit<'a>> {
+    let mut hits = Vec::new();
+    for (form_set_index, (form_set, strings_map)) in form_sets.iter().enumerate() {
+        for question in &form_set.questions {
+            let mut matches = Vec::new();
+            if regex.is_match(string(strings_map, question.prompt_string_id)) {
+                matches.push(String::from("prompt"));
+            }
+            let help = string(strings_map, question.help_string_id);
+            if regex.is_match(help) {
+                matches.push(format!("help \"{help}\""));
+            }
+            for option in &question.options {
+                let text = string(strings_map, option.string_id);
+                if regex.is_match(text) {
+                    matches.push(format!("option 0x{:X} \"{}\"", option.value, text));
+                }
+            }
+            if !matches.is_empty() {
+                hits.push(Hit {
+                    form_set_index,
+                    question,
+                    matches,
+                });
+            }
+        }
+    }
+    hits
+}
+
+// Form set title and form titles from the first form of a form set to the form of the question
+pub fn form_path(
+    form_sets: &[(FormSet, &HashMap<u16, String>)],
+    paths: &HashMap<(usize, u16), Vec<(usize, u16)>>,
+    form_set_index: usize,
+    form_id: u16,
+) -> String {
+    let form = (form_set_index, form_id);
+    let path = paths.get(&form).cloned().unwrap_or_else(|| vec![form]);
+    let (root_form_set, root_strings) = &form_sets[path[0].0];
+    let mut parts = vec![string(root_strings, root_form_set.title_string_id).to_string()];
+    for (index, id) in path {
+        let (form_set, strings_map) = &form_sets[index];
+        parts.push(match form_set.form(id) {
+            Some(f) => string(strings_map, f.title_string_id).to_string(),
+            None => format!("FormId 0x{id:X}"),
+        });
+    }
+    let mut path = parts.join(" > ");
+    if !paths.contains_key(&form) {
+        path += " (not reachable by any Ref)";
+    }
+    path
+}
+
+pub fn search_report(form_sets: &[(FormSet, &HashMap<u16, String>)], hits: &[Hit]) -> String {
+    let models: Vec<&FormSet> = form_sets.iter().map(|(form_set, _)| form_set).collect();
+    let links = form_links::links(&models);
+    let paths = form_links::form_paths(&models, &links);
+
+    let mut report = String::new();
+    for hit in hits {
+        let (form_set, strings_map) = &form_sets[hit.form_set_index];
+        let question = hit.question;
+        writeln!(
+            &mut report,
+            "{}: {:?} \"{}\", QuestionId: 0x{:X}, {}",
+            form_path(form_sets, &paths, hit.form_set_index, question.form_id),
+            question.kind,
+            string(strings_map, question.prompt_string_id),
+            question.question_id,
+            hidden_questions::storage_text(form_set, question, strings_map)
+        )
+        .unwrap();
+        writeln!(&mut report, "\tMatched: {}", hit.matches.join(", ")).unwrap();
+    }
+    report
+}
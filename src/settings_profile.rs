@@ -0,0 +1,602 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use serde_json;
+use toml;
+use uefi_model::{FormSet, Question, QuestionKind, VarStoreKind};
+use uefi_parser::Guid;
+use visibility::read_bits;
+
+//
+// Settings profile
+//
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SettingValue {
+    Number(u64),
+    Text(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Setting {
+    pub key: String,
+    pub value: SettingValue,
+}
+
+// Profiles are flat key/value maps, either as a JSON object or as a TOML document without tables,
+// keys are prompts or QuestionIds given as 0x… or id:…
+pub fn parse_profile(text: &str) -> Result<Vec<Setting>, String> {
+    if text.trim_start().starts_with('{') {
+        parse_json_profile(text)
+    } else {
+        parse_toml_profile(text)
+    }
+}
+
+fn parse_number(s: &str) -> Option<u64> {
+    let s = s.replace('_', "");
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else {
+        s.parse::<u64>().ok()
+    }
+}
+
+fn parse_toml_profile(text: &str) -> Result<Vec<Setting>, String> {
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| e.message().to_string())?;
+    let mut settings = Vec::new();
+    for (key, value) in table {
+        let value = match value {
+            toml::Value::String(text) => SettingValue::Text(text),
+            toml::Value::Integer(n) if n >= 0 => SettingValue::Number(n as u64),
+            toml::Value::Boolean(b) => SettingValue::Number(b as u64),
+            toml::Value::Table(_) => return Err(format!("\"{key}\": tables are not supported")),
+            value => return Err(format!("\"{key}\": invalid value {value}")),
+        };
+        settings.push(Setting { key, value });
+    }
+    Ok(settings)
+}
+
+fn parse_json_profile(text: &str) -> Result<Vec<Setting>, String> {
+    let object = match serde_json::from_str(text).map_err(|e| e.to_string())? {
+        serde_json::Value::Object(object) => object,
+        _ => return Err(String::from("expected an object")),
+    };
+    let mut settings = Vec::new();
+    for (key, value) in object {
+        let value = match value {
+            serde_json::Value::String(text) => SettingValue::Text(text),
+            serde_json::Value::Bool(b) => SettingValue::Number(b as u64),
+            serde_json::Value::Object(_) => return Err(format!("\"{key}\": nested objects are not supported")),
+            value => match value.as_u64() {
+                Some(n) => SettingValue::Number(n),
+                None => return Err(format!("\"{key}\": invalid value {value}")),
+            },
+        };
+        settings.push(Setting { key, value });
+    }
+    Ok(settings)
+}
+
+//
+// Applying a profile to variable buffers
+//
+pub struct Change {
+    pub setting: Setting,
+    pub var_store_name: String,
+    pub var_store_guid: Guid,
+    pub question_id: u16,
+    pub prompt: String,
+    pub bit_offset: usize, // Storage of the question as a range of bits
+    pub bit_width: usize,
+    pub bit_field: bool,
+    pub old_value: u64,
+    pub new_value: u64,
+}
+
+// Values are little-endian, bit-field values start from the least significant bit of a byte
+fn write_value(buffer: &mut [u8], bit_offset: usize, bit_width: usize, value: u64) {
+    for i in 0..bit_width {
+        let bit = bit_offset + i;
+        if (value >> i) & 1 != 0 {
+            buffer[bit / 8] |= 1 << (bit % 8);
+        } else {
+            buffer[bit / 8] &= !(1 << (bit % 8));
+        }
+    }
+}
+
+// Finds the value to be written for a question, validating it against the question's constraints
+fn resolve_value(
+    question: &Question,
+    value: &SettingValue,
+    strings_map: &HashMap<u16, String>,
+) -> Result<u64, String> {
+    let (_, bit_width) = question.bit_range();
+    let max_value = if bit_width >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << bit_width) - 1
+    };
+
+    let value = match question.kind {
+        QuestionKind::OneOf => {
+            let option = match *value {
+                SettingValue::Text(ref text) => question.options.iter().find(|o| {
+                    strings_map
+                        .get(&o.string_id)
+                        .map(|s| s.trim().eq_ignore_ascii_case(text.trim()))
+                        .unwrap_or(false)
+                }),
+                SettingValue::Number(n) => question.options.iter().find(|o| o.value == n),
+            };
+            match option {
+                Some(o) => Ok(o.value),
+                None => {
+                    let valid: Vec<String> = question
+                        .options
+                        .iter()
+                        .map(|o| {
+                            format!(
+                                "\"{}\" (0x{:X})",
+                                strings_map
+                                    .get(&o.string_id)
+                                    .map(|s| s.as_str())
+                                    .unwrap_or("InvalidId"),
+                                o.value
+                            )
+                        })
+                        .collect();
+                    Err(format!("value is not one of the options: {}", valid.join(", ")))
+                }
+            }
+        }
+        QuestionKind::CheckBox => match *value {
+            SettingValue::Number(n) if n <= 1 => Ok(n),
+            SettingValue::Text(ref text) => match text.trim().to_lowercase().as_str() {
+                "enabled" | "enable" | "true" | "on" => Ok(1),
+                "disabled" | "disable" | "false" | "off" => Ok(0),
+                _ => Err(format!("invalid CheckBox value \"{text}\"")),
+            },
+            SettingValue::Number(n) => Err(format!("invalid CheckBox value 0x{n:X}")),
+        },
+        QuestionKind::Numeric => {
+            let n = match *value {
+                SettingValue::Number(n) => n,
+                SettingValue::Text(ref text) => parse_number(text.trim())
+                    .ok_or_else(|| format!("invalid Numeric value \"{text}\""))?,
+            };
+            if let (Some(min), Some(max)) = (question.minimum, question.maximum) {
+                if n < min || n > max {
+                    return Err(format!("value 0x{n:X} is out of range [0x{min:X}..0x{max:X}]"));
+                }
+                if let Some(step) = question.step {
+                    if step != 0 && (n - min) % step != 0 {
+                        return Err(format!(
+                            "value 0x{n:X} is not a multiple of step 0x{step:X} from minimum 0x{min:X}"
+                        ));
+                    }
+                }
+            }
+            Ok(n)
+        }
+        _ => Err(format!("{:?} questions are not supported", question.kind)),
+    }?;
+    // Options and ranges of malformed questions can exceed their storage
+    if value > max_value {
+        return Err(format!("value 0x{value:X} does not fit into {bit_width} bits"));
+    }
+    Ok(value)
+}
+
+// QuestionId of a profile key given as 0x… or id:…, other keys are prompts even if they are numbers
+fn key_question_id(key: &str) -> Option<u64> {
+    let key = key.trim();
+    let number = match key.strip_prefix("id:") {
+        Some(id) => parse_number(id.trim()),
+        None if key.starts_with("0x") || key.starts_with("0X") => parse_number(key),
+        None => None,
+    };
+    number.filter(|n| *n <= 0xFFFF)
+}
+
+// Finds all questions matching a profile key, either a QuestionId or a prompt string
+fn find_questions<'a>(
+    key: &str,
+    form_sets: &'a [(FormSet, &'a HashMap<u16, String>)],
+) -> Vec<(&'a FormSet, &'a Question, &'a HashMap<u16, String>)> {
+    let question_id = key_question_id(key);
+    let mut result = Vec::new();
+    for &(ref fs, strings_map) in form_sets {
+        for q in &fs.questions {
+            let matches = match question_id {
+                Some(qid) => q.question_id as u64 == qid,
+                None => strings_map
+                    .get(&q.prompt_string_id)
+                    .map(|s| s.trim().eq_ignore_ascii_case(key.trim()))
+                    .unwrap_or(false),
+            };
+            if matches {
+                result.push((fs, q, strings_map));
+            }
+        }
+    }
+    result
+}
+
+// Validates all settings and applies them to variable buffers, keyed by varstore name and GUID
+// Nothing is modified if any of the settings can't be applied
+pub fn apply_profile(
+    settings: &[Setting],
+    form_sets: &[(FormSet, &HashMap<u16, String>)],
+    buffers: &mut HashMap<(String, Guid), Vec<u8>>,
+) -> Result<Vec<Change>, Vec<String>> {
+    let mut changes = Vec::new();
+    let mut errors = Vec::new();
+
+    for setting in settings {
+        let candidates: Vec<_> = find_questions(&setting.key, form_sets)
+            .into_iter()
+            .filter(|&(_, q, _)| q.var_store_id != 0)
+            .collect();
+
+        if candidates.is_empty() {
+            errors.push(format!("\"{}\": no question with storage found", setting.key));
+            continue;
+        }
+
+        // The same setting can be present in multiple forms, but all of them must point to the same storage
+        let mut targets: Vec<(String, Guid, (usize, usize))> = Vec::new();
+        let mut resolved = Vec::new();
+        for (fs, q, strings_map) in candidates {
+            let var_store = match fs.var_store(q.var_store_id) {
+                Some(vs) => vs,
+                None => {
+                    errors.push(format!(
+                        "\"{}\": VarStoreId 0x{:X} of QuestionId 0x{:X} is not defined",
+                        setting.key, q.var_store_id, q.question_id
+                    ));
+                    continue;
+                }
+            };
+            if var_store.kind == VarStoreKind::NameValue {
+                errors.push(format!(
+                    "\"{}\": QuestionId 0x{:X} is stored in a name/value varstore, which is not supported",
+                    setting.key, q.question_id
+                ));
+                continue;
+            }
+            let target = (var_store.name.clone(), var_store.guid, q.bit_range());
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+            resolved.push(((var_store.name.clone(), var_store.guid), q, strings_map));
+        }
+
+        if targets.len() > 1 {
+            let list: Vec<String> = targets
+                .iter()
+                .map(|t| format!("{} {}[0x{:X}]", t.0, t.1, t.2 .0 / 8))
+                .collect();
+            errors.push(format!(
+                "\"{}\": ambiguous, matches questions stored at {}",
+                setting.key,
+                list.join(", ")
+            ));
+            continue;
+        }
+
+        let (var_store, q, strings_map) = match resolved.into_iter().next() {
+            Some(x) => x,
+            None => continue,
+        };
+
+        let new_value = match resolve_value(q, &setting.value, strings_map) {
+            Ok(v) => v,
+            Err(e) => {
+                errors.push(format!("\"{}\": {}", setting.key, e));
+                continue;
+            }
+        };
+
+        let buffer = match buffers.get(&var_store) {
+            Some(b) => b,
+            None => {
+                errors.push(format!(
+                    "\"{}\": no variable file provided for varstore \"{}\" {}",
+                    setting.key, var_store.0, var_store.1
+                ));
+                continue;
+            }
+        };
+
+        let offset = q.byte_offset();
+        if offset + q.width > buffer.len() {
+            errors.push(format!(
+                "\"{}\": VarOffset 0x{:X} with width {} is out of bounds of \"{}\" variable of size 0x{:X}",
+                setting.key,
+                offset,
+                q.width,
+                var_store.0,
+                buffer.len()
+            ));
+            continue;
+        }
+
+        changes.push(Change {
+            setting: setting.clone(),
+            var_store_name: var_store.0,
+            var_store_guid: var_store.1,
+            question_id: q.question_id,
+            prompt: strings_map
+                .get(&q.prompt_string_id)
+                .cloned()
+                .unwrap_or_else(|| String::from("InvalidId")),
+            bit_offset: q.bit_range().0,
+            bit_width: q.bit_range().1,
+            bit_field: q.bit_width.is_some(),
+            old_value: read_bits(buffer, q.bit_range().0, q.bit_range().1).unwrap_or(0),
+            new_value,
+        });
+    }
+
+    // Settings sharing storage would overwrite each other in the order of the profile
+    for (index, change) in changes.iter().enumerate() {
+        let end = change.bit_offset + change.bit_width;
+        let conflict = changes[..index].iter().find(|other| {
+            other.var_store_name == change.var_store_name
+                && other.var_store_guid == change.var_store_guid
+                && other.bit_offset < end
+                && change.bit_offset < other.bit_offset + other.bit_width
+        });
+        if let Some(other) = conflict {
+            errors.push(format!(
+                "\"{}\": storage {}[0x{:X}] is also set by \"{}\"",
+                change.setting.key,
+                change.var_store_name,
+                change.bit_offset / 8,
+                other.setting.key
+            ));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    for change in &changes {
+        if let Some(buffer) = buffers.get_mut(&(change.var_store_name.clone(), change.var_store_guid)) {
+            write_value(buffer, change.bit_offset, change.bit_width, change.new_value);
+        }
+    }
+
+    Ok(changes)
+}
+
+pub fn change_report(changes: &[Change]) -> String {
+    let mut report = String::new();
+    for change in changes {
+        if change.bit_field {
+            write!(
+                &mut report,
+                "{}[0x{:X}] BitOffset: {}, BitWidth: {}",
+                change.var_store_name,
+                change.bit_offset / 8,
+                change.bit_offset % 8,
+                change.bit_width
+            )
+            .unwrap();
+        } else {
+            write!(
+                &mut report,
+                "{}[0x{:X}] Width: {}",
+                change.var_store_name,
+                change.bit_offset / 8,
+                change.bit_width / 8
+            )
+            .unwrap();
+        }
+        write!(
+            &mut report,
+            ", QuestionId: 0x{:X}, Prompt: \"{}\": 0x{:X} -> 0x{:X}",
+            change.question_id,
+            change.prompt,
+            change.old_value,
+            change.new_value
+        )
+        .unwrap();
+        if change.old_value == change.new_value {
+            write!(&mut report, " (unchanged)").unwrap();
+        }
+        writeln!(&mut report).unwrap();
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uefi_model::test_models::{bit_field, buffer, form_set, guid, option, question};
+
+    fn number(key: &str, n: u64) -> Setting {
+        Setting {
+            key: key.to_string(),
+            value: SettingValue::Number(n),
+        }
+    }
+
+    fn text(key: &str, text: &str) -> Setting {
+        Setting {
+            key: key.to_string(),
+            value: SettingValue::Text(text.to_string()),
+        }
+    }
+
+    fn strings(pairs: &[(u16, &str)]) -> HashMap<u16, String> {
+        pairs.iter().map(|&(id, s)| (id, s.to_string())).collect()
+    }
+
+    #[test]
+    fn toml_profile() {
+        let profile = "# Comment\n\"Boot Mode\" = \"UEFI\"\n0x5 = 0x10\n'id:6' = true\nDelay = 1_000\n";
+        assert_eq!(
+            parse_profile(profile),
+            Ok(vec![
+                text("Boot Mode", "UEFI"),
+                number("0x5", 0x10),
+                number("id:6", 1),
+                number("Delay", 1000)
+            ])
+        );
+    }
+
+    #[test]
+    fn toml_profile_with_unsupported_syntax() {
+        assert!(parse_profile("[Setup]\nDelay = 1\n").is_err());
+        assert!(parse_profile("Delay = [1, 2]\n").is_err());
+        assert!(parse_profile("Delay = -1\n").is_err());
+        assert!(parse_profile("Delay = 1.5\n").is_err());
+        assert!(parse_profile("Delay 1\n").is_err());
+        assert!(parse_profile("Delay = 1\nDelay = 2\n").is_err());
+    }
+
+    #[test]
+    fn json_profile() {
+        let profile = "{\"Boot Mode\": \"UEFI\", \"0x5\": 16, \"id:6\": false}";
+        assert_eq!(
+            parse_profile(profile),
+            Ok(vec![text("Boot Mode", "UEFI"), number("0x5", 16), number("id:6", 0)])
+        );
+    }
+
+    #[test]
+    fn json_profile_with_unsupported_syntax() {
+        assert!(parse_profile("{\"Delay\": 1} trailing").is_err());
+        assert!(parse_profile("{\"Delay\": one}").is_err());
+        assert!(parse_profile("{Delay: 1}").is_err());
+        assert!(parse_profile("{\"Delay\": {\"Value\": 1}}").is_err());
+        assert!(parse_profile("{\"Delay\": -1}").is_err());
+        assert!(parse_profile("{\"Delay\": null}").is_err());
+    }
+
+    #[test]
+    fn question_id_keys() {
+        assert_eq!(key_question_id("0x5"), Some(5));
+        assert_eq!(key_question_id("0X1F"), Some(0x1F));
+        assert_eq!(key_question_id("id:5"), Some(5));
+        assert_eq!(key_question_id("id:0x10"), Some(0x10));
+        assert_eq!(key_question_id("5"), None);
+        assert_eq!(key_question_id("0x10000"), None);
+        assert_eq!(key_question_id("Boot Mode"), None);
+    }
+
+    #[test]
+    fn numeric_keys_are_prompts() {
+        let strings = strings(&[(1, "5"), (5, "Delay")]);
+        let form_sets = vec![(
+            form_set(
+                vec![buffer(1, "Setup", 4)],
+                vec![
+                    question(QuestionKind::CheckBox, 1, 1, 0, 1),
+                    question(QuestionKind::CheckBox, 5, 1, 1, 1),
+                ],
+            ),
+            &strings,
+        )];
+        let found: Vec<u16> = find_questions("5", &form_sets).iter().map(|&(_, q, _)| q.question_id).collect();
+        assert_eq!(found, vec![1]);
+        let found: Vec<u16> = find_questions("id:5", &form_sets).iter().map(|&(_, q, _)| q.question_id).collect();
+        assert_eq!(found, vec![5]);
+    }
+
+    #[test]
+    fn buffers_are_keyed_by_name_and_guid() {
+        let strings = strings(&[(1, "First"), (2, "Second")]);
+        let mut other = buffer(2, "Setup", 4);
+        other.guid = guid(0x20);
+        let form_sets = vec![(
+            form_set(
+                vec![buffer(1, "Setup", 4), other],
+                vec![
+                    question(QuestionKind::CheckBox, 1, 1, 0, 1),
+                    question(QuestionKind::CheckBox, 2, 2, 0, 1),
+                ],
+            ),
+            &strings,
+        )];
+        let mut buffers = HashMap::new();
+        buffers.insert((String::from("Setup"), guid(0x10)), vec![0; 4]);
+        buffers.insert((String::from("Setup"), guid(0x20)), vec![0; 4]);
+
+        let changes = apply_profile(&[number("Second", 1)], &form_sets, &mut buffers).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].var_store_guid, guid(0x20));
+        assert_eq!(buffers[&(String::from("Setup"), guid(0x10))], vec![0; 4]);
+        assert_eq!(buffers[&(String::from("Setup"), guid(0x20))], vec![1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn settings_of_different_varstores_with_the_same_name_are_ambiguous() {
+        let strings = strings(&[(1, "Setting"), (2, "Setting")]);
+        let mut other = buffer(2, "Setup", 4);
+        other.guid = guid(0x20);
+        let form_sets = vec![(
+            form_set(
+                vec![buffer(1, "Setup", 4), other],
+                vec![
+                    question(QuestionKind::CheckBox, 1, 1, 0, 1),
+                    question(QuestionKind::CheckBox, 2, 2, 0, 1),
+                ],
+            ),
+            &strings,
+        )];
+        let mut buffers = HashMap::new();
+        buffers.insert((String::from("Setup"), guid(0x10)), vec![0; 4]);
+        buffers.insert((String::from("Setup"), guid(0x20)), vec![0; 4]);
+        assert!(apply_profile(&[number("Setting", 1)], &form_sets, &mut buffers).is_err());
+    }
+
+    #[test]
+    fn settings_sharing_storage_conflict() {
+        let strings = strings(&[(1, "Mode"), (2, "Delay"), (3, "Other")]);
+        let form_sets = vec![(
+            form_set(
+                vec![buffer(1, "Setup", 4)],
+                vec![
+                    question(QuestionKind::CheckBox, 1, 1, 0, 1),
+                    question(QuestionKind::Numeric, 2, 1, 0, 2),
+                    question(QuestionKind::CheckBox, 3, 1, 2, 1),
+                ],
+            ),
+            &strings,
+        )];
+        let mut buffers = HashMap::new();
+        buffers.insert((String::from("Setup"), guid(0x10)), vec![0; 4]);
+
+        let result = apply_profile(&[number("Mode", 1), number("id:1", 0)], &form_sets, &mut buffers);
+        assert_eq!(result.err(), Some(vec![String::from("\"id:1\": storage Setup[0x0] is also set by \"Mode\"")]));
+        assert!(apply_profile(&[number("Mode", 1), number("Delay", 2)], &form_sets, &mut buffers).is_err());
+        assert_eq!(buffers[&(String::from("Setup"), guid(0x10))], vec![0; 4]);
+        assert!(apply_profile(&[number("Mode", 1), number("Other", 1)], &form_sets, &mut buffers).is_ok());
+    }
+
+    #[test]
+    fn values_must_fit_the_storage() {
+        let strings = strings(&[(1, "Mode"), (3, "Wide"), (4, "Narrow")]);
+        let mut mode = question(QuestionKind::OneOf, 1, 1, 0, 1);
+        mode.options = vec![option(3, 0x1FF), option(4, 0x01)];
+        let mut bits = bit_field(question(QuestionKind::Numeric, 2, 1, 8, 1), 3);
+        bits.minimum = Some(0);
+        bits.maximum = Some(0xFF);
+        bits.step = Some(1);
+
+        let narrow = SettingValue::Text(String::from("Narrow"));
+        let wide = SettingValue::Text(String::from("Wide"));
+        assert_eq!(resolve_value(&mode, &narrow, &strings), Ok(1));
+        assert!(resolve_value(&mode, &wide, &strings).is_err());
+        assert!(resolve_value(&mode, &SettingValue::Number(0x1FF), &strings).is_err());
+        assert_eq!(resolve_value(&bits, &SettingValue::Number(7), &strings), Ok(7));
+        assert!(resolve_value(&bits, &SettingValue::Number(8), &strings).is_err());
+        assert!(resolve_value(&bits, &SettingValue::Text(String::from("0x8")), &strings).is_err());
+    }
+}
@@ -0,0 +1,552 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::ClearType;
+use crossterm::{cursor, event, execute, queue, terminal};
+
+use form_links;
+use hidden_questions;
+use menu_tree;
+use menu_tree::{string, Item};
+use uefi_model::{FormSet, Question, QuestionKind, StatementKind};
+use uefi_parser::Guid;
+use visibility;
+use visibility::{Context, Visibility};
+
+//
+// Interactive setup browser
+//
+enum Screen {
+    FormSets,
+    Form(usize, u16),                      // Form set index and FormId
+    Search(String, Vec<(usize, u16, usize)>), // Pattern and found items as form set index, FormId and offset
+}
+
+struct Frame {
+    screen: Screen,
+    selected: usize,
+    scroll: usize,
+}
+
+struct Row {
+    text: String,
+    dim: bool,
+    bold: bool,
+    item: Option<(usize, usize)>,            // Form set index and offset of the shown statement or question
+    open: Option<(usize, u16, Option<usize>)>, // Form to open, with the offset of the item to select
+    form_set_index: Option<usize>,           // Form set shown in the details of the form set list
+}
+
+struct Browser<'a> {
+    form_sets: &'a [(FormSet, &'a HashMap<u16, String>)],
+    contexts: Vec<Context<'a>>,
+    targets: HashMap<usize, Option<(usize, u16)>>, // Destinations of Refs by their offsets
+    stack: Vec<Frame>,
+    show_suppressed: bool,
+    search_input: Option<String>,
+    message: String,
+}
+
+// Restores the terminal when the browser ends, even on errors
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+// Splits text into lines no longer than the width, breaking at spaces where possible
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    if width == 0 {
+        return lines;
+    }
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let mut word: Vec<char> = word.chars().collect();
+            while word.len() > width {
+                if !line.is_empty() {
+                    lines.push(line);
+                }
+                line = word.drain(..width).collect();
+            }
+            let word: String = word.into_iter().collect();
+            if line.chars().count() + word.chars().count() + 1 > width && !line.is_empty() {
+                lines.push(line);
+                line = word;
+            } else if line.is_empty() {
+                line = word;
+            } else {
+                line.push(' ');
+                line += &word;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn visibility_mark(visibility: Visibility) -> &'static str {
+    match visibility {
+        Visibility::Visible => "",
+        Visibility::GrayedOut => "[G] ",
+        Visibility::Suppressed => "[S] ",
+        Visibility::Disabled => "[D] ",
+    }
+}
+
+impl<'a> Browser<'a> {
+    fn frame(&self) -> &Frame {
+        self.stack.last().unwrap()
+    }
+
+    fn frame_mut(&mut self) -> &mut Frame {
+        self.stack.last_mut().unwrap()
+    }
+
+    fn form_title(&self, form_set_index: usize, form_id: u16) -> String {
+        let (form_set, strings_map) = &self.form_sets[form_set_index];
+        form_set
+            .form(form_id)
+            .map(|f| string(strings_map, f.title_string_id).to_string())
+            .unwrap_or_else(|| format!("FormId 0x{form_id:X}"))
+    }
+
+    fn item(&self, form_set_index: usize, offset: usize) -> Option<Item<'a>> {
+        let form_set = &self.form_sets[form_set_index].0;
+        if let Some(q) = form_set.questions.iter().find(|q| q.offset == offset) {
+            return Some(Item::Question(q));
+        }
+        form_set
+            .statements
+            .iter()
+            .find(|s| s.offset == offset)
+            .map(Item::Statement)
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        match self.frame().screen {
+            Screen::FormSets => {
+                for (index, (form_set, strings_map)) in self.form_sets.iter().enumerate() {
+                    rows.push(Row {
+                        text: format!("> {}", string(strings_map, form_set.title_string_id)),
+                        dim: false,
+                        bold: false,
+                        item: None,
+                        open: form_set.forms.first().map(|f| (index, f.form_id, None)),
+                        form_set_index: Some(index),
+                    });
+                }
+            }
+            Screen::Form(form_set_index, form_id) => {
+                let (form_set, strings_map) = &self.form_sets[form_set_index];
+                let context = &self.contexts[form_set_index];
+                for item in menu_tree::form_items(form_set, form_id) {
+                    let (conditions, text, open) = match item {
+                        Item::Statement(s) => {
+                            let mut text = string(strings_map, s.prompt_string_id).to_string();
+                            if let (StatementKind::Text, Some(id)) = (s.kind, s.text_string_id) {
+                                if id != 0 {
+                                    text = format!("{}  {}", text, string(strings_map, id));
+                                }
+                            }
+                            (&s.conditions, text, None)
+                        }
+                        Item::Question(q) if q.kind == QuestionKind::Ref => {
+                            let target = self.targets.get(&q.offset).cloned().flatten();
+                            (
+                                &q.conditions,
+                                format!("> {}", string(strings_map, q.prompt_string_id)),
+                                target.map(|(index, id)| (index, id, None)),
+                            )
+                        }
+                        Item::Question(q) => {
+                            let mut text = string(strings_map, q.prompt_string_id).to_string();
+                            if let Some(value) = menu_tree::value_text(context, q, strings_map) {
+                                text = format!("{text}  [{value}]");
+                            }
+                            (&q.conditions, text, None)
+                        }
+                    };
+                    let visibility = visibility::conditions_visibility(context, conditions).visibility;
+                    let hidden = visibility == Visibility::Suppressed || visibility == Visibility::Disabled;
+                    if hidden && !self.show_suppressed {
+                        continue;
+                    }
+                    rows.push(Row {
+                        text: format!("{}{}", visibility_mark(visibility), text),
+                        dim: visibility != Visibility::Visible,
+                        bold: matches!(item, Item::Statement(s) if s.kind == StatementKind::Subtitle),
+                        item: Some((form_set_index, item.offset())),
+                        open,
+                        form_set_index: None,
+                    });
+                }
+            }
+            Screen::Search(_, ref results) => {
+                for &(form_set_index, form_id, offset) in results {
+                    let (form_set, strings_map) = &self.form_sets[form_set_index];
+                    let prompt = match self.item(form_set_index, offset) {
+                        Some(Item::Question(q)) => string(strings_map, q.prompt_string_id),
+                        Some(Item::Statement(s)) => string(strings_map, s.prompt_string_id),
+                        None => "InvalidId",
+                    };
+                    rows.push(Row {
+                        text: format!(
+                            "{} > {} > {}",
+                            string(strings_map, form_set.title_string_id),
+                            self.form_title(form_set_index, form_id),
+                            prompt
+                        ),
+                        dim: false,
+                        bold: false,
+                        item: Some((form_set_index, offset)),
+                        open: Some((form_set_index, form_id, Some(offset))),
+                        form_set_index: None,
+                    });
+                }
+            }
+        }
+        rows
+    }
+
+    fn question_details(&self, form_set_index: usize, question: &Question, lines: &mut Vec<String>) {
+        let (form_set, strings_map) = &self.form_sets[form_set_index];
+        let context = &self.contexts[form_set_index];
+        lines.push(format!(
+            "{:?}, QuestionId: 0x{:X}, FormId: 0x{:X}",
+            question.kind, question.question_id, question.form_id
+        ));
+        lines.push(format!("Storage: {}", hidden_questions::storage_text(form_set, question, strings_map)));
+        if let Some(value) = menu_tree::value_text(context, question, strings_map) {
+            lines.push(format!("Value: {value}"));
+        }
+        lines.push(format!(
+            "QuestionFlags: 0x{:X}, Flags: 0x{:X}",
+            question.question_flags, question.flags
+        ));
+        if let (Some(min), Some(max), Some(step)) = (question.minimum, question.maximum, question.step) {
+            lines.push(format!("Min: {min}, Max: {max}, Step: {step}"));
+        }
+        if !question.options.is_empty() {
+            lines.push(String::from("Options:"));
+            for option in &question.options {
+                let defaults: Vec<String> = question
+                    .defaults
+                    .iter()
+                    .filter(|d| d.value == option.value)
+                    .map(|d| format!("DefaultId 0x{:X}", d.default_id))
+                    .collect();
+                let mut line = format!("  0x{:X} \"{}\"", option.value, string(strings_map, option.string_id));
+                if !defaults.is_empty() {
+                    line += &format!(" ({})", defaults.join(", "));
+                }
+                lines.push(line);
+            }
+        } else {
+            for default in &question.defaults {
+                lines.push(format!("Default 0x{:X}: 0x{:X}", default.default_id, default.value));
+            }
+        }
+        if let Some(ref target) = question.ref_target {
+            match self.targets.get(&question.offset).cloned().flatten() {
+                Some((index, id)) => lines.push(format!(
+                    "Goes to: {} > {}",
+                    string(self.form_sets[index].1, self.form_sets[index].0.title_string_id),
+                    self.form_title(index, id)
+                )),
+                None => lines.push(format!(
+                    "Goes to: unknown destination, FormId: {}",
+                    target.form_id.map(|id| format!("0x{id:X}")).unwrap_or_else(|| String::from("from value"))
+                )),
+            }
+        }
+    }
+
+    fn details(&self, row: &Row) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(index) = row.form_set_index {
+            let (form_set, strings_map) = &self.form_sets[index];
+            lines.push(format!("Guid: {}", form_set.guid));
+            lines.push(format!("Help: {}", string(strings_map, form_set.help_string_id)));
+            lines.push(format!("Forms: {}, Questions: {}", form_set.forms.len(), form_set.questions.len()));
+            for var_store in &form_set.var_stores {
+                lines.push(format!(
+                    "{:?} VarStore \"{}\", VarStoreId: 0x{:X}",
+                    var_store.kind, var_store.name, var_store.var_store_id
+                ));
+            }
+            return lines;
+        }
+
+        let (form_set_index, offset) = match row.item {
+            Some(item) => item,
+            None => return lines,
+        };
+        let (form_set, strings_map) = &self.form_sets[form_set_index];
+        let context = &self.contexts[form_set_index];
+        let (help_string_id, conditions) = match self.item(form_set_index, offset) {
+            Some(Item::Question(q)) => {
+                self.question_details(form_set_index, q, &mut lines);
+                (q.help_string_id, &q.conditions)
+            }
+            Some(Item::Statement(s)) => {
+                lines.push(format!("{:?}, FormId: 0x{:X}", s.kind, s.form_id));
+                (s.help_string_id, &s.conditions)
+            }
+            None => return lines,
+        };
+        for &index in conditions {
+            if let Some(condition) = form_set.conditions.get(index) {
+                lines.push(format!(
+                    "{:?} at 0x{:X}: {:?}",
+                    condition.kind,
+                    condition.offset,
+                    visibility::evaluate(context, &condition.expression)
+                ));
+            }
+        }
+        lines.push(String::new());
+        lines.push(string(strings_map, help_string_id).to_string());
+        lines
+    }
+
+    fn breadcrumb(&self) -> String {
+        let parts: Vec<String> = self
+            .stack
+            .iter()
+            .map(|frame| match frame.screen {
+                Screen::FormSets => String::from("Form sets"),
+                Screen::Form(index, id) => self.form_title(index, id),
+                Screen::Search(ref pattern, _) => format!("Search \"{pattern}\""),
+            })
+            .collect();
+        parts.join(" > ")
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        if height < 3 || width < 10 {
+            return Ok(());
+        }
+        let list_height = height - 2;
+        let list_width = width * 55 / 100;
+        let details_width = width - list_width - 1;
+
+        let rows = self.rows();
+        {
+            let frame = self.frame_mut();
+            if frame.selected >= rows.len() {
+                frame.selected = rows.len().saturating_sub(1);
+            }
+            if frame.selected < frame.scroll {
+                frame.scroll = frame.selected;
+            } else if frame.selected >= frame.scroll + list_height {
+                frame.scroll = frame.selected + 1 - list_height;
+            }
+        }
+        let (selected, scroll) = (self.frame().selected, self.frame().scroll);
+        let details = rows.get(selected).map(|r| self.details(r)).unwrap_or_default();
+        let details: Vec<String> = details.iter().flat_map(|l| wrap(l, details_width)).collect();
+
+        queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        queue!(
+            out,
+            SetAttribute(Attribute::Reverse),
+            Print(format!("{:<width$}", truncate(&format!(" {}", self.breadcrumb()), width))),
+            SetAttribute(Attribute::Reset)
+        )?;
+
+        for line in 0..list_height {
+            queue!(out, cursor::MoveTo(0, (line + 1) as u16))?;
+            if let Some(row) = rows.get(scroll + line) {
+                if scroll + line == selected {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                }
+                if row.dim {
+                    queue!(out, SetAttribute(Attribute::Dim))?;
+                }
+                if row.bold {
+                    queue!(out, SetAttribute(Attribute::Bold))?;
+                }
+                queue!(
+                    out,
+                    Print(format!("{:<list_width$}", truncate(&format!(" {}", row.text), list_width))),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            }
+            queue!(out, cursor::MoveTo(list_width as u16, (line + 1) as u16), Print("|"))?;
+            if let Some(text) = details.get(line) {
+                queue!(out, Print(truncate(text, details_width)))?;
+            }
+        }
+
+        let status = match self.search_input {
+            Some(ref input) => format!(" Search prompts: {input}_"),
+            None => format!(
+                " Enter: open  Esc: back  s: suppressed items {}  /: search  q: quit  {}",
+                if self.show_suppressed { "shown" } else { "hidden" },
+                self.message
+            ),
+        };
+        queue!(
+            out,
+            cursor::MoveTo(0, (height - 1) as u16),
+            SetAttribute(Attribute::Reverse),
+            Print(format!("{:<width$}", truncate(&status, width))),
+            SetAttribute(Attribute::Reset)
+        )?;
+        out.flush()
+    }
+
+    fn open(&mut self, form_set_index: usize, form_id: u16, offset: Option<usize>) {
+        self.stack.push(Frame {
+            screen: Screen::Form(form_set_index, form_id),
+            selected: 0,
+            scroll: 0,
+        });
+        if let Some(offset) = offset {
+            // Found items can be hidden, show them instead of selecting something else
+            let mut position = self.rows().iter().position(|r| r.item == Some((form_set_index, offset)));
+            if position.is_none() && !self.show_suppressed {
+                self.show_suppressed = true;
+                position = self.rows().iter().position(|r| r.item == Some((form_set_index, offset)));
+            }
+            self.frame_mut().selected = position.unwrap_or(0);
+        }
+    }
+
+    fn search(&mut self, pattern: String) {
+        let lower = pattern.to_lowercase();
+        let mut results = Vec::new();
+        for (index, (form_set, strings_map)) in self.form_sets.iter().enumerate() {
+            for form in &form_set.forms {
+                for item in menu_tree::form_items(form_set, form.form_id) {
+                    let prompt_string_id = match item {
+                        Item::Question(q) => q.prompt_string_id,
+                        Item::Statement(s) => s.prompt_string_id,
+                    };
+                    if string(strings_map, prompt_string_id).to_lowercase().contains(&lower) {
+                        results.push((index, form.form_id, item.offset()));
+                    }
+                }
+            }
+        }
+        self.message = format!("{} item(s) found", results.len());
+        self.stack.push(Frame {
+            screen: Screen::Search(pattern, results),
+            selected: 0,
+            scroll: 0,
+        });
+    }
+
+    // Handles a key press, returns false when the browser should exit
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if let Some(mut input) = self.search_input.take() {
+            match key.code {
+                KeyCode::Enter if !input.is_empty() => self.search(input),
+                KeyCode::Esc | KeyCode::Enter => {}
+                KeyCode::Backspace => {
+                    input.pop();
+                    self.search_input = Some(input);
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    self.search_input = Some(input);
+                }
+                _ => self.search_input = Some(input),
+            }
+            return true;
+        }
+
+        self.message.clear();
+        let count = self.rows().len();
+        let selected = self.frame().selected;
+        let new_selected = match key.code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Up => selected.saturating_sub(1),
+            KeyCode::Down => (selected + 1).min(count.saturating_sub(1)),
+            KeyCode::PageUp => selected.saturating_sub(10),
+            KeyCode::PageDown => (selected + 10).min(count.saturating_sub(1)),
+            KeyCode::Home => 0,
+            KeyCode::End => count.saturating_sub(1),
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left => {
+                if self.stack.len() > 1 {
+                    self.stack.pop();
+                }
+                return true;
+            }
+            KeyCode::Enter | KeyCode::Right => {
+                match self.rows().get(selected).map(|r| r.open) {
+                    Some(Some((index, id, offset))) => self.open(index, id, offset),
+                    Some(None) => self.message = String::from("Nothing to open"),
+                    None => {}
+                }
+                return true;
+            }
+            KeyCode::Char('s') => {
+                self.show_suppressed = !self.show_suppressed;
+                return true;
+            }
+            KeyCode::Char('/') => {
+                self.search_input = Some(String::new());
+                return true;
+            }
+            _ => return true,
+        };
+        self.frame_mut().selected = new_selected;
+        true
+    }
+}
+
+// Runs the browser over all given form sets until the user quits
+pub fn browse(form_sets: &[(FormSet, &HashMap<u16, String>)], buffers: &HashMap<(String, Guid), Vec<u8>>) -> io::Result<()> {
+    let models: Vec<&FormSet> = form_sets.iter().map(|(form_set, _)| form_set).collect();
+    let links = form_links::links(&models);
+    let mut browser = Browser {
+        form_sets,
+        contexts: models
+            .iter()
+            .map(|form_set| Context {
+                form_set,
+                buffers,
+                default_id: 0,
+            })
+            .collect(),
+        targets: links.iter().map(|l| (l.question.offset, l.target)).collect(),
+        stack: vec![Frame {
+            screen: Screen::FormSets,
+            selected: 0,
+            scroll: 0,
+        }],
+        show_suppressed: false,
+        search_input: None,
+        message: String::new(),
+    };
+
+    terminal::enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    let mut out = io::stdout();
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    loop {
+        browser.draw(&mut out)?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !browser.key(key) {
+                return Ok(());
+            }
+        }
+    }
+}
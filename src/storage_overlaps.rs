@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use uefi_model::{FormSet, Question, VarStore, VarStoreKind};
+
+//
+// Overlapping question storage
+//
+pub struct Overlap<'a> {
+    pub var_store: &'a VarStore,
+    pub first: &'a Question,
+    pub second: &'a Question,
+    pub conflicts: Vec<String>,
+}
+
+fn ranges_overlap(first: &Question, second: &Question, kind: VarStoreKind) -> bool {
+    // Questions in name/value varstores only alias each other when they use the same name
+    if kind == VarStoreKind::NameValue {
+        return first.var_offset == second.var_offset;
+    }
+    let (first_start, first_bits) = first.bit_range();
+    let (second_start, second_bits) = second.bit_range();
+    first_bits > 0
+        && second_bits > 0
+        && first_start < second_start + second_bits
+        && second_start < first_start + first_bits
+}
+
+fn option_text(question: &Question, strings_map: &HashMap<u16, String>) -> Vec<String> {
+    question
+        .options
+        .iter()
+        .map(|o| {
+            format!(
+                "0x{:X} \"{}\"",
+                o.value,
+                strings_map.get(&o.string_id).unwrap_or(&String::from("InvalidId"))
+            )
+        })
+        .collect()
+}
+
+pub fn default_text(question: &Question) -> Vec<String> {
+    let mut defaults: Vec<(u16, u64)> = question
+        .defaults
+        .iter()
+        .map(|d| (d.default_id, d.value))
+        .collect();
+    defaults.sort_unstable();
+    defaults
+        .iter()
+        .map(|&(id, value)| format!("DefaultId 0x{id:X}: 0x{value:X}"))
+        .collect()
+}
+
+// Differences between two questions sharing storage that make them conflict instead of just alias
+fn conflicts(first: &Question, second: &Question, strings_map: &HashMap<u16, String>) -> Vec<String> {
+    let mut result = Vec::new();
+
+    if first.bit_range() != second.bit_range() {
+        result.push(format!(
+            "storage differs: {} vs {}",
+            storage_text(first),
+            storage_text(second)
+        ));
+    }
+
+    if first.kind != second.kind {
+        result.push(format!("kind differs: {:?} vs {:?}", first.kind, second.kind));
+    }
+
+    let first_options = option_text(first, strings_map);
+    let second_options = option_text(second, strings_map);
+    if first_options != second_options {
+        result.push(format!(
+            "options differ: [{}] vs [{}]",
+            first_options.join(", "),
+            second_options.join(", ")
+        ));
+    }
+
+    if first.minimum != second.minimum || first.maximum != second.maximum || first.step != second.step {
+        result.push(format!(
+            "ranges differ: {} vs {}",
+            range_text(first),
+            range_text(second)
+        ));
+    }
+
+    let first_defaults = default_text(first);
+    let second_defaults = default_text(second);
+    if first_defaults != second_defaults {
+        result.push(format!(
+            "defaults differ: [{}] vs [{}]",
+            first_defaults.join(", "),
+            second_defaults.join(", ")
+        ));
+    }
+
+    result
+}
+
+pub fn range_text(question: &Question) -> String {
+    match (question.minimum, question.maximum, question.step) {
+        (Some(min), Some(max), Some(step)) => format!("Min: 0x{min:X}, Max: 0x{max:X}, Step: 0x{step:X}"),
+        _ => String::from("none"),
+    }
+}
+
+pub fn storage_text(question: &Question) -> String {
+    match question.bit_width {
+        Some(bits) => format!(
+            "0x{:X} bit {}, {} bit(s)",
+            question.byte_offset(),
+            question.var_offset % 8,
+            bits
+        ),
+        None => format!("0x{:X}, {} byte(s)", question.var_offset, question.width),
+    }
+}
+
+// Finds all pairs of questions that share storage in the same varstore of a form set
+pub fn find_overlaps<'a>(form_set: &'a FormSet, strings_map: &HashMap<u16, String>) -> Vec<Overlap<'a>> {
+    let mut result = Vec::new();
+
+    for var_store in &form_set.var_stores {
+        let mut questions: Vec<&Question> = form_set
+            .questions
+            .iter()
+            .filter(|q| q.var_store_id == var_store.var_store_id)
+            .collect();
+        questions.sort_by_key(|q| (q.bit_range().0, q.offset));
+
+        for (i, first) in questions.iter().enumerate() {
+            for second in &questions[i + 1..] {
+                if ranges_overlap(first, second, var_store.kind) {
+                    result.push(Overlap {
+                        var_store,
+                        first,
+                        second,
+                        conflicts: conflicts(first, second, strings_map),
+                    });
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn question_text(question: &Question, strings_map: &HashMap<u16, String>) -> String {
+    format!(
+        "{:?} \"{}\" (QuestionId: 0x{:X}, FormId: 0x{:X}, Offset: 0x{:X})",
+        question.kind,
+        strings_map
+            .get(&question.prompt_string_id)
+            .unwrap_or(&String::from("InvalidId")),
+        question.question_id,
+        question.form_id,
+        question.offset
+    )
+}
+
+pub fn overlap_report(
+    form_set: &FormSet,
+    overlaps: &[Overlap],
+    strings_map: &HashMap<u16, String>,
+) -> String {
+    let mut report = String::new();
+    writeln!(
+        &mut report,
+        "FormSet Guid: {}, Title: \"{}\", Overlaps: {}",
+        form_set.guid,
+        strings_map
+            .get(&form_set.title_string_id)
+            .unwrap_or(&String::from("InvalidId")),
+        overlaps.len()
+    )
+    .unwrap();
+
+    for overlap in overlaps {
+        let location = if overlap.var_store.kind == VarStoreKind::NameValue {
+            format!(
+                "name \"{}\"",
+                strings_map
+                    .get(&overlap.first.var_offset)
+                    .unwrap_or(&String::from("InvalidId"))
+            )
+        } else {
+            storage_text(overlap.first)
+        };
+        writeln!(
+            &mut report,
+            "\tVarStore \"{}\" (VarStoreId: 0x{:X}) at {}:",
+            overlap.var_store.name, overlap.var_store.var_store_id, location
+        )
+        .unwrap();
+        writeln!(&mut report, "\t\t{}", question_text(overlap.first, strings_map)).unwrap();
+        writeln!(&mut report, "\t\t{}", question_text(overlap.second, strings_map)).unwrap();
+        if overlap.conflicts.is_empty() {
+            writeln!(&mut report, "\t\tIdentical storage, options and defaults").unwrap();
+        }
+        for conflict in &overlap.conflicts {
+            writeln!(&mut report, "\t\tConflict: {conflict}").unwrap();
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uefi_model::test_models::{bit_field, buffer, default, form_set, option, question};
+    use uefi_model::QuestionKind;
+
+    fn pairs(form_set: &FormSet) -> Vec<(u16, u16, usize)> {
+        find_overlaps(form_set, &HashMap::new())
+            .iter()
+            .map(|o| (o.first.question_id, o.second.question_id, o.conflicts.len()))
+            .collect()
+    }
+
+    #[test]
+    fn identical_questions_alias_without_conflicts() {
+        let form_set = form_set(
+            vec![buffer(1, "Setup", 4)],
+            vec![
+                question(QuestionKind::CheckBox, 1, 1, 0, 1),
+                question(QuestionKind::CheckBox, 2, 1, 0, 1),
+            ],
+        );
+        assert_eq!(pairs(&form_set), vec![(1, 2, 0)]);
+    }
+
+    #[test]
+    fn adjacent_questions_and_other_varstores_do_not_overlap() {
+        let form_set = form_set(
+            vec![buffer(1, "Setup", 4), buffer(2, "Other", 4)],
+            vec![
+                question(QuestionKind::Numeric, 1, 1, 0, 2),
+                question(QuestionKind::Numeric, 2, 1, 2, 2),
+                question(QuestionKind::Numeric, 3, 2, 0, 2),
+            ],
+        );
+        assert_eq!(pairs(&form_set), vec![]);
+    }
+
+    #[test]
+    fn partially_overlapping_questions_conflict_in_storage() {
+        let form_set = form_set(
+            vec![buffer(1, "Setup", 4)],
+            vec![
+                question(QuestionKind::Numeric, 1, 1, 0, 2),
+                question(QuestionKind::Numeric, 2, 1, 1, 1),
+            ],
+        );
+        let overlaps = find_overlaps(&form_set, &HashMap::new());
+        assert_eq!(overlaps.len(), 1);
+        assert_eq!(overlaps[0].conflicts, vec!["storage differs: 0x0, 2 byte(s) vs 0x1, 1 byte(s)"]);
+    }
+
+    #[test]
+    fn bit_fields_overlap_only_when_their_bits_do() {
+        let form_set = form_set(
+            vec![buffer(1, "Setup", 4)],
+            vec![
+                bit_field(question(QuestionKind::Numeric, 1, 1, 0, 1), 3),
+                bit_field(question(QuestionKind::Numeric, 2, 1, 3, 1), 5),
+                bit_field(question(QuestionKind::Numeric, 3, 1, 2, 1), 2),
+            ],
+        );
+        assert_eq!(pairs(&form_set), vec![(1, 3, 1), (3, 2, 1)]);
+    }
+
+    #[test]
+    fn name_value_questions_overlap_when_they_use_the_same_name() {
+        let mut var_store = buffer(1, "Names", 0);
+        var_store.kind = VarStoreKind::NameValue;
+        var_store.size = None;
+        let form_set = form_set(
+            vec![var_store],
+            vec![
+                question(QuestionKind::String, 1, 1, 0x20, 0),
+                question(QuestionKind::String, 2, 1, 0x21, 0),
+                question(QuestionKind::String, 3, 1, 0x20, 0),
+            ],
+        );
+        assert_eq!(pairs(&form_set), vec![(1, 3, 0)]);
+    }
+
+    #[test]
+    fn options_and_defaults_conflict() {
+        let mut first = question(QuestionKind::OneOf, 1, 1, 0, 1);
+        first.options = vec![option(0x10, 0), option(0x11, 1)];
+        first.defaults = vec![default(0, 1)];
+        let mut second = question(QuestionKind::OneOf, 2, 1, 0, 1);
+        second.options = vec![option(0x10, 0)];
+        second.defaults = vec![default(0, 0)];
+        let form_set = form_set(vec![buffer(1, "Setup", 4)], vec![first, second]);
+
+        let strings: HashMap<u16, String> = [(0x10, "Disabled"), (0x11, "Enabled")]
+            .iter()
+            .map(|&(id, s)| (id, s.to_string()))
+            .collect();
+        let overlaps = find_overlaps(&form_set, &strings);
+        assert_eq!(
+            overlaps[0].conflicts,
+            vec![
+                "options differ: [0x0 \"Disabled\", 0x1 \"Enabled\"] vs [0x0 \"Disabled\"]",
+                "defaults differ: [DefaultId 0x0: 0x1] vs [DefaultId 0x0: 0x0]"
+            ]
+        );
+    }
+}
@@ -0,0 +1,989 @@
+#![allow(non_snake_case)]
+
+use byte_writer::{EncodeError, EncodeResult, Writer};
+use uefi_parser::*;
+
+//
+// Encoders mirroring the parsers in uefi_parser, so an edited model can be written back
+// Lengths and counts are computed from the data they describe, everything else is written as stored,
+// so encoding an unmodified parsed structure gives back the bytes it was parsed from
+//
+
+//
+// Common data types
+//
+pub fn guid(value: &Guid) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u32(value.data1);
+    writer.u16(value.data2);
+    writer.u16(value.data3);
+    writer.bytes(&value.data4);
+    writer.finish()
+}
+
+//
+// HII package header
+//
+pub fn hii_package(value: &HiiPackage) -> EncodeResult {
+    let mut writer = Writer::new();
+    let data = value.Data.unwrap_or_default();
+    let len = writer.length("Length", 4 + data.len(), 0xFFFFFF)?;
+    writer.u32(len as u32 | (u8::from(value.Type) as u32) << 24);
+    writer.bytes(data);
+    writer.finish()
+}
+
+//
+// HII string package
+//
+// Language is padded with zeroes up to HdrSize, which only grows if the language doesn't fit
+pub fn hii_string_package(value: &HiiStringPackage) -> EncodeResult {
+    let mut writer = Writer::new();
+    let language_size = (value.HdrSize as usize).saturating_sub(0x2F).max(value.Language.len());
+    let hs = writer.length("HdrSize", 0x2F + language_size, u32::MAX as usize)?;
+    writer.u32(hs as u32);
+    writer.u32(value.StringInfoOffset);
+    for &c in &value.LanguageWindow {
+        writer.u16(c);
+    }
+    writer.u16(value.LanguageName);
+    writer.bytes(value.Language.as_bytes());
+    writer.bytes(&vec![0; language_size - value.Language.len()]);
+    writer.u8(0); // Terminating zero
+    writer.bytes(value.Data);
+    writer.finish()
+}
+
+pub fn hii_sibt_blocks(value: &[HiiSibtBlock]) -> EncodeResult {
+    let mut writer = Writer::new();
+    for block in value {
+        writer.encode(hii_sibt_block, block)?;
+    }
+    writer.finish()
+}
+
+// Data of a block is everything after its type, including lengths of extended blocks
+pub fn hii_sibt_block(value: &HiiSibtBlock) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(value.Type.into());
+    writer.bytes(value.Data.unwrap_or_default());
+    writer.finish()
+}
+
+// UCS2 string with its terminating zero
+fn ucs2_string(value: &str) -> EncodeResult {
+    let mut writer = Writer::new();
+    for c in value.encode_utf16() {
+        writer.u16(c);
+    }
+    writer.u16(0);
+    writer.finish()
+}
+
+// SCSU string with its terminating zero, only the ASCII subset of SCSU is produced by UTF-8 strings
+fn scsu_string(value: &str) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.zero_terminated(value.as_bytes());
+    writer.finish()
+}
+
+// Strings of a block with the count before them
+fn string_count(writer: &mut Writer, value: &[String], string: fn(&str) -> EncodeResult) -> Result<(), EncodeError> {
+    let cnt = writer.length("StringCount", value.len(), u16::MAX as usize)?;
+    writer.u16(cnt as u16);
+    for s in value {
+        writer.encode(string, s)?;
+    }
+    Ok(())
+}
+
+pub fn sibt_string_scsu(value: &str) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.encode(scsu_string, value)?;
+    writer.finish()
+}
+
+// Font identifier is not a part of the parsed string, so it's given separately
+pub fn sibt_string_scsu_font(font_identifier: u8, value: &str) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(font_identifier);
+    writer.encode(scsu_string, value)?;
+    writer.finish()
+}
+
+pub fn sibt_strings_scsu(value: &[String]) -> EncodeResult {
+    let mut writer = Writer::new();
+    string_count(&mut writer, value, scsu_string)?;
+    writer.finish()
+}
+
+pub fn sibt_strings_scsu_font(font_identifier: u8, value: &[String]) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(font_identifier);
+    string_count(&mut writer, value, scsu_string)?;
+    writer.finish()
+}
+
+pub fn sibt_string_ucs2(value: &str) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.encode(ucs2_string, value)?;
+    writer.finish()
+}
+
+pub fn sibt_string_ucs2_font(font_identifier: u8, value: &str) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(font_identifier);
+    writer.encode(ucs2_string, value)?;
+    writer.finish()
+}
+
+pub fn sibt_strings_ucs2(value: &[String]) -> EncodeResult {
+    let mut writer = Writer::new();
+    string_count(&mut writer, value, ucs2_string)?;
+    writer.finish()
+}
+
+pub fn sibt_strings_ucs2_font(font_identifier: u8, value: &[String]) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(font_identifier);
+    string_count(&mut writer, value, ucs2_string)?;
+    writer.finish()
+}
+
+//
+// HII form package
+//
+// Length is computed from the data, the scope bit is set for operations that start a scope
+pub fn ifr_operation(value: &IfrOperation) -> EncodeResult {
+    let mut writer = Writer::new();
+    let data = value.Data.unwrap_or_default();
+    let len = writer.length("Length", 2 + data.len(), 0x7F)? as u8;
+    writer.u8(value.OpCode.into());
+    writer.u8(if value.ScopeStart { len | 0x80 } else { len });
+    writer.bytes(data);
+    writer.finish()
+}
+
+pub fn ifr_operations(value: &[IfrOperation]) -> EncodeResult {
+    let mut writer = Writer::new();
+    for operation in value {
+        writer.encode(ifr_operation, operation)?;
+    }
+    writer.finish()
+}
+
+// Header shared by all questions
+fn question_header(
+    writer: &mut Writer,
+    prompt_string_id: u16,
+    help_string_id: u16,
+    question_id: u16,
+    var_store_id: u16,
+    var_store_info: u16,
+    question_flags: u8,
+) {
+    writer.u16(prompt_string_id);
+    writer.u16(help_string_id);
+    writer.u16(question_id);
+    writer.u16(var_store_id);
+    writer.u16(var_store_info);
+    writer.u8(question_flags);
+}
+
+// Min, max and step values in the size selected by the lowest two bits of Flags, missing values are written as zeroes
+fn min_max_step(
+    writer: &mut Writer,
+    flags: u8,
+    data8: &[Option<u8>; 3],
+    data16: &[Option<u16>; 3],
+    data32: &[Option<u32>; 3],
+    data64: &[Option<u64>; 3],
+) {
+    match flags & 0x03 {
+        0 => data8.iter().for_each(|v| writer.u8(v.unwrap_or_default())),
+        1 => data16.iter().for_each(|v| writer.u16(v.unwrap_or_default())),
+        2 => data32.iter().for_each(|v| writer.u32(v.unwrap_or_default())),
+        _ => data64.iter().for_each(|v| writer.u64(v.unwrap_or_default())),
+    }
+}
+
+//
+//0x01 => IfrOpcode::Form
+//
+pub fn ifr_form(value: &IfrForm) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.FormId);
+    writer.u16(value.TitleStringId);
+    writer.finish()
+}
+
+//
+//0x02 => IfrOpcode::Subtitle
+//
+pub fn ifr_subtitle(value: &IfrSubtitle) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.PromptStringId);
+    writer.u16(value.HelpStringId);
+    writer.u8(value.Flags);
+    writer.finish()
+}
+
+//
+//0x03 => IfrOpcode::Text
+//
+pub fn ifr_text(value: &IfrText) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.PromptStringId);
+    writer.u16(value.HelpStringId);
+    writer.u16(value.TextId);
+    writer.finish()
+}
+
+//
+//0x04 => IfrOpcode::Image
+//
+pub fn ifr_image(value: &IfrImage) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.ImageId);
+    writer.finish()
+}
+
+//
+//0x05 => IfrOpcode::OneOf
+//
+pub fn ifr_one_of(value: &IfrOneOf) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    writer.u8(value.Flags);
+    min_max_step(
+        &mut writer,
+        value.Flags,
+        &value.MinMaxStepData8,
+        &value.MinMaxStepData16,
+        &value.MinMaxStepData32,
+        &value.MinMaxStepData64,
+    );
+    writer.finish()
+}
+
+//
+//0x06 => IfrOpcode::CheckBox
+//
+pub fn ifr_check_box(value: &IfrCheckBox) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    writer.u8(value.Flags);
+    writer.finish()
+}
+
+//
+//0x07 => IfrOpcode::Numeric
+//
+pub fn ifr_numeric(value: &IfrNumeric) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    writer.u8(value.Flags);
+    min_max_step(
+        &mut writer,
+        value.Flags,
+        &value.MinMaxStepData8,
+        &value.MinMaxStepData16,
+        &value.MinMaxStepData32,
+        &value.MinMaxStepData64,
+    );
+    writer.finish()
+}
+
+//
+//0x08 => IfrOpcode::Password
+//
+pub fn ifr_password(value: &IfrPassword) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    writer.u16(value.MinSize);
+    writer.u16(value.MaxSize);
+    writer.finish()
+}
+
+//
+//0x09 => IfrOpcode::OneOfOption
+//
+pub fn hii_time(value: &HiiTime) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(value.Hour);
+    writer.u8(value.Minute);
+    writer.u8(value.Second);
+    writer.finish()
+}
+
+pub fn hii_date(value: &HiiDate) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.Year);
+    writer.u8(value.Month);
+    writer.u8(value.Day);
+    writer.finish()
+}
+
+// Fields are present up to the first missing one
+pub fn hii_ref(value: &HiiRef) -> EncodeResult {
+    let mut writer = Writer::new();
+    if let Some(qid) = value.QuestionId {
+        writer.u16(qid);
+        if let Some(fid) = value.FormId {
+            writer.u16(fid);
+            if let Some(fsg) = &value.FormSetGuid {
+                writer.encode(guid, fsg)?;
+                if let Some(dpid) = value.DevicePathStringId {
+                    writer.u16(dpid);
+                }
+            }
+        }
+    }
+    writer.finish()
+}
+
+// Boolean values are written as 1 and 0
+fn ifr_type_value(value: &IfrTypeValue) -> EncodeResult {
+    let mut writer = Writer::new();
+    match value {
+        IfrTypeValue::NumSize8(x) => {
+            writer.u8(0x00);
+            writer.u8(*x);
+        }
+        IfrTypeValue::NumSize16(x) => {
+            writer.u8(0x01);
+            writer.u16(*x);
+        }
+        IfrTypeValue::NumSize32(x) => {
+            writer.u8(0x02);
+            writer.u32(*x);
+        }
+        IfrTypeValue::NumSize64(x) => {
+            writer.u8(0x03);
+            writer.u64(*x);
+        }
+        IfrTypeValue::Boolean(x) => {
+            writer.u8(0x04);
+            writer.u8(*x as u8);
+        }
+        IfrTypeValue::Time(x) => {
+            writer.u8(0x05);
+            writer.encode(hii_time, x)?;
+        }
+        IfrTypeValue::Date(x) => {
+            writer.u8(0x06);
+            writer.encode(hii_date, x)?;
+        }
+        IfrTypeValue::String(x) => {
+            writer.u8(0x07);
+            writer.u16(*x);
+        }
+        IfrTypeValue::Other => writer.u8(0x08),
+        IfrTypeValue::Undefined => writer.u8(0x09),
+        IfrTypeValue::Action(x) => {
+            writer.u8(0x0A);
+            writer.u16(*x);
+        }
+        IfrTypeValue::Buffer(x) => {
+            writer.u8(0x0B);
+            writer.bytes(x);
+        }
+        IfrTypeValue::Ref(x) => {
+            writer.u8(0x0C);
+            writer.encode(hii_ref, x)?;
+        }
+        IfrTypeValue::Unknown(x) => writer.u8(*x),
+    }
+    writer.finish()
+}
+
+pub fn ifr_one_of_option(value: &IfrOneOfOption) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.OptionStringId);
+    writer.u8(value.Flags);
+    writer.encode(ifr_type_value, &value.Value)?;
+    writer.finish()
+}
+
+//
+//0x0C => IfrOpcode::Action
+//
+pub fn ifr_action(value: &IfrAction) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    if let Some(csid) = value.ConfigStringId {
+        writer.u16(csid);
+    }
+    writer.finish()
+}
+
+//
+//0x0D => IfrOpcode::ResetButton
+//
+pub fn ifr_reset_button(value: &IfrResetButton) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.PromptStringId);
+    writer.u16(value.HelpStringId);
+    writer.u16(value.DefaultId);
+    writer.finish()
+}
+
+//
+//0x0E => IfrOpcode::FormSet
+//
+// The number of class GUIDs is stored in the lowest two bits of Flags
+pub fn ifr_form_set(value: &IfrFormSet) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.encode(guid, &value.Guid)?;
+    writer.u16(value.TitleStringId);
+    writer.u16(value.HelpStringId);
+    if let Some(f) = value.Flags {
+        let guids = value.ClassGuids.as_deref().unwrap_or_default();
+        let cnt = writer.length("ClassGuids", guids.len(), 0x03)? as u8;
+        writer.u8((f & !0x03) | cnt);
+        for g in guids {
+            writer.encode(guid, g)?;
+        }
+    }
+    writer.finish()
+}
+
+//
+//0x0F => IfrOpcode::Ref
+//
+pub fn ifr_ref(value: &IfrRef) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    if let Some(fid) = value.FormId {
+        writer.u16(fid);
+        if let Some(rqid) = value.RefQuestionId {
+            writer.u16(rqid);
+            if let Some(fsg) = &value.FormSetGuid {
+                writer.encode(guid, fsg)?;
+                if let Some(dpid) = value.DevicePathId {
+                    writer.u16(dpid);
+                }
+            }
+        }
+    }
+    writer.finish()
+}
+
+//
+//0x10 => IfrOpcode::NoSubmitIf
+//
+pub fn ifr_no_submit_if(value: &IfrNoSumbitIf) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.ErrorStringId);
+    writer.finish()
+}
+
+//
+//0x11 => IfrOpcode::InconsistentIf
+//
+pub fn ifr_inconsistent_if(value: &IfrInconsistentIf) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.ErrorStringId);
+    writer.finish()
+}
+
+//
+//0x12 => IfrOpcode::EqIdVal
+//
+pub fn ifr_eq_id_val(value: &IfrEqIdVal) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.QuestionId);
+    writer.u16(value.Value);
+    writer.finish()
+}
+
+//
+//0x13 => IfrOpcode::EqIdId
+//
+pub fn ifr_eq_id_id(value: &IfrEqIdId) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.QuestionId);
+    writer.u16(value.OtherQuestionId);
+    writer.finish()
+}
+
+//
+//0x14 => IfrOpcode::EqIdValList
+//
+pub fn ifr_eq_id_val_list(value: &IfrEqIdValList) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.QuestionId);
+    let len = writer.length("ListLength", value.Values.len(), u16::MAX as usize)?;
+    writer.u16(len as u16);
+    for &v in &value.Values {
+        writer.u16(v);
+    }
+    writer.finish()
+}
+
+//
+//0x18 => IfrOpcode::Rule
+//
+pub fn ifr_rule(value: &IfrRule) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(value.RuleId);
+    writer.finish()
+}
+
+//
+//0x1A => IfrOpcode::Date
+//
+pub fn ifr_date(value: &IfrDate) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    writer.u8(value.Flags);
+    writer.finish()
+}
+
+//
+//0x1B => IfrOpcode::Time
+//
+pub fn ifr_time(value: &IfrTime) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    writer.u8(value.Flags);
+    writer.finish()
+}
+
+//
+//0x1C => IfrOpcode::String
+//
+pub fn ifr_string(value: &IfrString) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    writer.u8(value.MinSize);
+    writer.u8(value.MaxSize);
+    writer.u8(value.Flags);
+    writer.finish()
+}
+
+//
+//0x1D => IfrOpcode::Refresh
+//
+pub fn ifr_refresh(value: &IfrRefresh) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(value.RefreshInterval);
+    writer.finish()
+}
+
+//
+//0x1F => IfrOpcode::Animation
+//
+pub fn ifr_animation(value: &IfrAnimation) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.AnimationId);
+    writer.finish()
+}
+
+//
+//0x23 => IfrOpcode::OrderedList
+//
+pub fn ifr_ordered_list(value: &IfrOrderedList) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    writer.u8(value.MaxContainers);
+    writer.u8(value.Flags);
+    writer.finish()
+}
+
+//
+//0x24 => IfrOpcode::VarStore
+//
+pub fn ifr_var_store(value: &IfrVarStore) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.encode(guid, &value.Guid)?;
+    writer.u16(value.VarStoreId);
+    writer.u16(value.Size);
+    writer.zero_terminated(value.Name.as_bytes());
+    writer.finish()
+}
+
+//
+//0x25 => IfrOpcode::VarStoreNameValue
+//
+pub fn ifr_var_store_name_value(value: &IfrVarStoreNameValue) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.VarStoreId);
+    writer.encode(guid, &value.Guid)?;
+    writer.finish()
+}
+
+//
+//0x26 => IfrOpcode::VarStoreEfi
+//
+// Name is only present after Size
+pub fn ifr_var_store_efi(value: &IfrVarStoreEfi) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.VarStoreId);
+    writer.encode(guid, &value.Guid)?;
+    writer.u32(value.Attributes);
+    if let Some(size) = value.Size {
+        writer.u16(size);
+        if let Some(name) = &value.Name {
+            writer.zero_terminated(name.as_bytes());
+        }
+    }
+    writer.finish()
+}
+
+//
+//0x27 => IfrOpcode::VarStoreDevice
+//
+pub fn ifr_var_store_device(value: &IfrVarStoreDevice) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.DevicePathStringId);
+    writer.finish()
+}
+
+//
+//0x2B => IfrOpcode::Get
+//
+pub fn ifr_get(value: &IfrGet) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.VarStoreId);
+    writer.u16(value.VarStoreInfo);
+    writer.u8(value.VarStoreType);
+    writer.finish()
+}
+
+//
+//0x2C => IfrOpcode::Set
+//
+pub fn ifr_set(value: &IfrSet) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.VarStoreId);
+    writer.u16(value.VarStoreInfo);
+    writer.u8(value.VarStoreType);
+    writer.finish()
+}
+
+//
+//0x3F => IfrOpcode::RuleRef
+//
+pub fn ifr_rule_ref(value: &IfrRuleRef) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(value.RuleId);
+    writer.finish()
+}
+
+//
+//0x40 => IfrOpcode::QuestionRef1
+//
+pub fn ifr_question_ref_1(value: &IfrQuestionRef1) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.QuestionId);
+    writer.finish()
+}
+
+//
+//0x42 => IfrOpcode::Uint8
+//
+pub fn ifr_uint8(value: &IfrUint8) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(value.Value);
+    writer.finish()
+}
+
+//
+//0x43 => IfrOpcode::Uint16
+//
+pub fn ifr_uint16(value: &IfrUint16) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.Value);
+    writer.finish()
+}
+
+//
+//0x44 => IfrOpcode::Uint32
+//
+pub fn ifr_uint32(value: &IfrUint32) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u32(value.Value);
+    writer.finish()
+}
+
+//
+//0x45 => IfrOpcode::Uint64
+//
+pub fn ifr_uint64(value: &IfrUint64) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u64(value.Value);
+    writer.finish()
+}
+
+//
+//0x49 => IfrOpcode::ToString
+//
+pub fn ifr_to_string(value: &IfrToString) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(value.Format);
+    writer.finish()
+}
+
+//
+//0x4C => IfrOpcode::Find
+//
+pub fn ifr_find(value: &IfrFind) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(value.Format);
+    writer.finish()
+}
+
+//
+//0x4E => IfrOpcode::StringRef1
+//
+pub fn ifr_string_ref_1(value: &IfrStringRef1) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.StringId);
+    writer.finish()
+}
+
+//
+//0x51 => IfrOpcode::QuestionRef3
+//
+pub fn ifr_question_ref_3(value: &IfrQuestionRef3) -> EncodeResult {
+    let mut writer = Writer::new();
+    if let Some(dpid) = value.DevicePathId {
+        writer.u16(dpid);
+        if let Some(qg) = &value.QuestionGuid {
+            writer.encode(guid, qg)?;
+        }
+    }
+    writer.finish()
+}
+
+//
+//0x59 => IfrOpcode::Span
+//
+pub fn ifr_span(value: &IfrSpan) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(value.Flags);
+    writer.finish()
+}
+
+//
+//0x5B => IfrOpcode::Default
+//
+pub fn ifr_default(value: &IfrDefault) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.DefaultId);
+    writer.encode(ifr_type_value, &value.Value)?;
+    writer.finish()
+}
+
+//
+//0x5C => IfrOpcode::DefaultStore
+//
+pub fn ifr_default_store(value: &IfrDefaultStore) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.NameStringId);
+    writer.u16(value.DefaultId);
+    writer.finish()
+}
+
+//
+//0x5D => IfrOpcode::FormMap
+//
+pub fn ifr_form_map_method(value: &IfrFormMapMethod) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.MethodTitleId);
+    writer.encode(guid, &value.MethodIdentifier)?;
+    writer.finish()
+}
+
+pub fn ifr_form_map(value: &IfrFormMap) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.FormId);
+    for method in &value.Methods {
+        writer.encode(ifr_form_map_method, method)?;
+    }
+    writer.finish()
+}
+
+//
+//0x5F => IfrOpcode::Guid
+//
+pub fn ifr_guid(value: &IfrGuid) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.encode(guid, &value.Guid)?;
+    writer.bytes(value.Data);
+    writer.finish()
+}
+
+// Bit-field varstore questions always store Min/Max/Step values as u32, missing values are written as zeroes
+pub fn ifr_one_of_bit(value: &IfrOneOf) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    writer.u8(value.Flags);
+    value.MinMaxStepData32.iter().for_each(|v| writer.u32(v.unwrap_or_default()));
+    writer.finish()
+}
+
+pub fn ifr_numeric_bit(value: &IfrNumeric) -> EncodeResult {
+    let mut writer = Writer::new();
+    question_header(
+        &mut writer,
+        value.PromptStringId,
+        value.HelpStringId,
+        value.QuestionId,
+        value.VarStoreId,
+        value.VarStoreInfo,
+        value.QuestionFlags,
+    );
+    writer.u8(value.Flags);
+    value.MinMaxStepData32.iter().for_each(|v| writer.u32(v.unwrap_or_default()));
+    writer.finish()
+}
+
+pub fn ifr_guid_edk2(value: &IfrGuidEdk2) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(value.ExtendedOpCode.into());
+    writer.bytes(value.Data);
+    writer.finish()
+}
+
+pub fn ifr_guid_edk2_banner(value: &IfrGuidEdk2Banner) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.TitleId);
+    writer.u16(value.LineNumber);
+    writer.u8(value.Alignment);
+    writer.finish()
+}
+
+pub fn ifr_guid_edk(value: &IfrGuidEdk) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u8(value.ExtendedOpCode.into());
+    writer.u16(value.QuestionId);
+    writer.bytes(value.Data);
+    writer.finish()
+}
+
+//
+//0x60 => IfrOpcode::Security
+//
+pub fn ifr_security(value: &IfrSecurity) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.encode(guid, &value.Guid)?;
+    writer.finish()
+}
+
+//
+//0x62 => IfrOpcode::RefreshId
+//
+pub fn ifr_refresh_id(value: &IfrRefreshId) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.encode(guid, &value.Guid)?;
+    writer.finish()
+}
+
+//
+//0x63 => IfrOpcode::WarningIf
+//
+pub fn ifr_warning_if(value: &IfrWarningIf) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.u16(value.WarningStringId);
+    writer.u8(value.Timeout);
+    writer.finish()
+}
+
+//
+//0x64 => IfrOpcode::Match2
+//
+pub fn ifr_match_2(value: &IfrMatch2) -> EncodeResult {
+    let mut writer = Writer::new();
+    writer.encode(guid, &value.Guid)?;
+    writer.finish()
+}
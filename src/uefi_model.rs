@@ -0,0 +1,1008 @@
+use uefi_parser;
+use uefi_parser::{Guid, IfrOpcode, IfrOperation, IfrTypeValue};
+
+//
+// Parsed model of a UEFI HII form package
+//
+#[derive(Debug, Clone)]
+pub struct FormSet {
+    pub guid: Guid,
+    pub title_string_id: u16,
+    pub help_string_id: u16,
+    pub offset: usize,
+    pub var_stores: Vec<VarStore>,
+    pub forms: Vec<Form>,
+    pub questions: Vec<Question>,
+    pub statements: Vec<Statement>,
+    pub conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Form {
+    pub form_id: u16,
+    pub title_string_id: u16,
+    pub offset: usize,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum VarStoreKind {
+    Buffer,
+    Efi,
+    NameValue,
+}
+
+#[derive(Debug, Clone)]
+pub struct VarStore {
+    pub kind: VarStoreKind,
+    pub var_store_id: u16,
+    pub guid: Guid,
+    pub name: String,
+    pub size: Option<u16>,
+    pub offset: usize,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum QuestionKind {
+    OneOf,
+    CheckBox,
+    Numeric,
+    Password,
+    Action,
+    Ref,
+    Date,
+    Time,
+    String,
+    OrderedList,
+}
+
+#[derive(Debug, Clone)]
+pub struct QuestionOption {
+    pub string_id: u16,
+    pub value: u64,
+    pub flags: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct QuestionDefault {
+    pub default_id: u16,
+    pub value: u64,
+}
+
+// Destination of a Ref question, missing parts refer to the current form set or form
+#[derive(Debug, Clone)]
+pub struct RefTarget {
+    pub form_id: Option<u16>, // Ref5 takes the destination from its value
+    pub question_id: Option<u16>,
+    pub form_set_guid: Option<Guid>,
+    pub device_path_id: Option<u16>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Question {
+    pub kind: QuestionKind,
+    pub form_id: u16,
+    pub prompt_string_id: u16,
+    pub help_string_id: u16,
+    pub question_id: u16,
+    pub var_store_id: u16,
+    pub var_offset: u16, // Bit offset for bit-field questions, name StringId for questions in name/value varstores
+    pub bit_width: Option<u8>, // Only set for questions in bit-field varstores
+    pub question_flags: u8,
+    pub flags: u8,
+    pub width: usize, // Storage width in bytes, for bit-field questions the number of bytes they span
+    pub minimum: Option<u64>,
+    pub maximum: Option<u64>,
+    pub step: Option<u64>,
+    pub options: Vec<QuestionOption>,
+    pub defaults: Vec<QuestionDefault>,
+    pub conditions: Vec<usize>, // Indices of all conditions the question is placed in, outermost first
+    pub ref_target: Option<RefTarget>, // Only set for Ref questions
+    pub offset: usize,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum StatementKind {
+    Subtitle,
+    Text,
+}
+
+// Statements only show text in a form and have no storage
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub form_id: u16,
+    pub prompt_string_id: u16,
+    pub help_string_id: u16,
+    pub text_string_id: Option<u16>, // Second text of Text statements
+    pub conditions: Vec<usize>,
+    pub offset: usize,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ConditionKind {
+    SuppressIf,
+    GrayOutIf,
+    DisableIf,
+}
+
+// Expression operations in postfix order, operands are taken from the stack
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum ExpressionOp {
+    EqIdVal { question_id: u16, value: u64 },
+    EqIdId { question_id: u16, other_question_id: u16 },
+    EqIdValList { question_id: u16, values: Vec<u64> },
+    QuestionRef(u16),
+    QuestionRefFromStack, // QuestionRef2 and QuestionRef3 take the QuestionId from the stack
+    Get { var_store_id: u16, var_offset: u16, width: usize },
+    Uint(u64),
+    Boolean(bool),
+    Undefined,
+    Not,
+    And,
+    Or,
+    Equal,
+    NotEqual,
+    GreaterThan,
+    GreaterEqual,
+    LessThan,
+    LessEqual,
+    BitwiseNot,
+    BitwiseAnd,
+    BitwiseOr,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    ToBoolean,
+    ToUint,
+    Conditional,
+    Dup,
+    Other { opcode: u8, operands: usize }, // Operations that can't be evaluated, their result is undefined
+}
+
+#[derive(Debug, Clone)]
+pub struct Condition {
+    pub kind: ConditionKind,
+    pub expression: Vec<ExpressionOp>,
+    pub offset: usize,
+}
+
+impl FormSet {
+    pub fn var_store(&self, var_store_id: u16) -> Option<&VarStore> {
+        self.var_stores
+            .iter()
+            .find(|vs| vs.var_store_id == var_store_id)
+    }
+
+    pub fn form(&self, form_id: u16) -> Option<&Form> {
+        self.forms.iter().find(|f| f.form_id == form_id)
+    }
+}
+
+impl Question {
+    // Offset of the first byte of the storage of this question
+    pub fn byte_offset(&self) -> usize {
+        match self.bit_width {
+            Some(_) => self.var_offset as usize / 8,
+            None => self.var_offset as usize,
+        }
+    }
+
+    // Storage range of this question as (first bit, number of bits)
+    pub fn bit_range(&self) -> (usize, usize) {
+        match self.bit_width {
+            Some(bits) => (self.var_offset as usize, bits as usize),
+            None => (self.var_offset as usize * 8, self.width * 8),
+        }
+    }
+}
+
+// Numeric and boolean values are the only ones that can be stored in a varstore as an integer
+pub fn type_value_as_u64(value: &IfrTypeValue) -> Option<u64> {
+    match *value {
+        IfrTypeValue::NumSize8(x) => Some(x as u64),
+        IfrTypeValue::NumSize16(x) => Some(x as u64),
+        IfrTypeValue::NumSize32(x) => Some(x as u64),
+        IfrTypeValue::NumSize64(x) => Some(x),
+        IfrTypeValue::Boolean(x) => Some(x as u64),
+        _ => None,
+    }
+}
+
+fn type_value_width(value: &IfrTypeValue) -> usize {
+    match *value {
+        IfrTypeValue::NumSize16(_) => 2,
+        IfrTypeValue::NumSize32(_) => 4,
+        IfrTypeValue::NumSize64(_) => 8,
+        _ => 1,
+    }
+}
+
+fn min_max_step<T: Copy + Into<u64>>(data: &[Option<T>; 3]) -> Option<[u64; 3]> {
+    match *data {
+        [Some(min), Some(max), Some(step)] => Some([min.into(), max.into(), step.into()]),
+        _ => None,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn new_question(
+    kind: QuestionKind,
+    form_id: u16,
+    prompt_string_id: u16,
+    help_string_id: u16,
+    question_id: u16,
+    var_store_id: u16,
+    var_offset: u16,
+    question_flags: u8,
+    offset: usize,
+) -> Question {
+    Question {
+        kind,
+        form_id,
+        prompt_string_id,
+        help_string_id,
+        question_id,
+        var_store_id,
+        var_offset,
+        bit_width: None,
+        question_flags,
+        flags: 0,
+        width: 0,
+        minimum: None,
+        maximum: None,
+        step: None,
+        options: Vec::new(),
+        defaults: Vec::new(),
+        conditions: Vec::new(),
+        ref_target: None,
+        offset,
+    }
+}
+
+// What was opened by an operation with ScopeStart set
+enum Scope {
+    FormSet,
+    Form,
+    Question(usize),
+    Condition,
+    BitVarStore,
+    Other,
+}
+
+// Build the model of all form sets found in a list of IFR operations
+// offsets are the offsets of the operations in the input file
+pub fn form_sets(operations: &[IfrOperation], offsets: &[usize]) -> Vec<FormSet> {
+    let mut form_sets: Vec<FormSet> = Vec::new();
+    let mut scopes: Vec<Scope> = Vec::new();
+    let mut current_form_id: u16 = 0;
+    let mut open_conditions: Vec<usize> = Vec::new(); // Conditions in the order of their scopes
+    let mut expression_target: Option<usize> = None; // Condition that receives expression operations
+
+    for (operation, &operation_offset) in operations.iter().zip(offsets) {
+        if operation.OpCode == IfrOpcode::End {
+            match scopes.pop() {
+                Some(Scope::Form) => current_form_id = 0,
+                Some(Scope::Condition) => {
+                    open_conditions.pop();
+                    expression_target = None;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let data = operation.Data.unwrap_or(&[]);
+
+        // Expressions follow the condition operation and end with the first statement
+        if let Some(index) = expression_target {
+            match expression_op(operation.OpCode, data) {
+                Some(op) => {
+                    if let Some(fs) = form_sets.last_mut() {
+                        if let Some(condition) = fs.conditions.get_mut(index) {
+                            condition.expression.push(op);
+                        }
+                    }
+                    if operation.ScopeStart {
+                        scopes.push(Scope::Other);
+                    }
+                    continue;
+                }
+                None => expression_target = None,
+            }
+        }
+
+        let mut scope = Scope::Other;
+        let bit_var_store = scopes.iter().any(|s| matches!(*s, Scope::BitVarStore));
+
+        // Questions are always added to the last form set found
+        let mut question: Option<Question> = None;
+
+        match operation.OpCode {
+            IfrOpcode::FormSet => {
+                if let Ok((_, fs)) = uefi_parser::ifr_form_set(data) {
+                    form_sets.push(FormSet {
+                        guid: fs.Guid,
+                        title_string_id: fs.TitleStringId,
+                        help_string_id: fs.HelpStringId,
+                        offset: operation_offset,
+                        var_stores: Vec::new(),
+                        forms: Vec::new(),
+                        questions: Vec::new(),
+                        statements: Vec::new(),
+                        conditions: Vec::new(),
+                    });
+                    scope = Scope::FormSet;
+                }
+            }
+            IfrOpcode::Form => {
+                if let Ok((_, form)) = uefi_parser::ifr_form(data) {
+                    current_form_id = form.FormId;
+                    if let Some(fs) = form_sets.last_mut() {
+                        fs.forms.push(Form {
+                            form_id: form.FormId,
+                            title_string_id: form.TitleStringId,
+                            offset: operation_offset,
+                        });
+                    }
+                    scope = Scope::Form;
+                }
+            }
+            IfrOpcode::FormMap => {
+                if let Ok((_, form_map)) = uefi_parser::ifr_form_map(data) {
+                    current_form_id = form_map.FormId;
+                    if let Some(fs) = form_sets.last_mut() {
+                        fs.forms.push(Form {
+                            form_id: form_map.FormId,
+                            title_string_id: form_map
+                                .Methods
+                                .first()
+                                .map(|m| m.MethodTitleId)
+                                .unwrap_or(0),
+                            offset: operation_offset,
+                        });
+                    }
+                    scope = Scope::Form;
+                }
+            }
+            IfrOpcode::VarStore => {
+                if let Ok((_, vs)) = uefi_parser::ifr_var_store(data) {
+                    if let Some(fs) = form_sets.last_mut() {
+                        fs.var_stores.push(VarStore {
+                            kind: VarStoreKind::Buffer,
+                            var_store_id: vs.VarStoreId,
+                            guid: vs.Guid,
+                            name: vs.Name,
+                            size: Some(vs.Size),
+                            offset: operation_offset,
+                        });
+                    }
+                }
+            }
+            IfrOpcode::VarStoreEfi => {
+                if let Ok((_, vs)) = uefi_parser::ifr_var_store_efi(data) {
+                    if let Some(fs) = form_sets.last_mut() {
+                        fs.var_stores.push(VarStore {
+                            kind: VarStoreKind::Efi,
+                            var_store_id: vs.VarStoreId,
+                            guid: vs.Guid,
+                            name: vs.Name.unwrap_or_default(),
+                            size: vs.Size,
+                            offset: operation_offset,
+                        });
+                    }
+                }
+            }
+            IfrOpcode::VarStoreNameValue => {
+                if let Ok((_, vs)) = uefi_parser::ifr_var_store_name_value(data) {
+                    if let Some(fs) = form_sets.last_mut() {
+                        fs.var_stores.push(VarStore {
+                            kind: VarStoreKind::NameValue,
+                            var_store_id: vs.VarStoreId,
+                            guid: vs.Guid,
+                            name: String::new(),
+                            size: None,
+                            offset: operation_offset,
+                        });
+                    }
+                }
+            }
+            IfrOpcode::Subtitle => {
+                if let Ok((_, x)) = uefi_parser::ifr_subtitle(data) {
+                    if let Some(fs) = form_sets.last_mut() {
+                        fs.statements.push(Statement {
+                            kind: StatementKind::Subtitle,
+                            form_id: current_form_id,
+                            prompt_string_id: x.PromptStringId,
+                            help_string_id: x.HelpStringId,
+                            text_string_id: None,
+                            conditions: open_conditions.clone(),
+                            offset: operation_offset,
+                        });
+                    }
+                }
+            }
+            IfrOpcode::Text => {
+                if let Ok((_, x)) = uefi_parser::ifr_text(data) {
+                    if let Some(fs) = form_sets.last_mut() {
+                        fs.statements.push(Statement {
+                            kind: StatementKind::Text,
+                            form_id: current_form_id,
+                            prompt_string_id: x.PromptStringId,
+                            help_string_id: x.HelpStringId,
+                            text_string_id: Some(x.TextId),
+                            conditions: open_conditions.clone(),
+                            offset: operation_offset,
+                        });
+                    }
+                }
+            }
+            IfrOpcode::OneOf => {
+                let parsed = if bit_var_store {
+                    uefi_parser::ifr_one_of_bit(data)
+                } else {
+                    uefi_parser::ifr_one_of(data)
+                };
+                if let Ok((_, q)) = parsed {
+                    let mut new = new_question(
+                        QuestionKind::OneOf,
+                        current_form_id,
+                        q.PromptStringId,
+                        q.HelpStringId,
+                        q.QuestionId,
+                        q.VarStoreId,
+                        q.VarStoreInfo,
+                        q.QuestionFlags,
+                        operation_offset,
+                    );
+                    new.flags = q.Flags;
+                    if bit_var_store {
+                        set_bit_width(&mut new, q.Flags & uefi_parser::EDKII_IFR_NUMERIC_SIZE_BIT);
+                    } else {
+                        new.width = 1 << (q.Flags & 0x03);
+                    }
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::Numeric => {
+                let parsed = if bit_var_store {
+                    uefi_parser::ifr_numeric_bit(data)
+                } else {
+                    uefi_parser::ifr_numeric(data)
+                };
+                if let Ok((_, q)) = parsed {
+                    let mut new = new_question(
+                        QuestionKind::Numeric,
+                        current_form_id,
+                        q.PromptStringId,
+                        q.HelpStringId,
+                        q.QuestionId,
+                        q.VarStoreId,
+                        q.VarStoreInfo,
+                        q.QuestionFlags,
+                        operation_offset,
+                    );
+                    new.flags = q.Flags;
+                    if bit_var_store {
+                        set_bit_width(&mut new, q.Flags & uefi_parser::EDKII_IFR_NUMERIC_SIZE_BIT);
+                    } else {
+                        new.width = 1 << (q.Flags & 0x03);
+                    }
+                    if let Some(mms) = min_max_step(&q.MinMaxStepData8)
+                        .or_else(|| min_max_step(&q.MinMaxStepData16))
+                        .or_else(|| min_max_step(&q.MinMaxStepData32))
+                        .or_else(|| min_max_step(&q.MinMaxStepData64))
+                    {
+                        new.minimum = Some(mms[0]);
+                        new.maximum = Some(mms[1]);
+                        new.step = Some(mms[2]);
+                    }
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::CheckBox => {
+                if let Ok((_, q)) = uefi_parser::ifr_check_box(data) {
+                    let mut new = new_question(
+                        QuestionKind::CheckBox,
+                        current_form_id,
+                        q.PromptStringId,
+                        q.HelpStringId,
+                        q.QuestionId,
+                        q.VarStoreId,
+                        q.VarStoreInfo,
+                        q.QuestionFlags,
+                        operation_offset,
+                    );
+                    new.flags = q.Flags;
+                    if bit_var_store {
+                        set_bit_width(&mut new, 1);
+                    } else {
+                        new.width = 1;
+                    }
+                    // Default values of a CheckBox are stored in its flags
+                    new.defaults.push(QuestionDefault {
+                        default_id: 0,
+                        value: (q.Flags & uefi_parser::IfrCheckBoxDefaultFlags::Default as u8 != 0)
+                            as u64,
+                    });
+                    new.defaults.push(QuestionDefault {
+                        default_id: 1,
+                        value: (q.Flags & uefi_parser::IfrCheckBoxDefaultFlags::MfgDefault as u8
+                            != 0) as u64,
+                    });
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::Password => {
+                if let Ok((_, q)) = uefi_parser::ifr_password(data) {
+                    let mut new = new_question(
+                        QuestionKind::Password,
+                        current_form_id,
+                        q.PromptStringId,
+                        q.HelpStringId,
+                        q.QuestionId,
+                        q.VarStoreId,
+                        q.VarStoreInfo,
+                        q.QuestionFlags,
+                        operation_offset,
+                    );
+                    new.width = q.MaxSize as usize * 2;
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::Action => {
+                if let Ok((_, q)) = uefi_parser::ifr_action(data) {
+                    let mut new = new_question(
+                        QuestionKind::Action,
+                        current_form_id,
+                        q.PromptStringId,
+                        q.HelpStringId,
+                        q.QuestionId,
+                        q.VarStoreId,
+                        q.VarStoreInfo,
+                        q.QuestionFlags,
+                        operation_offset,
+                    );
+                    new.width = 2;
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::Ref => {
+                if let Ok((_, q)) = uefi_parser::ifr_ref(data) {
+                    let mut new = new_question(
+                        QuestionKind::Ref,
+                        current_form_id,
+                        q.PromptStringId,
+                        q.HelpStringId,
+                        q.QuestionId,
+                        q.VarStoreId,
+                        q.VarStoreInfo,
+                        q.QuestionFlags,
+                        operation_offset,
+                    );
+                    new.ref_target = Some(RefTarget {
+                        form_id: q.FormId,
+                        question_id: q.RefQuestionId,
+                        form_set_guid: q.FormSetGuid,
+                        device_path_id: q.DevicePathId,
+                    });
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::Date => {
+                if let Ok((_, q)) = uefi_parser::ifr_date(data) {
+                    let mut new = new_question(
+                        QuestionKind::Date,
+                        current_form_id,
+                        q.PromptStringId,
+                        q.HelpStringId,
+                        q.QuestionId,
+                        q.VarStoreId,
+                        q.VarStoreInfo,
+                        q.QuestionFlags,
+                        operation_offset,
+                    );
+                    new.flags = q.Flags;
+                    new.width = 4;
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::Time => {
+                if let Ok((_, q)) = uefi_parser::ifr_time(data) {
+                    let mut new = new_question(
+                        QuestionKind::Time,
+                        current_form_id,
+                        q.PromptStringId,
+                        q.HelpStringId,
+                        q.QuestionId,
+                        q.VarStoreId,
+                        q.VarStoreInfo,
+                        q.QuestionFlags,
+                        operation_offset,
+                    );
+                    new.flags = q.Flags;
+                    new.width = 3;
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::String => {
+                if let Ok((_, q)) = uefi_parser::ifr_string(data) {
+                    let mut new = new_question(
+                        QuestionKind::String,
+                        current_form_id,
+                        q.PromptStringId,
+                        q.HelpStringId,
+                        q.QuestionId,
+                        q.VarStoreId,
+                        q.VarStoreInfo,
+                        q.QuestionFlags,
+                        operation_offset,
+                    );
+                    new.flags = q.Flags;
+                    new.width = q.MaxSize as usize * 2;
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::OrderedList => {
+                if let Ok((_, q)) = uefi_parser::ifr_ordered_list(data) {
+                    let mut new = new_question(
+                        QuestionKind::OrderedList,
+                        current_form_id,
+                        q.PromptStringId,
+                        q.HelpStringId,
+                        q.QuestionId,
+                        q.VarStoreId,
+                        q.VarStoreInfo,
+                        q.QuestionFlags,
+                        operation_offset,
+                    );
+                    new.flags = q.Flags;
+                    // Corrected once the type of the options is known
+                    new.width = q.MaxContainers as usize;
+                    question = Some(new);
+                }
+            }
+            IfrOpcode::SuppressIf | IfrOpcode::GrayOutIf | IfrOpcode::DisableIf => {
+                let kind = match operation.OpCode {
+                    IfrOpcode::SuppressIf => ConditionKind::SuppressIf,
+                    IfrOpcode::GrayOutIf => ConditionKind::GrayOutIf,
+                    _ => ConditionKind::DisableIf,
+                };
+                if let Some(fs) = form_sets.last_mut() {
+                    fs.conditions.push(Condition {
+                        kind,
+                        expression: Vec::new(),
+                        offset: operation_offset,
+                    });
+                    if operation.ScopeStart {
+                        open_conditions.push(fs.conditions.len() - 1);
+                        expression_target = Some(fs.conditions.len() - 1);
+                        scope = Scope::Condition;
+                    }
+                }
+            }
+            IfrOpcode::Guid => {
+                if let Ok((_, guid)) = uefi_parser::ifr_guid(data) {
+                    if guid.Guid == uefi_parser::EDKII_IFR_BIT_VARSTORE_GUID {
+                        scope = Scope::BitVarStore;
+                    }
+                }
+            }
+            IfrOpcode::OneOfOption => {
+                if let Ok((_, opt)) = uefi_parser::ifr_one_of_option(data) {
+                    if let Some(q) = current_question(&scopes, &mut form_sets) {
+                        if let Some(value) = type_value_as_u64(&opt.Value) {
+                            if q.kind == QuestionKind::OrderedList && q.options.is_empty() {
+                                q.width *= type_value_width(&opt.Value);
+                            }
+                            if opt.Flags & uefi_parser::IfrOneOfOptionDefaultFlags::Default as u8 != 0
+                            {
+                                q.defaults.push(QuestionDefault {
+                                    default_id: 0,
+                                    value,
+                                });
+                            }
+                            if opt.Flags & uefi_parser::IfrOneOfOptionDefaultFlags::MfgDefault as u8
+                                != 0
+                            {
+                                q.defaults.push(QuestionDefault {
+                                    default_id: 1,
+                                    value,
+                                });
+                            }
+                            q.options.push(QuestionOption {
+                                string_id: opt.OptionStringId,
+                                value,
+                                flags: opt.Flags,
+                            });
+                        }
+                    }
+                }
+            }
+            IfrOpcode::Default => {
+                if let Ok((_, def)) = uefi_parser::ifr_default(data) {
+                    if let Some(q) = current_question(&scopes, &mut form_sets) {
+                        if let Some(value) = type_value_as_u64(&def.Value) {
+                            // Explicit defaults override the ones set by flags
+                            q.defaults.retain(|d| d.default_id != def.DefaultId);
+                            q.defaults.push(QuestionDefault {
+                                default_id: def.DefaultId,
+                                value,
+                            });
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(mut q) = question {
+            q.conditions = open_conditions.clone();
+            if let Some(fs) = form_sets.last_mut() {
+                fs.questions.push(q);
+                scope = Scope::Question(fs.questions.len() - 1);
+            }
+        }
+
+        if operation.ScopeStart {
+            scopes.push(scope);
+        }
+    }
+
+    form_sets
+}
+
+// Converts an expression operation into the model, returns None for operations that can't be a part of an expression
+fn expression_op(opcode: IfrOpcode, data: &[u8]) -> Option<ExpressionOp> {
+    let op = match opcode {
+        IfrOpcode::EqIdVal => match uefi_parser::ifr_eq_id_val(data) {
+            Ok((_, x)) => ExpressionOp::EqIdVal {
+                question_id: x.QuestionId,
+                value: x.Value as u64,
+            },
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::EqIdId => match uefi_parser::ifr_eq_id_id(data) {
+            Ok((_, x)) => ExpressionOp::EqIdId {
+                question_id: x.QuestionId,
+                other_question_id: x.OtherQuestionId,
+            },
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::EqIdValList => match uefi_parser::ifr_eq_id_val_list(data) {
+            Ok((_, x)) => ExpressionOp::EqIdValList {
+                question_id: x.QuestionId,
+                values: x.Values.iter().map(|v| *v as u64).collect(),
+            },
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::QuestionRef1 => match uefi_parser::ifr_question_ref_1(data) {
+            Ok((_, x)) => ExpressionOp::QuestionRef(x.QuestionId),
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::QuestionRef2 => ExpressionOp::QuestionRefFromStack,
+        // Questions from other form sets can't be resolved
+        IfrOpcode::QuestionRef3 => match uefi_parser::ifr_question_ref_3(data) {
+            Ok((_, ref x)) if x.DevicePathId.is_none() && x.QuestionGuid.is_none() => {
+                ExpressionOp::QuestionRefFromStack
+            }
+            _ => ExpressionOp::Other {
+                opcode: opcode.into(),
+                operands: 1,
+            },
+        },
+        IfrOpcode::Get => match uefi_parser::ifr_get(data) {
+            Ok((_, x)) => ExpressionOp::Get {
+                var_store_id: x.VarStoreId,
+                var_offset: x.VarStoreInfo,
+                width: match x.VarStoreType {
+                    0x01 => 2,
+                    0x02 => 4,
+                    0x03 => 8,
+                    _ => 1,
+                },
+            },
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::Uint8 => match uefi_parser::ifr_uint8(data) {
+            Ok((_, x)) => ExpressionOp::Uint(x.Value as u64),
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::Uint16 => match uefi_parser::ifr_uint16(data) {
+            Ok((_, x)) => ExpressionOp::Uint(x.Value as u64),
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::Uint32 => match uefi_parser::ifr_uint32(data) {
+            Ok((_, x)) => ExpressionOp::Uint(x.Value as u64),
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::Uint64 => match uefi_parser::ifr_uint64(data) {
+            Ok((_, x)) => ExpressionOp::Uint(x.Value),
+            Err(_) => ExpressionOp::Undefined,
+        },
+        IfrOpcode::Zero => ExpressionOp::Uint(0),
+        IfrOpcode::One => ExpressionOp::Uint(1),
+        IfrOpcode::Ones => ExpressionOp::Uint(u64::MAX),
+        IfrOpcode::True => ExpressionOp::Boolean(true),
+        IfrOpcode::False => ExpressionOp::Boolean(false),
+        IfrOpcode::Undefined => ExpressionOp::Undefined,
+        IfrOpcode::Not => ExpressionOp::Not,
+        IfrOpcode::And => ExpressionOp::And,
+        IfrOpcode::Or => ExpressionOp::Or,
+        IfrOpcode::Equal => ExpressionOp::Equal,
+        IfrOpcode::NotEqual => ExpressionOp::NotEqual,
+        IfrOpcode::GreaterThan => ExpressionOp::GreaterThan,
+        IfrOpcode::GreaterEqual => ExpressionOp::GreaterEqual,
+        IfrOpcode::LessThan => ExpressionOp::LessThan,
+        IfrOpcode::LessEqual => ExpressionOp::LessEqual,
+        IfrOpcode::BitwiseNot => ExpressionOp::BitwiseNot,
+        IfrOpcode::BitwiseAnd => ExpressionOp::BitwiseAnd,
+        IfrOpcode::BitwiseOr => ExpressionOp::BitwiseOr,
+        IfrOpcode::ShiftLeft => ExpressionOp::ShiftLeft,
+        IfrOpcode::ShiftRight => ExpressionOp::ShiftRight,
+        IfrOpcode::Add => ExpressionOp::Add,
+        IfrOpcode::Substract => ExpressionOp::Subtract,
+        IfrOpcode::Multiply => ExpressionOp::Multiply,
+        IfrOpcode::Divide => ExpressionOp::Divide,
+        IfrOpcode::Modulo => ExpressionOp::Modulo,
+        IfrOpcode::ToBoolean => ExpressionOp::ToBoolean,
+        IfrOpcode::ToUint => ExpressionOp::ToUint,
+        IfrOpcode::Conditional => ExpressionOp::Conditional,
+        IfrOpcode::Dup => ExpressionOp::Dup,
+        // Operations without a value that can be evaluated
+        IfrOpcode::Version
+        | IfrOpcode::RuleRef
+        | IfrOpcode::StringRef1
+        | IfrOpcode::This
+        | IfrOpcode::Security => ExpressionOp::Other {
+            opcode: opcode.into(),
+            operands: 0,
+        },
+        IfrOpcode::Length
+        | IfrOpcode::StringRef2
+        | IfrOpcode::ToString
+        | IfrOpcode::ToUpper
+        | IfrOpcode::ToLower
+        | IfrOpcode::Set
+        | IfrOpcode::Map => ExpressionOp::Other {
+            opcode: opcode.into(),
+            operands: 1,
+        },
+        IfrOpcode::Catenate | IfrOpcode::Match | IfrOpcode::Match2 => ExpressionOp::Other {
+            opcode: opcode.into(),
+            operands: 2,
+        },
+        IfrOpcode::Mid | IfrOpcode::Find | IfrOpcode::Token | IfrOpcode::Span => ExpressionOp::Other {
+            opcode: opcode.into(),
+            operands: 3,
+        },
+        _ => return None,
+    };
+    Some(op)
+}
+
+fn set_bit_width(question: &mut Question, bit_width: u8) {
+    question.bit_width = Some(bit_width);
+    question.width = (question.var_offset as usize % 8 + bit_width as usize).div_ceil(8);
+}
+
+// Innermost question whose scope is currently open
+fn current_question<'a>(scopes: &[Scope], form_sets: &'a mut [FormSet]) -> Option<&'a mut Question> {
+    let index = scopes.iter().rev().find_map(|s| match *s {
+        Scope::Question(i) => Some(i),
+        _ => None,
+    })?;
+    form_sets.last_mut()?.questions.get_mut(index)
+}
+
+//
+// Models built by hand for unit tests
+//
+#[cfg(test)]
+pub mod test_models {
+    use super::*;
+
+    pub fn guid(data1: u32) -> Guid {
+        Guid {
+            data1,
+            data2: 0,
+            data3: 0,
+            data4: [0; 8],
+        }
+    }
+
+    pub fn form_set(var_stores: Vec<VarStore>, questions: Vec<Question>) -> FormSet {
+        FormSet {
+            guid: guid(0xF0),
+            title_string_id: 1,
+            help_string_id: 0,
+            offset: 0,
+            var_stores,
+            forms: vec![Form {
+                form_id: 1,
+                title_string_id: 2,
+                offset: 0,
+            }],
+            questions,
+            statements: Vec::new(),
+            conditions: Vec::new(),
+        }
+    }
+
+    pub fn buffer(var_store_id: u16, name: &str, size: u16) -> VarStore {
+        VarStore {
+            kind: VarStoreKind::Buffer,
+            var_store_id,
+            guid: guid(0x10),
+            name: name.to_string(),
+            size: Some(size),
+            offset: 0,
+        }
+    }
+
+    // Question in form 1 with the QuestionId as its prompt StringId
+    pub fn question(kind: QuestionKind, question_id: u16, var_store_id: u16, var_offset: u16, width: usize) -> Question {
+        Question {
+            kind,
+            form_id: 1,
+            prompt_string_id: question_id,
+            help_string_id: 0,
+            question_id,
+            var_store_id,
+            var_offset,
+            bit_width: None,
+            question_flags: 0,
+            flags: 0,
+            width,
+            minimum: None,
+            maximum: None,
+            step: None,
+            options: Vec::new(),
+            defaults: Vec::new(),
+            conditions: Vec::new(),
+            ref_target: None,
+            offset: 0,
+        }
+    }
+
+    pub fn form(form_id: u16, title_string_id: u16) -> Form {
+        Form {
+            form_id,
+            title_string_id,
+            offset: 0,
+        }
+    }
+
+    // Ref in a given form to a form of the same form set, its QuestionId is also its prompt StringId
+    pub fn reference(question_id: u16, form_id: u16, target_form_id: u16) -> Question {
+        let mut question = question(QuestionKind::Ref, question_id, 0, 0, 0);
+        question.form_id = form_id;
+        question.ref_target = Some(RefTarget {
+            form_id: Some(target_form_id),
+            question_id: None,
+            form_set_guid: None,
+            device_path_id: None,
+        });
+        question
+    }
+
+    pub fn bit_field(mut question: Question, bit_width: u8) -> Question {
+        set_bit_width(&mut question, bit_width);
+        question
+    }
+
+    pub fn option(string_id: u16, value: u64) -> QuestionOption {
+        QuestionOption {
+            string_id,
+            value,
+            flags: 0,
+        }
+    }
+
+    pub fn default(default_id: u16, value: u64) -> QuestionDefault {
+        QuestionDefault { default_id, value }
+    }
+}
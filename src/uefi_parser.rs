@@ -1,15 +1,12 @@
 #![allow(non_snake_case)]
-#![allow(deprecated)]
 
-extern crate nom;
-
-use nom::{le_u16, le_u32, le_u64, le_u8, rest, IResult};
+use byte_reader::{IResult, ParseError, ParseErrorKind, Reader};
 use std::fmt;
 
 //
 // Common data types
 //
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub struct Guid {
     pub data1: u32,
     pub data2: u16,
@@ -18,19 +15,18 @@ pub struct Guid {
 }
 
 pub fn guid(input: &[u8]) -> IResult<&[u8], Guid> {
-    do_parse!(
-        input,
-        d1: le_u32
-            >> d2: le_u16
-            >> d3: le_u16
-            >> d4: count_fixed!(u8, le_u8, 8)
-            >> (Guid {
-                data1: d1,
-                data2: d2,
-                data3: d3,
-                data4: d4,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let d1 = reader.u32("Data1")?;
+    let d2 = reader.u16("Data2")?;
+    let d3 = reader.u16("Data3")?;
+    let mut d4 = [0u8; 8];
+    d4.copy_from_slice(reader.bytes(8, "Data4")?);
+    reader.finish(Guid {
+        data1: d1,
+        data2: d2,
+        data3: d3,
+        data4: d4,
+    })
 }
 
 impl fmt::Display for Guid {
@@ -96,59 +92,79 @@ impl From<u8> for HiiPackageType {
     }
 }
 
-pub fn hii_package(input: &[u8]) -> IResult<&[u8], HiiPackage> {
-    do_parse!(
-        input,
-        len_raw: le_u32
-            >> len: verify!(value!(len_raw & 0xFFFFFF), |val: u32| val >= 4)
-            >> typ: value!(((len_raw & 0xFF000000) >> 24) as u8)
-            >> data: cond_with_error!(len > 4, take!(len - 4))
-            >> (HiiPackage {
-                Length: len,
-                Type: HiiPackageType::from(typ),
-                Data: data,
-            })
-    )
+impl From<HiiPackageType> for u8 {
+    fn from(val: HiiPackageType) -> Self {
+        match val {
+            HiiPackageType::Guid => 0x01,
+            HiiPackageType::Form => 0x02,
+            HiiPackageType::KeyboardLayout => 0x03,
+            HiiPackageType::Strings => 0x04,
+            HiiPackageType::Fonts => 0x05,
+            HiiPackageType::Images => 0x06,
+            HiiPackageType::SimpleFonts => 0x07,
+            HiiPackageType::DevicePath => 0x08,
+            HiiPackageType::End => 0xDF,
+            HiiPackageType::System(n) => n,
+            HiiPackageType::Unknown(n) => n,
+        }
+    }
+}
+
+pub fn hii_package(input: &[u8]) -> IResult<&[u8], HiiPackage<'_>> {
+    let mut reader = Reader::new(input);
+    let len_raw = reader.u32("Length")?;
+    let len = reader.verify(0, "Length", len_raw & 0xFFFFFF, len_raw & 0xFFFFFF >= 4)?;
+    let typ = ((len_raw & 0xFF000000) >> 24) as u8;
+    let data = reader.cond(len > 4, |r| r.bytes(len as usize - 4, "Data"))?;
+    reader.finish(HiiPackage {
+        Length: len,
+        Type: HiiPackageType::from(typ),
+        Data: data,
+    })
 }
 
 pub fn hii_string_package_candidate(input: &[u8]) -> IResult<&[u8], &[u8]> {
-    do_parse!(
-        input,
-        len: peek!(hii_string_package_candidate_helper) >> dat: take!(len) >> (dat)
-    )
+    let (_, len) = hii_string_package_candidate_helper(input)?;
+    let mut reader = Reader::new(input);
+    let dat = reader.bytes(len, "Package")?;
+    reader.finish(dat)
 }
 
 fn hii_string_package_candidate_helper(input: &[u8]) -> IResult<&[u8], usize> {
-    do_parse!(
-        input,
-        len_raw: le_u32 >>
-        len: verify!(value!(len_raw & 0x00FFFFFF), |val: u32| val > 0x04 + 0x34) >> // Total length of the package is sane
-        verify!(value!(len_raw & 0xFF000000), |val: u32| val == 0x04000000) >> // Package type is 0x04 
-        verify!(le_u32, |val: u32| val == 0x34) >> // Header size is 0x34
-        take!(len - 0x04 - 0x04 - 0x02) >> // Skip the rest up to the last 2 bytes
-        verify!(le_u16, |val: u16| val == 0) >> // Last 2 bytes must be zeroes
-        ( len as usize )
-    )
+    let mut reader = Reader::new(input);
+    let len_raw = reader.u32("Length")?;
+    let len = len_raw & 0x00FFFFFF;
+    reader.verify(0, "Length", len, len > 0x04 + 0x34)?; // Total length of the package is sane
+    reader.verify(0, "Type", len_raw, len_raw & 0xFF000000 == 0x04000000)?; // Package type is 0x04
+    let hs = reader.u32("HdrSize")?;
+    reader.verify(4, "HdrSize", hs, hs == 0x34)?; // Header size is 0x34
+    reader.bytes(len as usize - 0x04 - 0x04 - 0x02, "Data")?; // Skip the rest up to the last 2 bytes
+    let start = reader.position();
+    let last = reader.u16("End")?;
+    reader.verify(start, "End", last, last == 0)?; // Last 2 bytes must be zeroes
+    reader.finish(len as usize)
 }
 
 pub fn hii_form_package_candidate(input: &[u8]) -> IResult<&[u8], &[u8]> {
-    do_parse!(
-        input,
-        len: peek!(hii_form_package_candidate_helper) >> dat: take!(len) >> (dat)
-    )
+    let (_, len) = hii_form_package_candidate_helper(input)?;
+    let mut reader = Reader::new(input);
+    let dat = reader.bytes(len, "Package")?;
+    reader.finish(dat)
 }
 
 fn hii_form_package_candidate_helper(input: &[u8]) -> IResult<&[u8], usize> {
-    do_parse!(
-        input,
-        len_raw: le_u32 >>
-        len: verify!(value!(len_raw & 0x00FFFFFF), |val: u32| val > 0x4) >> // Total length of the package is sane
-        verify!(value!(len_raw & 0xFF000000), |val: u32| val == 0x02000000) >> // Package type is 0x02 
-        verify!(le_u8, |val: u8| val == 0x0E) >> // Must start with IfrOpCode::FormSet
-        take!(len - 0x04 - 0x01 - 0x02) >> // Skip the rest up to the last 2 bytes
-        verify!(le_u16, |val: u16| val == 0x0229) >> // Last 2 bytes must be IfrOpCode::End
-        ( len as usize )
-    )
+    let mut reader = Reader::new(input);
+    let len_raw = reader.u32("Length")?;
+    let len = len_raw & 0x00FFFFFF;
+    reader.verify(0, "Length", len, len >= 0x04 + 0x01 + 0x02)?; // Total length of the package is sane
+    reader.verify(0, "Type", len_raw, len_raw & 0xFF000000 == 0x02000000)?; // Package type is 0x02
+    let opcode = reader.u8("OpCode")?;
+    reader.verify(4, "OpCode", opcode, opcode == 0x0E)?; // Must start with IfrOpCode::FormSet
+    reader.bytes(len as usize - 0x04 - 0x01 - 0x02, "Data")?; // Skip the rest up to the last 2 bytes
+    let start = reader.position();
+    let last = reader.u16("End")?;
+    reader.verify(start, "End", last, last == 0x0229)?; // Last 2 bytes must be IfrOpCode::End
+    reader.finish(len as usize)
 }
 
 //
@@ -164,26 +180,27 @@ pub struct HiiStringPackage<'a> {
     pub Data: &'a [u8],
 }
 
-pub fn hii_string_package(input: &[u8]) -> IResult<&[u8], HiiStringPackage> {
-    do_parse!(
-        input,
-        hs  : verify!(le_u32, |val: u32| val == 0x34) >>
-        sio : le_u32 >>
-        lw  : count_fixed!(u16, le_u16, 16) >>
-        ln  : le_u16 >>
-        lg  : take!(hs - 0x2F) >>
-              take!(1) >> // Skip terminating zero 
-        d   : rest >>
-        ( HiiStringPackage {
-            HdrSize : hs,
-            StringInfoOffset : sio,
-            LanguageWindow : lw,
-            LanguageName : ln,
-            Language : String::from_utf8_lossy(lg).to_string(),
-            Data : d,
-            }
-        )
-    )
+pub fn hii_string_package(input: &[u8]) -> IResult<&[u8], HiiStringPackage<'_>> {
+    let mut reader = Reader::new(input);
+    let hs = reader.u32("HdrSize")?;
+    reader.verify(0, "HdrSize", hs, hs >= 0x2F)?; // 0x34 in packages made by EDK2
+    let sio = reader.u32("StringInfoOffset")?;
+    let mut lw = [0u16; 16];
+    for c in lw.iter_mut() {
+        *c = reader.u16("LanguageWindow")?;
+    }
+    let ln = reader.u16("LanguageName")?;
+    let lg = reader.bytes(hs as usize - 0x2F, "Language")?;
+    reader.u8("Language")?; // Skip terminating zero
+    let d = reader.rest();
+    reader.finish(HiiStringPackage {
+        HdrSize: hs,
+        StringInfoOffset: sio,
+        LanguageWindow: lw,
+        LanguageName: ln,
+        Language: String::from_utf8_lossy(lg.split(|&b| b == 0).next().unwrap_or(lg)).to_string(),
+        Data: d,
+    })
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -229,166 +246,214 @@ impl From<u8> for HiiSibtType {
     }
 }
 
+impl From<HiiSibtType> for u8 {
+    fn from(val: HiiSibtType) -> Self {
+        match val {
+            HiiSibtType::End => 0x00,
+            HiiSibtType::StringScsu => 0x10,
+            HiiSibtType::StringScsuFont => 0x11,
+            HiiSibtType::StringsScsu => 0x12,
+            HiiSibtType::StringsScsuFont => 0x13,
+            HiiSibtType::StringUcs2 => 0x14,
+            HiiSibtType::StringUcs2Font => 0x15,
+            HiiSibtType::StringsUcs2 => 0x16,
+            HiiSibtType::StringsUcs2Font => 0x17,
+            HiiSibtType::Duplicate => 0x20,
+            HiiSibtType::Skip2 => 0x21,
+            HiiSibtType::Skip1 => 0x22,
+            HiiSibtType::Ext1 => 0x30,
+            HiiSibtType::Ext2 => 0x31,
+            HiiSibtType::Ext4 => 0x32,
+            HiiSibtType::Unknown(n) => n,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct HiiSibtBlock<'a> {
     pub Type: HiiSibtType,
     pub Data: Option<&'a [u8]>,
 }
 
-pub fn hii_sibt_blocks(input: &[u8]) -> IResult<&[u8], Vec<HiiSibtBlock>> {
-    do_parse!(input, v: many1!(complete!(hii_sibt_block)) >> (v))
-}
-
-pub fn hii_sibt_block(input: &[u8]) -> IResult<&[u8], HiiSibtBlock> {
-    do_parse!(
-        input,
-        typ: peek!(le_u8)
-            >> len: switch!(le_u8,
-                0x00 => value!(0) | // End block has no data
-                0x10 => peek!(do_parse!(s: scsu_string >> (s.len()))) | // Just SCSU string
-                0x11 => peek!(do_parse!(take!(1) >> s: scsu_string >> (s.len()))) | // One u8 and SCSU string
-                0x12 => peek!(do_parse!(cnt: le_u16 >>
-                                        v: count!(do_parse!(s: scsu_string >> (s.len())), cnt as usize) >>
-                                        ( v.iter().sum() ))) | // One u16 as count, and a number of SCSU strings
-                0x12 => peek!(do_parse!(take!(1) >>
-                                        cnt: le_u16 >>
-                                        v: count!(do_parse!(s: scsu_string >> (s.len())), cnt as usize) >>
-                                        ( v.iter().sum() ))) | // One u8, one u16 as count and a number of SCSU strings
-                0x14 => peek!(do_parse!(s: ucs2_string >> (s.len() * 2))) | // Just UCS2 string
-                0x15 => peek!(do_parse!(take!(1) >> s: ucs2_string >> (s.len()*2))) | // One u8 and UCS2 string
-                0x16 => peek!(do_parse!(cnt: le_u16 >>
-                                        v: count!(do_parse!(s: ucs2_string >> (s.len()*2)), cnt as usize) >>
-                                        ( v.iter().sum() ))) | // One u16 as count, and a number of UCS2 strings
-                0x17 => peek!(do_parse!(take!(1) >>
-                                        cnt: le_u16 >>
-                                        v: count!(do_parse!(s: ucs2_string >> (s.len()*2)), cnt as usize) >>
-                                        ( v.iter().sum() ))) | // One u8, one u16 as count, and a number of UCS2 strings
-                0x20 => value!(2) | // Duplicate block has one u16
-                0x21 => value!(2) | // Skip2 block has one u16
-                0x22 => value!(1) | // Skip1 block has one u8
-                0x30 => peek!(do_parse!(le_u8 >>
-                                  l: le_u8 >>
-                                  take!(l as usize) >>
-                                  ( l as usize ))) | // Obtain length from Ext1 block
-                0x31 => peek!(do_parse!(le_u8 >>
-                                  l: le_u16 >>
-                                  take!(l as usize) >>
-                                  ( l as usize ))) | // Obtain length from Ext2 block
-                0x32 => peek!(do_parse!(le_u8 >>
-                                  l: le_u32 >>
-                                  take!(l as usize) >>
-                                  ( l as usize )))  // Obtain length from Ext4 block
-            )
-            >> dat: cond_with_error!(len > 0, take!(len))
-            >> (HiiSibtBlock {
-                Type: HiiSibtType::from(typ),
-                Data: dat,
-            })
-    )
+pub fn hii_sibt_blocks(input: &[u8]) -> IResult<&[u8], Vec<HiiSibtBlock<'_>>> {
+    let mut reader = Reader::new(input);
+    let v = reader.many1(hii_sibt_block)?;
+    reader.finish(v)
 }
 
-named!(
-    ucs2_string<Vec<u16>>,
-    map!(
-        many_till!(le_u16, verify!(le_u16, |n: u16| n == 0)),
-        |(mut v, n)| {
-            v.push(n);
-            v
+pub fn hii_sibt_block(input: &[u8]) -> IResult<&[u8], HiiSibtBlock<'_>> {
+    let mut reader = Reader::new(input);
+    let typ = reader.u8("BlockType")?;
+    // Data length is obtained by reading the block ahead without consuming anything
+    let mut peek = Reader::new(input);
+    peek.u8("BlockType")?;
+    let len = match typ {
+        0x00 => 0, // End block has no data
+        0x10 => {
+            // Just SCSU string
+            peek.parse(scsu_string)?.len() + 1
+        }
+        0x11 => {
+            // One u8 and SCSU string
+            peek.u8("FontIdentifier")?;
+            1 + peek.parse(scsu_string)?.len() + 1
+        }
+        0x12 => {
+            // One u16 as count, and a number of SCSU strings
+            let cnt = peek.u16("StringCount")?;
+            let v = peek.count(cnt as usize, |r| r.parse(scsu_string))?;
+            2 + v.iter().map(|s| s.len() + 1).sum::<usize>()
+        }
+        0x13 => {
+            // One u8, one u16 as count, and a number of SCSU strings
+            peek.u8("FontIdentifier")?;
+            let cnt = peek.u16("StringCount")?;
+            let v = peek.count(cnt as usize, |r| r.parse(scsu_string))?;
+            3 + v.iter().map(|s| s.len() + 1).sum::<usize>()
+        }
+        0x14 => {
+            // Just UCS2 string
+            (peek.parse(ucs2_string)?.len() + 1) * 2
         }
-    )
-);
-
-named!(
-    scsu_string<Vec<u8>>,
-    map!(many_till!(le_u8, verify!(le_u8, |n: u8| n == 0)), |(
-        mut v,
-        n,
-    )| {
-        v.push(n);
-        v
+        0x15 => {
+            // One u8 and UCS2 string
+            peek.u8("FontIdentifier")?;
+            1 + (peek.parse(ucs2_string)?.len() + 1) * 2
+        }
+        0x16 => {
+            // One u16 as count, and a number of UCS2 strings
+            let cnt = peek.u16("StringCount")?;
+            let v = peek.count(cnt as usize, |r| r.parse(ucs2_string))?;
+            2 + v.iter().map(|s| (s.len() + 1) * 2).sum::<usize>()
+        }
+        0x17 => {
+            // One u8, one u16 as count, and a number of UCS2 strings
+            peek.u8("FontIdentifier")?;
+            let cnt = peek.u16("StringCount")?;
+            let v = peek.count(cnt as usize, |r| r.parse(ucs2_string))?;
+            3 + v.iter().map(|s| (s.len() + 1) * 2).sum::<usize>()
+        }
+        0x20 => 2, // Duplicate block has one u16
+        0x21 => 2, // Skip2 block has one u16
+        0x22 => 1, // Skip1 block has one u8
+        0x30 => {
+            // Ext1 block has one u8 as length of the whole block
+            peek.u8("BlockType2")?;
+            let l = peek.u8("Length")? as usize;
+            peek.verify(2, "Length", l, l >= 3)?;
+            peek.bytes(l - 3, "Data")?;
+            l - 1
+        }
+        0x31 => {
+            // Ext2 block has one u16 as length of the whole block
+            peek.u8("BlockType2")?;
+            let l = peek.u16("Length")? as usize;
+            peek.verify(2, "Length", l, l >= 4)?;
+            peek.bytes(l - 4, "Data")?;
+            l - 1
+        }
+        0x32 => {
+            // Ext4 block has one u32 as length of the whole block
+            peek.u8("BlockType2")?;
+            let l = peek.u32("Length")? as usize;
+            peek.verify(2, "Length", l, l >= 6)?;
+            peek.bytes(l - 6, "Data")?;
+            l - 1
+        }
+        _ => {
+            return Err(ParseError {
+                offset: 0,
+                field: "BlockType",
+                kind: ParseErrorKind::InvalidValue,
+            })
+        }
+    };
+    let dat = reader.cond(len > 0, |r| r.bytes(len, "Data"))?;
+    reader.finish(HiiSibtBlock {
+        Type: HiiSibtType::from(typ),
+        Data: dat,
     })
-);
+}
+
+// UCS2 string without its terminating zero, which is consumed
+fn ucs2_string(input: &[u8]) -> IResult<&[u8], Vec<u16>> {
+    let mut reader = Reader::new(input);
+    let mut s = Vec::new();
+    loop {
+        let c = reader.u16("String")?;
+        if c == 0 {
+            break;
+        }
+        s.push(c);
+    }
+    reader.finish(s)
+}
+
+// SCSU string without its terminating zero, which is consumed
+fn scsu_string(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    let mut reader = Reader::new(input);
+    let s = reader.zero_terminated("String")?;
+    reader.finish(s)
+}
 
 pub fn sibt_string_scsu(input: &[u8]) -> IResult<&[u8], String> {
-    do_parse!(
-        input,
-        s: scsu_string >> (String::from_utf8_lossy(&s[..s.len() - 1]).to_string())
-    )
+    let mut reader = Reader::new(input);
+    let s = reader.parse(scsu_string)?;
+    reader.finish(String::from_utf8_lossy(s).to_string())
 }
 
 pub fn sibt_string_scsu_font(input: &[u8]) -> IResult<&[u8], String> {
-    do_parse!(
-        input,
-        take!(1) >> s: scsu_string >> (String::from_utf8_lossy(&s[..s.len() - 1]).to_string())
-    )
+    let mut reader = Reader::new(input);
+    reader.u8("FontIdentifier")?;
+    let s = reader.parse(scsu_string)?;
+    reader.finish(String::from_utf8_lossy(s).to_string())
 }
 
 pub fn sibt_strings_scsu(input: &[u8]) -> IResult<&[u8], Vec<String>> {
-    do_parse!(
-        input,
-        cnt: le_u16
-            >> v: count!(
-                do_parse!(
-                    s: scsu_string >> (String::from_utf8_lossy(&s[..s.len() - 1]).to_string())
-                ),
-                cnt as usize
-            )
-            >> (v)
-    )
+    let mut reader = Reader::new(input);
+    let cnt = reader.u16("StringCount")?;
+    let v = reader.count(cnt as usize, |r| {
+        r.parse(scsu_string).map(|s| String::from_utf8_lossy(s).to_string())
+    })?;
+    reader.finish(v)
 }
 
 pub fn sibt_strings_scsu_font(input: &[u8]) -> IResult<&[u8], Vec<String>> {
-    do_parse!(
-        input,
-        take!(1)
-            >> cnt: le_u16
-            >> v: count!(
-                do_parse!(
-                    s: scsu_string >> (String::from_utf8_lossy(&s[..s.len() - 1]).to_string())
-                ),
-                cnt as usize
-            )
-            >> (v)
-    )
+    let mut reader = Reader::new(input);
+    reader.u8("FontIdentifier")?;
+    let cnt = reader.u16("StringCount")?;
+    let v = reader.count(cnt as usize, |r| {
+        r.parse(scsu_string).map(|s| String::from_utf8_lossy(s).to_string())
+    })?;
+    reader.finish(v)
 }
 
 pub fn sibt_string_ucs2(input: &[u8]) -> IResult<&[u8], String> {
-    do_parse!(
-        input,
-        s: ucs2_string >> (String::from_utf16_lossy(&s[..s.len() - 1]))
-    )
+    let mut reader = Reader::new(input);
+    let s = reader.parse(ucs2_string)?;
+    reader.finish(String::from_utf16_lossy(&s))
 }
 
 pub fn sibt_string_ucs2_font(input: &[u8]) -> IResult<&[u8], String> {
-    do_parse!(
-        input,
-        take!(1) >> s: ucs2_string >> (String::from_utf16_lossy(&s[..s.len() - 1]))
-    )
+    let mut reader = Reader::new(input);
+    reader.u8("FontIdentifier")?;
+    let s = reader.parse(ucs2_string)?;
+    reader.finish(String::from_utf16_lossy(&s))
 }
 
 pub fn sibt_strings_ucs2(input: &[u8]) -> IResult<&[u8], Vec<String>> {
-    do_parse!(
-        input,
-        cnt: le_u16
-            >> v: count!(
-                do_parse!(s: ucs2_string >> (String::from_utf16_lossy(&s[..s.len() - 1]))),
-                cnt as usize
-            )
-            >> (v)
-    )
+    let mut reader = Reader::new(input);
+    let cnt = reader.u16("StringCount")?;
+    let v = reader.count(cnt as usize, |r| r.parse(ucs2_string).map(|s| String::from_utf16_lossy(&s)))?;
+    reader.finish(v)
 }
 
 pub fn sibt_strings_ucs2_font(input: &[u8]) -> IResult<&[u8], Vec<String>> {
-    do_parse!(
-        input,
-        take!(1)
-            >> cnt: le_u16
-            >> v: count!(
-                do_parse!(s: ucs2_string >> (String::from_utf16_lossy(&s[..s.len() - 1]))),
-                cnt as usize
-            )
-            >> (v)
-    )
+    let mut reader = Reader::new(input);
+    reader.u8("FontIdentifier")?;
+    let cnt = reader.u16("StringCount")?;
+    let v = reader.count(cnt as usize, |r| r.parse(ucs2_string).map(|s| String::from_utf16_lossy(&s)))?;
+    reader.finish(v)
 }
 
 //
@@ -402,24 +467,250 @@ pub struct IfrOperation<'a> {
     pub Data: Option<&'a [u8]>,
 }
 
-pub fn ifr_operation(input: &[u8]) -> IResult<&[u8], IfrOperation> {
-    do_parse!(
-        input,
-        opcode: le_u8
-            >> len_raw: le_u8
-            >> len: verify!(value!(len_raw & 0x7F), |val: u8| val >= 2)
-            >> data: cond_with_error!(len > 2, take!((len - 2) as usize))
-            >> (IfrOperation {
-                OpCode: IfrOpcode::from(opcode),
-                Length: len,
-                ScopeStart: (len_raw & 0x80) == 0x80,
-                Data: data
-            })
-    )
+pub fn ifr_operation(input: &[u8]) -> IResult<&[u8], IfrOperation<'_>> {
+    let mut reader = Reader::new(input);
+    let opcode = reader.u8("OpCode")?;
+    let len_raw = reader.u8("Length")?;
+    let len = reader.verify(1, "Length", len_raw & 0x7F, len_raw & 0x7F >= 2)?;
+    let data = reader.cond(len > 2, |r| r.bytes((len - 2) as usize, "Data"))?;
+    reader.finish(IfrOperation {
+        OpCode: IfrOpcode::from(opcode),
+        Length: len,
+        ScopeStart: (len_raw & 0x80) == 0x80,
+        Data: data,
+    })
+}
+
+pub fn ifr_operations(input: &[u8]) -> IResult<&[u8], Vec<IfrOperation<'_>>> {
+    let mut reader = Reader::new(input);
+    let v = reader.many1(ifr_operation)?;
+    reader.finish(v)
+}
+
+// Bytes of a form package skipped while resynchronizing after a malformed operation
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct DamagedRegion {
+    pub offset: usize, // Relative to the start of the operations
+    pub length: usize,
+    pub truncated: bool, // The last operation extends past the end of the package
+}
+
+// Operations with their offsets or bytes skipped between them, parts of a parsed form package
+#[derive(Debug, PartialEq, Eq)]
+pub enum IfrRegion<'a, T> {
+    Operations(&'a [T], &'a [usize]),
+    Damaged(&'a DamagedRegion),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct IfrOperations<'a> {
+    pub operations: Vec<IfrOperation<'a>>,
+    pub offsets: Vec<usize>, // Offset of every operation relative to the start of the operations
+    pub damaged: Vec<DamagedRegion>,
 }
 
-pub fn ifr_operations(input: &[u8]) -> IResult<&[u8], Vec<IfrOperation>> {
-    do_parse!(input, v: many1!(complete!(ifr_operation)) >> (v))
+impl<'a> IfrOperations<'a> {
+    // Offsets of all operations in the input file
+    pub fn file_offsets(&self, base_offset: usize) -> Vec<usize> {
+        self.offsets.iter().map(|offset| base_offset + offset).collect()
+    }
+
+    pub fn damaged_length(&self) -> usize {
+        self.damaged.iter().map(|region| region.length).sum()
+    }
+
+    // Runs of operations and the damaged regions between them in the order they appear in the form package,
+    // offsets of the operations are taken from the given list, i.e. their offsets in the input file
+    pub fn regions<'s>(&'s self, offsets: &'s [usize]) -> Vec<IfrRegion<'s, IfrOperation<'a>>> {
+        let mut regions = Vec::new();
+        let mut start = 0;
+        for damaged in &self.damaged {
+            let end = start + self.offsets[start..].iter().take_while(|&&offset| offset < damaged.offset).count();
+            if end > start {
+                regions.push(IfrRegion::Operations(&self.operations[start..end], &offsets[start..end]));
+            }
+            regions.push(IfrRegion::Damaged(damaged));
+            start = end;
+        }
+        if start < self.operations.len() {
+            regions.push(IfrRegion::Operations(&self.operations[start..], &offsets[start..]));
+        }
+        regions
+    }
+}
+
+// A few known operations in a row or the end of the package, a likely start of valid IFR after damaged bytes
+fn ifr_operations_plausible(input: &[u8]) -> bool {
+    let mut rest = input;
+    for _ in 0..3 {
+        if rest.is_empty() {
+            return true;
+        }
+        match ifr_operation(rest) {
+            Ok((r, operation)) if !matches!(operation.OpCode, IfrOpcode::Unknown(_)) => rest = r,
+            _ => return false,
+        }
+    }
+    true
+}
+
+// Operations starting at offset end exactly at the end offset
+fn ifr_operations_reach(input: &[u8], mut offset: usize, end: usize) -> bool {
+    while offset < end {
+        match ifr_operation(&input[offset..]) {
+            Ok((_, operation)) => offset += operation.Length as usize,
+            Err(_) => return false,
+        }
+    }
+    offset == end
+}
+
+// Parses all operations of a form package, an operation with invalid length is skipped up to the next plausible
+// operation boundary and parsing continues, an operation that doesn't fit into the package ends it
+pub fn ifr_operations_resilient(input: &[u8]) -> IfrOperations<'_> {
+    let mut result = IfrOperations {
+        operations: Vec::new(),
+        offsets: Vec::new(),
+        damaged: Vec::new(),
+    };
+    let mut offset = 0;
+    while offset < input.len() {
+        // Padding at the end of the package is not damage
+        if is_padding(&input[offset..]) {
+            break;
+        }
+        if let Ok((_, operation)) = ifr_operation(&input[offset..]) {
+            result.offsets.push(offset);
+            offset += operation.Length as usize;
+            result.operations.push(operation);
+            continue;
+        }
+        let rest = &input[offset..];
+        if rest.len() < 2 || rest[1] & 0x7F >= 2 {
+            result.damaged.push(DamagedRegion {
+                offset,
+                length: rest.len(),
+                truncated: true,
+            });
+            break;
+        }
+        let mut next = (offset + 1..input.len())
+            .find(|&next| ifr_operations_plausible(&input[next..]))
+            .unwrap_or(input.len());
+        // Damaged bytes can look like the start of a long operation that covers valid ones,
+        // prefer a later boundary whose operations end exactly where the long one does
+        while next < input.len() {
+            let end = next + (input[next + 1] & 0x7F) as usize;
+            match (next + 1..end).find(|&p| ifr_operations_plausible(&input[p..]) && ifr_operations_reach(input, p, end)) {
+                Some(p) => next = p,
+                None => break,
+            }
+        }
+        result.damaged.push(DamagedRegion {
+            offset,
+            length: next - offset,
+            truncated: false,
+        });
+        offset = next;
+    }
+    result
+}
+
+//
+// Relaxed package discovery
+//
+pub fn is_padding(input: &[u8]) -> bool {
+    input.iter().all(|&b| b == 0x00) || input.iter().all(|&b| b == 0xFF)
+}
+
+// Known operations up to padding or the end of the input, None if there is anything else
+fn trailing_data(input: &[u8], padding: &'static str, operations: &'static str) -> Option<&'static str> {
+    let mut rest = input;
+    let mut found = false;
+    while !rest.is_empty() && !is_padding(rest) {
+        match ifr_operation(rest) {
+            Ok((r, operation)) if !matches!(operation.OpCode, IfrOpcode::Unknown(_)) => rest = r,
+            _ => return None,
+        }
+        found = true;
+    }
+    Some(if found { operations } else { padding })
+}
+
+// Form package accepted by relaxed discovery: known operations from a FormSet until its scope is closed,
+// followed by padding or more operations, returns the package and how it differs from a strict candidate
+pub fn hii_form_package_candidate_relaxed(input: &[u8]) -> Option<(&[u8], String)> {
+    let (_, package) = hii_package(input).ok()?;
+    if package.Type != HiiPackageType::Form {
+        return None;
+    }
+    let data = package.Data?;
+    let (mut rest, form_set) = ifr_operation(data).ok()?;
+    if form_set.OpCode != IfrOpcode::FormSet || !form_set.ScopeStart {
+        return None;
+    }
+    let mut scope_depth = 1;
+    let mut last = form_set;
+    while scope_depth > 0 {
+        let (r, operation) = ifr_operation(rest).ok()?;
+        if let IfrOpcode::Unknown(_) = operation.OpCode {
+            return None;
+        }
+        if operation.OpCode == IfrOpcode::End {
+            scope_depth -= 1;
+        } else if operation.ScopeStart {
+            scope_depth += 1;
+        }
+        rest = r;
+        last = operation;
+    }
+    let deviation = if rest.is_empty() {
+        format!("last operation is an End of length 0x{:X}", last.Length)
+    } else {
+        String::from(trailing_data(rest, "padding after the final End", "operations after the final End")?)
+    };
+    Some((&input[..package.Length as usize], deviation))
+}
+
+// String package accepted by relaxed discovery: any header size that fits the language name,
+// SIBT blocks with an End block, followed by anything
+pub fn hii_string_package_candidate_relaxed(input: &[u8]) -> Option<(&[u8], String)> {
+    let (_, package) = hii_package(input).ok()?;
+    if package.Type != HiiPackageType::Strings {
+        return None;
+    }
+    let data = package.Data?;
+    let (_, string_package) = hii_string_package(data).ok()?;
+    if string_package.Language.is_empty() || !string_package.Language.bytes().all(|b| b.is_ascii_graphic()) {
+        return None;
+    }
+    let (_, blocks) = hii_sibt_blocks(string_package.Data).ok()?;
+    if !blocks.iter().any(|block| block.Type == HiiSibtType::End) {
+        return None;
+    }
+    let deviation = if string_package.HdrSize != 0x34 {
+        format!("header size 0x{:X} instead of 0x34", string_package.HdrSize)
+    } else {
+        String::from("no zero bytes at the end")
+    };
+    Some((&input[..package.Length as usize], deviation))
+}
+
+// Candidate accepted by strict heuristics, or by relaxed ones if requested
+pub fn form_package_candidate(input: &[u8], relaxed: bool) -> Option<&[u8]> {
+    match hii_form_package_candidate(input) {
+        Ok((_, candidate)) => Some(candidate),
+        Err(_) if relaxed => hii_form_package_candidate_relaxed(input).map(|(candidate, _)| candidate),
+        Err(_) => None,
+    }
+}
+
+pub fn string_package_candidate(input: &[u8], relaxed: bool) -> Option<&[u8]> {
+    match hii_string_package_candidate(input) {
+        Ok((_, candidate)) => Some(candidate),
+        Err(_) if relaxed => hii_string_package_candidate_relaxed(input).map(|(candidate, _)| candidate),
+        Err(_) => None,
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -783,15 +1074,13 @@ pub struct IfrForm {
 }
 
 pub fn ifr_form(input: &[u8]) -> IResult<&[u8], IfrForm> {
-    do_parse!(
-        input,
-        fid: le_u16
-            >> tsid: le_u16
-            >> (IfrForm {
-                FormId: fid,
-                TitleStringId: tsid,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let fid = reader.u16("FormId")?;
+    let tsid = reader.u16("TitleStringId")?;
+    reader.finish(IfrForm {
+        FormId: fid,
+        TitleStringId: tsid,
+    })
 }
 
 //
@@ -805,17 +1094,15 @@ pub struct IfrSubtitle {
 }
 
 pub fn ifr_subtitle(input: &[u8]) -> IResult<&[u8], IfrSubtitle> {
-    do_parse!(
-        input,
-        p: le_u16
-            >> h: le_u16
-            >> f: le_u8
-            >> (IfrSubtitle {
-                PromptStringId: p,
-                HelpStringId: h,
-                Flags: f,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let p = reader.u16("PromptStringId")?;
+    let h = reader.u16("HelpStringId")?;
+    let f = reader.u8("Flags")?;
+    reader.finish(IfrSubtitle {
+        PromptStringId: p,
+        HelpStringId: h,
+        Flags: f,
+    })
 }
 
 //
@@ -829,17 +1116,15 @@ pub struct IfrText {
 }
 
 pub fn ifr_text(input: &[u8]) -> IResult<&[u8], IfrText> {
-    do_parse!(
-        input,
-        p: le_u16
-            >> h: le_u16
-            >> t: le_u16
-            >> (IfrText {
-                PromptStringId: p,
-                HelpStringId: h,
-                TextId: t,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let p = reader.u16("PromptStringId")?;
+    let h = reader.u16("HelpStringId")?;
+    let t = reader.u16("TextId")?;
+    reader.finish(IfrText {
+        PromptStringId: p,
+        HelpStringId: h,
+        TextId: t,
+    })
 }
 
 //
@@ -851,7 +1136,9 @@ pub struct IfrImage {
 }
 
 pub fn ifr_image(input: &[u8]) -> IResult<&[u8], IfrImage> {
-    do_parse!(input, iid: le_u16 >> (IfrImage { ImageId: iid }))
+    let mut reader = Reader::new(input);
+    let iid = reader.u16("ImageId")?;
+    reader.finish(IfrImage { ImageId: iid })
 }
 
 //
@@ -873,41 +1160,39 @@ pub struct IfrOneOf {
 }
 
 pub fn ifr_one_of(input: &[u8]) -> IResult<&[u8], IfrOneOf> {
-    do_parse!(
-        input,
-        psid: le_u16
-            >> hsid: le_u16
-            >> qid: le_u16
-            >> vsid: le_u16
-            >> vsin: le_u16
-            >> qf: le_u8
-            >> f: le_u8
-            >> mms8_0: cond!(f & 0x03 == 0, le_u8)
-            >> mms8_1: cond!(f & 0x03 == 0, le_u8)
-            >> mms8_2: cond!(f & 0x03 == 0, le_u8)
-            >> mms16_0: cond!(f & 0x03 == 1, le_u16)
-            >> mms16_1: cond!(f & 0x03 == 1, le_u16)
-            >> mms16_2: cond!(f & 0x03 == 1, le_u16)
-            >> mms32_0: cond!(f & 0x03 == 2, le_u32)
-            >> mms32_1: cond!(f & 0x03 == 2, le_u32)
-            >> mms32_2: cond!(f & 0x03 == 2, le_u32)
-            >> mms64_0: cond!(f & 0x03 == 3, le_u64)
-            >> mms64_1: cond!(f & 0x03 == 3, le_u64)
-            >> mms64_2: cond!(f & 0x03 == 3, le_u64)
-            >> (IfrOneOf {
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                QuestionId: qid,
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                QuestionFlags: qf,
-                Flags: f,
-                MinMaxStepData8: [mms8_0, mms8_1, mms8_2],
-                MinMaxStepData16: [mms16_0, mms16_1, mms16_2],
-                MinMaxStepData32: [mms32_0, mms32_1, mms32_2],
-                MinMaxStepData64: [mms64_0, mms64_1, mms64_2],
-            })
-    )
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let f = reader.u8("Flags")?;
+    let mms8_0 = reader.cond(f & 0x03 == 0, |r| r.u8("MinValue"))?;
+    let mms8_1 = reader.cond(f & 0x03 == 0, |r| r.u8("MaxValue"))?;
+    let mms8_2 = reader.cond(f & 0x03 == 0, |r| r.u8("Step"))?;
+    let mms16_0 = reader.cond(f & 0x03 == 1, |r| r.u16("MinValue"))?;
+    let mms16_1 = reader.cond(f & 0x03 == 1, |r| r.u16("MaxValue"))?;
+    let mms16_2 = reader.cond(f & 0x03 == 1, |r| r.u16("Step"))?;
+    let mms32_0 = reader.cond(f & 0x03 == 2, |r| r.u32("MinValue"))?;
+    let mms32_1 = reader.cond(f & 0x03 == 2, |r| r.u32("MaxValue"))?;
+    let mms32_2 = reader.cond(f & 0x03 == 2, |r| r.u32("Step"))?;
+    let mms64_0 = reader.cond(f & 0x03 == 3, |r| r.u64("MinValue"))?;
+    let mms64_1 = reader.cond(f & 0x03 == 3, |r| r.u64("MaxValue"))?;
+    let mms64_2 = reader.cond(f & 0x03 == 3, |r| r.u64("Step"))?;
+    reader.finish(IfrOneOf {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        Flags: f,
+        MinMaxStepData8: [mms8_0, mms8_1, mms8_2],
+        MinMaxStepData16: [mms16_0, mms16_1, mms16_2],
+        MinMaxStepData32: [mms32_0, mms32_1, mms32_2],
+        MinMaxStepData64: [mms64_0, mms64_1, mms64_2],
+    })
 }
 
 //
@@ -931,25 +1216,23 @@ pub enum IfrCheckBoxDefaultFlags {
 }
 
 pub fn ifr_check_box(input: &[u8]) -> IResult<&[u8], IfrCheckBox> {
-    do_parse!(
-        input,
-        psid: le_u16
-            >> hsid: le_u16
-            >> qid: le_u16
-            >> vsid: le_u16
-            >> vsin: le_u16
-            >> qf: le_u8
-            >> f: le_u8
-            >> (IfrCheckBox {
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                QuestionId: qid,
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                QuestionFlags: qf,
-                Flags: f,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let f = reader.u8("Flags")?;
+    reader.finish(IfrCheckBox {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        Flags: f,
+    })
 }
 
 //
@@ -971,41 +1254,39 @@ pub struct IfrNumeric {
 }
 
 pub fn ifr_numeric(input: &[u8]) -> IResult<&[u8], IfrNumeric> {
-    do_parse!(
-        input,
-        psid: le_u16
-            >> hsid: le_u16
-            >> qid: le_u16
-            >> vsid: le_u16
-            >> vsin: le_u16
-            >> qf: le_u8
-            >> f: le_u8
-            >> mms8_0: cond!(f & 0x03 == 0, le_u8)
-            >> mms8_1: cond!(f & 0x03 == 0, le_u8)
-            >> mms8_2: cond!(f & 0x03 == 0, le_u8)
-            >> mms16_0: cond!(f & 0x03 == 1, le_u16)
-            >> mms16_1: cond!(f & 0x03 == 1, le_u16)
-            >> mms16_2: cond!(f & 0x03 == 1, le_u16)
-            >> mms32_0: cond!(f & 0x03 == 2, le_u32)
-            >> mms32_1: cond!(f & 0x03 == 2, le_u32)
-            >> mms32_2: cond!(f & 0x03 == 2, le_u32)
-            >> mms64_0: cond!(f & 0x03 == 3, le_u64)
-            >> mms64_1: cond!(f & 0x03 == 3, le_u64)
-            >> mms64_2: cond!(f & 0x03 == 3, le_u64)
-            >> (IfrNumeric {
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                QuestionId: qid,
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                QuestionFlags: qf,
-                Flags: f,
-                MinMaxStepData8: [mms8_0, mms8_1, mms8_2],
-                MinMaxStepData16: [mms16_0, mms16_1, mms16_2],
-                MinMaxStepData32: [mms32_0, mms32_1, mms32_2],
-                MinMaxStepData64: [mms64_0, mms64_1, mms64_2],
-            })
-    )
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let f = reader.u8("Flags")?;
+    let mms8_0 = reader.cond(f & 0x03 == 0, |r| r.u8("MinValue"))?;
+    let mms8_1 = reader.cond(f & 0x03 == 0, |r| r.u8("MaxValue"))?;
+    let mms8_2 = reader.cond(f & 0x03 == 0, |r| r.u8("Step"))?;
+    let mms16_0 = reader.cond(f & 0x03 == 1, |r| r.u16("MinValue"))?;
+    let mms16_1 = reader.cond(f & 0x03 == 1, |r| r.u16("MaxValue"))?;
+    let mms16_2 = reader.cond(f & 0x03 == 1, |r| r.u16("Step"))?;
+    let mms32_0 = reader.cond(f & 0x03 == 2, |r| r.u32("MinValue"))?;
+    let mms32_1 = reader.cond(f & 0x03 == 2, |r| r.u32("MaxValue"))?;
+    let mms32_2 = reader.cond(f & 0x03 == 2, |r| r.u32("Step"))?;
+    let mms64_0 = reader.cond(f & 0x03 == 3, |r| r.u64("MinValue"))?;
+    let mms64_1 = reader.cond(f & 0x03 == 3, |r| r.u64("MaxValue"))?;
+    let mms64_2 = reader.cond(f & 0x03 == 3, |r| r.u64("Step"))?;
+    reader.finish(IfrNumeric {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        Flags: f,
+        MinMaxStepData8: [mms8_0, mms8_1, mms8_2],
+        MinMaxStepData16: [mms16_0, mms16_1, mms16_2],
+        MinMaxStepData32: [mms32_0, mms32_1, mms32_2],
+        MinMaxStepData64: [mms64_0, mms64_1, mms64_2],
+    })
 }
 
 //
@@ -1024,27 +1305,25 @@ pub struct IfrPassword {
 }
 
 pub fn ifr_password(input: &[u8]) -> IResult<&[u8], IfrPassword> {
-    do_parse!(
-        input,
-        psid: le_u16
-            >> hsid: le_u16
-            >> qid: le_u16
-            >> vsid: le_u16
-            >> vsin: le_u16
-            >> qf: le_u8
-            >> ms: le_u16
-            >> xs: le_u16
-            >> (IfrPassword {
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                QuestionId: qid,
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                QuestionFlags: qf,
-                MinSize: ms,
-                MaxSize: xs,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let ms = reader.u16("MinSize")?;
+    let xs = reader.u16("MaxSize")?;
+    reader.finish(IfrPassword {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        MinSize: ms,
+        MaxSize: xs,
+    })
 }
 
 //
@@ -1064,17 +1343,15 @@ pub struct HiiTime {
 }
 
 pub fn hii_time(input: &[u8]) -> IResult<&[u8], HiiTime> {
-    do_parse!(
-        input,
-        h: le_u8
-            >> m: le_u8
-            >> s: le_u8
-            >> (HiiTime {
-                Hour: h,
-                Minute: m,
-                Second: s,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let h = reader.u8("Hour")?;
+    let m = reader.u8("Minute")?;
+    let s = reader.u8("Second")?;
+    reader.finish(HiiTime {
+        Hour: h,
+        Minute: m,
+        Second: s,
+    })
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -1085,17 +1362,15 @@ pub struct HiiDate {
 }
 
 pub fn hii_date(input: &[u8]) -> IResult<&[u8], HiiDate> {
-    do_parse!(
-        input,
-        y: le_u16
-            >> m: le_u8
-            >> d: le_u8
-            >> (HiiDate {
-                Year: y,
-                Month: m,
-                Day: d,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let y = reader.u16("Year")?;
+    let m = reader.u8("Month")?;
+    let d = reader.u8("Day")?;
+    reader.finish(HiiDate {
+        Year: y,
+        Month: m,
+        Day: d,
+    })
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -1107,20 +1382,18 @@ pub struct HiiRef {
 }
 
 pub fn hii_ref(input: &[u8]) -> IResult<&[u8], HiiRef> {
-    do_parse!(
-        input,
-        r: peek!(rest)
-            >> qid: cond_with_error!(r.len() >= 2, le_u16)
-            >> fid: cond_with_error!(r.len() >= 4, le_u16)
-            >> fsg: cond_with_error!(r.len() >= 20, guid)
-            >> dpid: cond_with_error!(r.len() >= 24, le_u16)
-            >> (HiiRef {
-                QuestionId: qid,
-                FormId: fid,
-                FormSetGuid: fsg,
-                DevicePathStringId: dpid,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let r = reader.remaining();
+    let qid = reader.cond(r.len() >= 2, |r| r.u16("QuestionId"))?;
+    let fid = reader.cond(r.len() >= 4, |r| r.u16("FormId"))?;
+    let fsg = reader.cond(r.len() >= 20, |r| r.parse(guid))?;
+    let dpid = reader.cond(r.len() >= 22, |r| r.u16("DevicePathStringId"))?;
+    reader.finish(HiiRef {
+        QuestionId: qid,
+        FormId: fid,
+        FormSetGuid: fsg,
+        DevicePathStringId: dpid,
+    })
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -1178,26 +1451,25 @@ impl fmt::Display for IfrTypeValue {
 }
 
 fn ifr_type_value(input: &[u8]) -> IResult<&[u8], IfrTypeValue> {
-    do_parse!(
-        input,
-        val: switch!(le_u8,
-                0x00 => do_parse!(i: le_u8 >> ( IfrTypeValue::NumSize8(i) )) |
-                0x01 => do_parse!(i: le_u16 >> ( IfrTypeValue::NumSize16(i) )) |
-                0x02 => do_parse!(i: le_u32 >> ( IfrTypeValue::NumSize32(i) )) |
-                0x03 => do_parse!(i: le_u64 >> ( IfrTypeValue::NumSize64(i) )) |
-                0x04 => do_parse!(i: le_u8 >> ( IfrTypeValue::Boolean(i != 0) )) |
-                0x05 => do_parse!(t: hii_time >> ( IfrTypeValue::Time(t) )) |
-                0x06 => do_parse!(d: hii_date >> ( IfrTypeValue::Date(d) )) |
-                0x07 => do_parse!(i: le_u16 >> ( IfrTypeValue::String(i) )) |
-                0x08 => value!(IfrTypeValue::Other) |
-                0x09 => value!(IfrTypeValue::Undefined) |
-                0x0A => do_parse!(i: le_u16 >> ( IfrTypeValue::Action(i) )) |
-                0x0B => do_parse!(b: rest >> ( IfrTypeValue::Buffer(b.to_vec()) )) |
-                0x0C => do_parse!(r: hii_ref >> ( IfrTypeValue::Ref(r) )) |
-                x => value!(IfrTypeValue::Unknown(x))
-        ) >> rest
-            >> (val)
-    )
+    let mut reader = Reader::new(input);
+    let val = match reader.u8("Type")? {
+        0x00 => IfrTypeValue::NumSize8(reader.u8("Value")?),
+        0x01 => IfrTypeValue::NumSize16(reader.u16("Value")?),
+        0x02 => IfrTypeValue::NumSize32(reader.u32("Value")?),
+        0x03 => IfrTypeValue::NumSize64(reader.u64("Value")?),
+        0x04 => IfrTypeValue::Boolean(reader.u8("Value")? != 0),
+        0x05 => IfrTypeValue::Time(reader.parse(hii_time)?),
+        0x06 => IfrTypeValue::Date(reader.parse(hii_date)?),
+        0x07 => IfrTypeValue::String(reader.u16("Value")?),
+        0x08 => IfrTypeValue::Other,
+        0x09 => IfrTypeValue::Undefined,
+        0x0A => IfrTypeValue::Action(reader.u16("Value")?),
+        0x0B => IfrTypeValue::Buffer(reader.rest().to_vec()),
+        0x0C => IfrTypeValue::Ref(reader.parse(hii_ref)?),
+        x => IfrTypeValue::Unknown(x),
+    };
+    reader.rest();
+    reader.finish(val)
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -1208,17 +1480,15 @@ pub struct IfrOneOfOption {
 }
 
 pub fn ifr_one_of_option(input: &[u8]) -> IResult<&[u8], IfrOneOfOption> {
-    do_parse!(
-        input,
-        osid: le_u16
-            >> flgs: le_u8
-            >> val: ifr_type_value
-            >> (IfrOneOfOption {
-                OptionStringId: osid,
-                Flags: flgs,
-                Value: val,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let osid = reader.u16("OptionStringId")?;
+    let flgs = reader.u8("Flags")?;
+    let val = reader.parse(ifr_type_value)?;
+    reader.finish(IfrOneOfOption {
+        OptionStringId: osid,
+        Flags: flgs,
+        Value: val,
+    })
 }
 
 //0x0A => IfrOpcode::SuppressIf
@@ -1239,26 +1509,24 @@ pub struct IfrAction {
 }
 
 pub fn ifr_action(input: &[u8]) -> IResult<&[u8], IfrAction> {
-    do_parse!(
-        input,
-        psid: le_u16
-            >> hsid: le_u16
-            >> qid: le_u16
-            >> vsid: le_u16
-            >> vsin: le_u16
-            >> qf: le_u8
-            >> r: peek!(rest)
-            >> csid: cond_with_error!(r.len() >= 2, le_u16)
-            >> (IfrAction {
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                QuestionId: qid,
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                QuestionFlags: qf,
-                ConfigStringId: csid,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let r = reader.remaining();
+    let csid = reader.cond(r.len() >= 2, |r| r.u16("ConfigStringId"))?;
+    reader.finish(IfrAction {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        ConfigStringId: csid,
+    })
 }
 
 //
@@ -1272,17 +1540,15 @@ pub struct IfrResetButton {
 }
 
 pub fn ifr_reset_button(input: &[u8]) -> IResult<&[u8], IfrResetButton> {
-    do_parse!(
-        input,
-        p: le_u16
-            >> h: le_u16
-            >> d: le_u16
-            >> (IfrResetButton {
-                PromptStringId: p,
-                HelpStringId: h,
-                DefaultId: d,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let p = reader.u16("PromptStringId")?;
+    let h = reader.u16("HelpStringId")?;
+    let d = reader.u16("DefaultId")?;
+    reader.finish(IfrResetButton {
+        PromptStringId: p,
+        HelpStringId: h,
+        DefaultId: d,
+    })
 }
 
 //
@@ -1299,23 +1565,23 @@ pub struct IfrFormSet {
 
 // Making Flags optional here is required because of some files found in the wild
 pub fn ifr_form_set(input: &[u8]) -> IResult<&[u8], IfrFormSet> {
-    do_parse!(
-        input,
-        mg: guid
-            >> tsid: le_u16
-            >> hsid: le_u16
-            >> r: peek!(rest)
-            >> flags: cond_with_error!(!r.is_empty(), le_u8)
-            >> guids_count: cond_with_error!(!r.is_empty(), value!(flags.unwrap() & 0x03))
-            >> guids: cond_with_error!(!r.is_empty(), count!(guid, guids_count.unwrap() as usize))
-            >> (IfrFormSet {
-                Guid: mg,
-                TitleStringId: tsid,
-                HelpStringId: hsid,
-                Flags: flags,
-                ClassGuids: guids,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let mg = reader.parse(guid)?;
+    let tsid = reader.u16("TitleStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let r = reader.remaining();
+    let flags = reader.cond(!r.is_empty(), |r| r.u8("Flags"))?;
+    let guids = match flags {
+        Some(f) => Some(reader.count((f & 0x03) as usize, |r| r.parse(guid))?),
+        None => None,
+    };
+    reader.finish(IfrFormSet {
+        Guid: mg,
+        TitleStringId: tsid,
+        HelpStringId: hsid,
+        Flags: flags,
+        ClassGuids: guids,
+    })
 }
 
 //
@@ -1336,32 +1602,30 @@ pub struct IfrRef {
 }
 
 pub fn ifr_ref(input: &[u8]) -> IResult<&[u8], IfrRef> {
-    do_parse!(
-        input,
-        psid: le_u16
-            >> hsid: le_u16
-            >> qid: le_u16
-            >> vsid: le_u16
-            >> vsin: le_u16
-            >> qf: le_u8
-            >> r: peek!(rest)
-            >> fid: cond_with_error!(r.len() >= 2, le_u16)
-            >> rqid: cond_with_error!(r.len() >= 4, le_u16)
-            >> fsg: cond_with_error!(r.len() >= 20, guid)
-            >> dpid: cond_with_error!(r.len() >= 24, le_u16)
-            >> (IfrRef {
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                QuestionId: qid,
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                QuestionFlags: qf,
-                FormId: fid,
-                RefQuestionId: rqid,
-                FormSetGuid: fsg,
-                DevicePathId: dpid,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let r = reader.remaining();
+    let fid = reader.cond(r.len() >= 2, |r| r.u16("FormId"))?;
+    let rqid = reader.cond(r.len() >= 4, |r| r.u16("RefQuestionId"))?;
+    let fsg = reader.cond(r.len() >= 20, |r| r.parse(guid))?;
+    let dpid = reader.cond(r.len() >= 22, |r| r.u16("DevicePathId"))?;
+    reader.finish(IfrRef {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        FormId: fid,
+        RefQuestionId: rqid,
+        FormSetGuid: fsg,
+        DevicePathId: dpid,
+    })
 }
 
 //
@@ -1373,13 +1637,11 @@ pub struct IfrNoSumbitIf {
 }
 
 pub fn ifr_no_submit_if(input: &[u8]) -> IResult<&[u8], IfrNoSumbitIf> {
-    do_parse!(
-        input,
-        esid: le_u16
-            >> (IfrNoSumbitIf {
-                ErrorStringId: esid,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let esid = reader.u16("ErrorStringId")?;
+    reader.finish(IfrNoSumbitIf {
+        ErrorStringId: esid,
+    })
 }
 
 //
@@ -1391,13 +1653,11 @@ pub struct IfrInconsistentIf {
 }
 
 pub fn ifr_inconsistent_if(input: &[u8]) -> IResult<&[u8], IfrInconsistentIf> {
-    do_parse!(
-        input,
-        esid: le_u16
-            >> (IfrInconsistentIf {
-                ErrorStringId: esid,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let esid = reader.u16("ErrorStringId")?;
+    reader.finish(IfrInconsistentIf {
+        ErrorStringId: esid,
+    })
 }
 
 //
@@ -1410,15 +1670,13 @@ pub struct IfrEqIdVal {
 }
 
 pub fn ifr_eq_id_val(input: &[u8]) -> IResult<&[u8], IfrEqIdVal> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> val: le_u16
-            >> (IfrEqIdVal {
-                QuestionId: qid,
-                Value: val,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let val = reader.u16("Value")?;
+    reader.finish(IfrEqIdVal {
+        QuestionId: qid,
+        Value: val,
+    })
 }
 
 //
@@ -1431,15 +1689,13 @@ pub struct IfrEqIdId {
 }
 
 pub fn ifr_eq_id_id(input: &[u8]) -> IResult<&[u8], IfrEqIdId> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> oid: le_u16
-            >> (IfrEqIdId {
-                QuestionId: qid,
-                OtherQuestionId: oid,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let oid = reader.u16("OtherQuestionId")?;
+    reader.finish(IfrEqIdId {
+        QuestionId: qid,
+        OtherQuestionId: oid,
+    })
 }
 
 //
@@ -1452,16 +1708,14 @@ pub struct IfrEqIdValList {
 }
 
 pub fn ifr_eq_id_val_list(input: &[u8]) -> IResult<&[u8], IfrEqIdValList> {
-    do_parse!(
-        input,
-        qid: le_u16
-            >> len: le_u16
-            >> val: count!(le_u16, len as usize)
-            >> (IfrEqIdValList {
-                QuestionId: qid,
-                Values: val,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    let len = reader.u16("ListLength")?;
+    let val = reader.count(len as usize, |r| r.u16("Values"))?;
+    reader.finish(IfrEqIdValList {
+        QuestionId: qid,
+        Values: val,
+    })
 }
 
 //0x15 => IfrOpcode::And
@@ -1477,7 +1731,9 @@ pub struct IfrRule {
 }
 
 pub fn ifr_rule(input: &[u8]) -> IResult<&[u8], IfrRule> {
-    do_parse!(input, rid: le_u8 >> (IfrRule { RuleId: rid }))
+    let mut reader = Reader::new(input);
+    let rid = reader.u8("RuleId")?;
+    reader.finish(IfrRule { RuleId: rid })
 }
 
 //0x19 => IfrOpcode::GrayOutIf
@@ -1497,25 +1753,23 @@ pub struct IfrDate {
 }
 
 pub fn ifr_date(input: &[u8]) -> IResult<&[u8], IfrDate> {
-    do_parse!(
-        input,
-        psid: le_u16
-            >> hsid: le_u16
-            >> qid: le_u16
-            >> vsid: le_u16
-            >> vsin: le_u16
-            >> qf: le_u8
-            >> f: le_u8
-            >> (IfrDate {
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                QuestionId: qid,
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                QuestionFlags: qf,
-                Flags: f,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let f = reader.u8("Flags")?;
+    reader.finish(IfrDate {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        Flags: f,
+    })
 }
 
 //
@@ -1533,25 +1787,23 @@ pub struct IfrTime {
 }
 
 pub fn ifr_time(input: &[u8]) -> IResult<&[u8], IfrTime> {
-    do_parse!(
-        input,
-        psid: le_u16
-            >> hsid: le_u16
-            >> qid: le_u16
-            >> vsid: le_u16
-            >> vsin: le_u16
-            >> qf: le_u8
-            >> f: le_u8
-            >> (IfrTime {
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                QuestionId: qid,
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                QuestionFlags: qf,
-                Flags: f,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let f = reader.u8("Flags")?;
+    reader.finish(IfrTime {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        Flags: f,
+    })
 }
 
 //
@@ -1571,29 +1823,27 @@ pub struct IfrString {
 }
 
 pub fn ifr_string(input: &[u8]) -> IResult<&[u8], IfrString> {
-    do_parse!(
-        input,
-        psid: le_u16
-            >> hsid: le_u16
-            >> qid: le_u16
-            >> vsid: le_u16
-            >> vsin: le_u16
-            >> qf: le_u8
-            >> ms: le_u8
-            >> xs: le_u8
-            >> f: le_u8
-            >> (IfrString {
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                QuestionId: qid,
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                QuestionFlags: qf,
-                MinSize: ms,
-                MaxSize: xs,
-                Flags: f
-            })
-    )
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let ms = reader.u8("MinSize")?;
+    let xs = reader.u8("MaxSize")?;
+    let f = reader.u8("Flags")?;
+    reader.finish(IfrString {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        MinSize: ms,
+        MaxSize: xs,
+        Flags: f,
+    })
 }
 
 //
@@ -1605,13 +1855,11 @@ pub struct IfrRefresh {
 }
 
 pub fn ifr_refresh(input: &[u8]) -> IResult<&[u8], IfrRefresh> {
-    do_parse!(
-        input,
-        ri: le_u8
-            >> (IfrRefresh {
-                RefreshInterval: ri,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let ri = reader.u8("RefreshInterval")?;
+    reader.finish(IfrRefresh {
+        RefreshInterval: ri,
+    })
 }
 
 //0x1E => IfrOpcode::DisableIf
@@ -1625,7 +1873,9 @@ pub struct IfrAnimation {
 }
 
 pub fn ifr_animation(input: &[u8]) -> IResult<&[u8], IfrAnimation> {
-    do_parse!(input, aid: le_u16 >> (IfrAnimation { AnimationId: aid }))
+    let mut reader = Reader::new(input);
+    let aid = reader.u16("AnimationId")?;
+    reader.finish(IfrAnimation { AnimationId: aid })
 }
 
 //0x20 => IfrOpcode::ToLower
@@ -1648,27 +1898,25 @@ pub struct IfrOrderedList {
 }
 
 pub fn ifr_ordered_list(input: &[u8]) -> IResult<&[u8], IfrOrderedList> {
-    do_parse!(
-        input,
-        psid: le_u16
-            >> hsid: le_u16
-            >> qid: le_u16
-            >> vsid: le_u16
-            >> vsin: le_u16
-            >> qf: le_u8
-            >> mc: le_u8
-            >> f: le_u8
-            >> (IfrOrderedList {
-                PromptStringId: psid,
-                HelpStringId: hsid,
-                QuestionId: qid,
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                QuestionFlags: qf,
-                MaxContainers: mc,
-                Flags: f,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let mc = reader.u8("MaxContainers")?;
+    let f = reader.u8("Flags")?;
+    reader.finish(IfrOrderedList {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        MaxContainers: mc,
+        Flags: f,
+    })
 }
 
 //
@@ -1683,19 +1931,17 @@ pub struct IfrVarStore {
 }
 
 pub fn ifr_var_store(input: &[u8]) -> IResult<&[u8], IfrVarStore> {
-    do_parse!(
-        input,
-        g: guid
-            >> vsid: le_u16
-            >> size: le_u16
-            >> name: take_until_and_consume!("\x00")
-            >> (IfrVarStore {
-                Guid: g,
-                VarStoreId: vsid,
-                Size: size,
-                Name: String::from_utf8_lossy(name).to_string(),
-            })
-    )
+    let mut reader = Reader::new(input);
+    let g = reader.parse(guid)?;
+    let vsid = reader.u16("VarStoreId")?;
+    let size = reader.u16("Size")?;
+    let name = reader.zero_terminated("Name")?;
+    reader.finish(IfrVarStore {
+        Guid: g,
+        VarStoreId: vsid,
+        Size: size,
+        Name: String::from_utf8_lossy(name).to_string(),
+    })
 }
 
 //
@@ -1708,15 +1954,13 @@ pub struct IfrVarStoreNameValue {
 }
 
 pub fn ifr_var_store_name_value(input: &[u8]) -> IResult<&[u8], IfrVarStoreNameValue> {
-    do_parse!(
-        input,
-        vsid: le_u16
-            >> g: guid
-            >> (IfrVarStoreNameValue {
-                VarStoreId: vsid,
-                Guid: g,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let vsid = reader.u16("VarStoreId")?;
+    let g = reader.parse(guid)?;
+    reader.finish(IfrVarStoreNameValue {
+        VarStoreId: vsid,
+        Guid: g,
+    })
 }
 
 //
@@ -1732,22 +1976,20 @@ pub struct IfrVarStoreEfi {
 }
 
 pub fn ifr_var_store_efi(input: &[u8]) -> IResult<&[u8], IfrVarStoreEfi> {
-    do_parse!(
-        input,
-        r: peek!(rest)
-            >> vsid: le_u16
-            >> g: guid
-            >> atr: le_u32
-            >> size: cond_with_error!(r.len() >= 24, le_u16)
-            >> name: cond_with_error!(r.len() >= 26, take_until_and_consume!("\x00"))
-            >> (IfrVarStoreEfi {
-                VarStoreId: vsid,
-                Guid: g,
-                Attributes: atr,
-                Size: size,
-                Name: name.map(|n| String::from_utf8_lossy(n).to_string())
-            })
-    )
+    let mut reader = Reader::new(input);
+    let r = reader.remaining();
+    let vsid = reader.u16("VarStoreId")?;
+    let g = reader.parse(guid)?;
+    let atr = reader.u32("Attributes")?;
+    let size = reader.cond(r.len() >= 24, |r| r.u16("Size"))?;
+    let name = reader.cond(r.len() >= 25, |r| r.zero_terminated("Name"))?; // Empty name is just its terminator
+    reader.finish(IfrVarStoreEfi {
+        VarStoreId: vsid,
+        Guid: g,
+        Attributes: atr,
+        Size: size,
+        Name: name.map(|n| String::from_utf8_lossy(n).to_string()),
+    })
 }
 
 //
@@ -1759,13 +2001,11 @@ pub struct IfrVarStoreDevice {
 }
 
 pub fn ifr_var_store_device(input: &[u8]) -> IResult<&[u8], IfrVarStoreDevice> {
-    do_parse!(
-        input,
-        dp: le_u16
-            >> (IfrVarStoreDevice {
-                DevicePathStringId: dp,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let dp = reader.u16("DevicePathStringId")?;
+    reader.finish(IfrVarStoreDevice {
+        DevicePathStringId: dp,
+    })
 }
 
 //0x28 => IfrOpcode::Version
@@ -1783,17 +2023,15 @@ pub struct IfrGet {
 }
 
 pub fn ifr_get(input: &[u8]) -> IResult<&[u8], IfrGet> {
-    do_parse!(
-        input,
-        vsid: le_u16
-            >> vsin: le_u16
-            >> vst: le_u8
-            >> (IfrGet {
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                VarStoreType: vst,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let vst = reader.u8("VarStoreType")?;
+    reader.finish(IfrGet {
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        VarStoreType: vst,
+    })
 }
 
 //
@@ -1807,17 +2045,15 @@ pub struct IfrSet {
 }
 
 pub fn ifr_set(input: &[u8]) -> IResult<&[u8], IfrSet> {
-    do_parse!(
-        input,
-        vsid: le_u16
-            >> vsin: le_u16
-            >> vst: le_u8
-            >> (IfrSet {
-                VarStoreId: vsid,
-                VarStoreInfo: vsin,
-                VarStoreType: vst,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let vst = reader.u8("VarStoreType")?;
+    reader.finish(IfrSet {
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        VarStoreType: vst,
+    })
 }
 
 //
@@ -1829,7 +2065,9 @@ pub struct IfrRuleRef {
 }
 
 pub fn ifr_rule_ref(input: &[u8]) -> IResult<&[u8], IfrRuleRef> {
-    do_parse!(input, rid: le_u8 >> (IfrRuleRef { RuleId: rid }))
+    let mut reader = Reader::new(input);
+    let rid = reader.u8("RuleId")?;
+    reader.finish(IfrRuleRef { RuleId: rid })
 }
 
 //
@@ -1841,7 +2079,9 @@ pub struct IfrQuestionRef1 {
 }
 
 pub fn ifr_question_ref_1(input: &[u8]) -> IResult<&[u8], IfrQuestionRef1> {
-    do_parse!(input, qid: le_u16 >> (IfrQuestionRef1 { QuestionId: qid }))
+    let mut reader = Reader::new(input);
+    let qid = reader.u16("QuestionId")?;
+    reader.finish(IfrQuestionRef1 { QuestionId: qid })
 }
 
 //0x41 => IfrOpcode::QuestionRef2
@@ -1855,7 +2095,9 @@ pub struct IfrUint8 {
 }
 
 pub fn ifr_uint8(input: &[u8]) -> IResult<&[u8], IfrUint8> {
-    do_parse!(input, u: le_u8 >> (IfrUint8 { Value: u }))
+    let mut reader = Reader::new(input);
+    let u = reader.u8("Value")?;
+    reader.finish(IfrUint8 { Value: u })
 }
 
 //
@@ -1867,7 +2109,9 @@ pub struct IfrUint16 {
 }
 
 pub fn ifr_uint16(input: &[u8]) -> IResult<&[u8], IfrUint16> {
-    do_parse!(input, u: le_u16 >> (IfrUint16 { Value: u }))
+    let mut reader = Reader::new(input);
+    let u = reader.u16("Value")?;
+    reader.finish(IfrUint16 { Value: u })
 }
 
 //
@@ -1879,7 +2123,9 @@ pub struct IfrUint32 {
 }
 
 pub fn ifr_uint32(input: &[u8]) -> IResult<&[u8], IfrUint32> {
-    do_parse!(input, u: le_u32 >> (IfrUint32 { Value: u }))
+    let mut reader = Reader::new(input);
+    let u = reader.u32("Value")?;
+    reader.finish(IfrUint32 { Value: u })
 }
 
 //
@@ -1891,7 +2137,9 @@ pub struct IfrUint64 {
 }
 
 pub fn ifr_uint64(input: &[u8]) -> IResult<&[u8], IfrUint64> {
-    do_parse!(input, u: le_u64 >> (IfrUint64 { Value: u }))
+    let mut reader = Reader::new(input);
+    let u = reader.u64("Value")?;
+    reader.finish(IfrUint64 { Value: u })
 }
 
 //0x46 => IfrOpcode::True
@@ -1907,7 +2155,9 @@ pub struct IfrToString {
 }
 
 pub fn ifr_to_string(input: &[u8]) -> IResult<&[u8], IfrToString> {
-    do_parse!(input, f: le_u8 >> (IfrToString { Format: f }))
+    let mut reader = Reader::new(input);
+    let f = reader.u8("Format")?;
+    reader.finish(IfrToString { Format: f })
 }
 
 //0x4A => IfrOpcode::ToBoolean
@@ -1922,7 +2172,9 @@ pub struct IfrFind {
 }
 
 pub fn ifr_find(input: &[u8]) -> IResult<&[u8], IfrFind> {
-    do_parse!(input, f: le_u8 >> (IfrFind { Format: f }))
+    let mut reader = Reader::new(input);
+    let f = reader.u8("Format")?;
+    reader.finish(IfrFind { Format: f })
 }
 
 //0x4D => IfrOpcode::Token
@@ -1936,7 +2188,9 @@ pub struct IfrStringRef1 {
 }
 
 pub fn ifr_string_ref_1(input: &[u8]) -> IResult<&[u8], IfrStringRef1> {
-    do_parse!(input, sid: le_u16 >> (IfrStringRef1 { StringId: sid }))
+    let mut reader = Reader::new(input);
+    let sid = reader.u16("StringId")?;
+    reader.finish(IfrStringRef1 { StringId: sid })
 }
 
 //0x4F => IfrOpcode::StringRef2
@@ -1952,16 +2206,14 @@ pub struct IfrQuestionRef3 {
 }
 
 pub fn ifr_question_ref_3(input: &[u8]) -> IResult<&[u8], IfrQuestionRef3> {
-    do_parse!(
-        input,
-        r: peek!(rest)
-            >> dpid: cond_with_error!(r.len() >= 2, le_u16)
-            >> qg: cond_with_error!(r.len() >= 2 + 16, guid)
-            >> (IfrQuestionRef3 {
-                DevicePathId: dpid,
-                QuestionGuid: qg,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let r = reader.remaining();
+    let dpid = reader.cond(r.len() >= 2, |r| r.u16("DevicePathId"))?;
+    let qg = reader.cond(r.len() >= 2 + 16, |r| r.parse(guid))?;
+    reader.finish(IfrQuestionRef3 {
+        DevicePathId: dpid,
+        QuestionGuid: qg,
+    })
 }
 
 //0x52 => IfrOpcode::Zero
@@ -1981,7 +2233,9 @@ pub struct IfrSpan {
 }
 
 pub fn ifr_span(input: &[u8]) -> IResult<&[u8], IfrSpan> {
-    do_parse!(input, f: le_u8 >> (IfrSpan { Flags: f }))
+    let mut reader = Reader::new(input);
+    let f = reader.u8("Flags")?;
+    reader.finish(IfrSpan { Flags: f })
 }
 
 //0x5A => IfrOpcode::Value
@@ -1996,15 +2250,13 @@ pub struct IfrDefault {
 }
 
 pub fn ifr_default(input: &[u8]) -> IResult<&[u8], IfrDefault> {
-    do_parse!(
-        input,
-        did: le_u16
-            >> val: ifr_type_value
-            >> (IfrDefault {
-                DefaultId: did,
-                Value: val
-            })
-    )
+    let mut reader = Reader::new(input);
+    let did = reader.u16("DefaultId")?;
+    let val = reader.parse(ifr_type_value)?;
+    reader.finish(IfrDefault {
+        DefaultId: did,
+        Value: val,
+    })
 }
 
 //
@@ -2017,15 +2269,13 @@ pub struct IfrDefaultStore {
 }
 
 pub fn ifr_default_store(input: &[u8]) -> IResult<&[u8], IfrDefaultStore> {
-    do_parse!(
-        input,
-        nsid: le_u16
-            >> did: le_u16
-            >> (IfrDefaultStore {
-                NameStringId: nsid,
-                DefaultId: did,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let nsid = reader.u16("NameStringId")?;
+    let did = reader.u16("DefaultId")?;
+    reader.finish(IfrDefaultStore {
+        NameStringId: nsid,
+        DefaultId: did,
+    })
 }
 
 //
@@ -2038,15 +2288,13 @@ pub struct IfrFormMapMethod {
 }
 
 pub fn ifr_form_map_method(input: &[u8]) -> IResult<&[u8], IfrFormMapMethod> {
-    do_parse!(
-        input,
-        mtl: le_u16
-            >> mid: guid
-            >> (IfrFormMapMethod {
-                MethodTitleId: mtl,
-                MethodIdentifier: mid,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let mtl = reader.u16("MethodTitleId")?;
+    let mid = reader.parse(guid)?;
+    reader.finish(IfrFormMapMethod {
+        MethodTitleId: mtl,
+        MethodIdentifier: mid,
+    })
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -2056,15 +2304,13 @@ pub struct IfrFormMap {
 }
 
 pub fn ifr_form_map(input: &[u8]) -> IResult<&[u8], IfrFormMap> {
-    do_parse!(
-        input,
-        fid: le_u16
-            >> mv: many1!(complete!(ifr_form_map_method))
-            >> (IfrFormMap {
-                FormId: fid,
-                Methods: mv,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let fid = reader.u16("FormId")?;
+    let mv = reader.many1(ifr_form_map_method)?;
+    reader.finish(IfrFormMap {
+        FormId: fid,
+        Methods: mv,
+    })
 }
 
 //0x5E => IfrOpcode::Catenate
@@ -2078,8 +2324,11 @@ pub struct IfrGuid<'a> {
     pub Data: &'a [u8],
 }
 
-pub fn ifr_guid(input: &[u8]) -> IResult<&[u8], IfrGuid> {
-    do_parse!(input, g: guid >> d: rest >> (IfrGuid { Guid: g, Data: d }))
+pub fn ifr_guid(input: &[u8]) -> IResult<&[u8], IfrGuid<'_>> {
+    let mut reader = Reader::new(input);
+    let g = reader.parse(guid)?;
+    let d = reader.rest();
+    reader.finish(IfrGuid { Guid: g, Data: d })
 }
 
 // EDK2 GUID types
@@ -2093,6 +2342,64 @@ pub enum IfrEdk2ExtendOpCode {
     Unknown(u8),
 }
 
+// Inside of EDKII_IFR_BIT_VARSTORE_GUID scope, Flags of OneOf and Numeric hold the bit width of the question,
+// and Min/Max/Step values are always stored as u32
+pub const EDKII_IFR_NUMERIC_SIZE_BIT: u8 = 0x3F;
+
+pub fn ifr_one_of_bit(input: &[u8]) -> IResult<&[u8], IfrOneOf> {
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let f = reader.u8("Flags")?;
+    let mms32_0 = reader.u32("MinValue")?;
+    let mms32_1 = reader.u32("MaxValue")?;
+    let mms32_2 = reader.u32("Step")?;
+    reader.finish(IfrOneOf {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        Flags: f,
+        MinMaxStepData8: [None, None, None],
+        MinMaxStepData16: [None, None, None],
+        MinMaxStepData32: [Some(mms32_0), Some(mms32_1), Some(mms32_2)],
+        MinMaxStepData64: [None, None, None],
+    })
+}
+
+pub fn ifr_numeric_bit(input: &[u8]) -> IResult<&[u8], IfrNumeric> {
+    let mut reader = Reader::new(input);
+    let psid = reader.u16("PromptStringId")?;
+    let hsid = reader.u16("HelpStringId")?;
+    let qid = reader.u16("QuestionId")?;
+    let vsid = reader.u16("VarStoreId")?;
+    let vsin = reader.u16("VarStoreInfo")?;
+    let qf = reader.u8("QuestionFlags")?;
+    let f = reader.u8("Flags")?;
+    let mms32_0 = reader.u32("MinValue")?;
+    let mms32_1 = reader.u32("MaxValue")?;
+    let mms32_2 = reader.u32("Step")?;
+    reader.finish(IfrNumeric {
+        PromptStringId: psid,
+        HelpStringId: hsid,
+        QuestionId: qid,
+        VarStoreId: vsid,
+        VarStoreInfo: vsin,
+        QuestionFlags: qf,
+        Flags: f,
+        MinMaxStepData8: [None, None, None],
+        MinMaxStepData16: [None, None, None],
+        MinMaxStepData32: [Some(mms32_0), Some(mms32_1), Some(mms32_2)],
+        MinMaxStepData64: [None, None, None],
+    })
+}
+
 impl From<u8> for IfrEdk2ExtendOpCode {
     fn from(n: u8) -> IfrEdk2ExtendOpCode {
         match n {
@@ -2106,6 +2413,19 @@ impl From<u8> for IfrEdk2ExtendOpCode {
     }
 }
 
+impl From<IfrEdk2ExtendOpCode> for u8 {
+    fn from(val: IfrEdk2ExtendOpCode) -> Self {
+        match val {
+            IfrEdk2ExtendOpCode::Label => 0x00,
+            IfrEdk2ExtendOpCode::Banner => 0x01,
+            IfrEdk2ExtendOpCode::Timeout => 0x02,
+            IfrEdk2ExtendOpCode::Class => 0x03,
+            IfrEdk2ExtendOpCode::SubClass => 0x04,
+            IfrEdk2ExtendOpCode::Unknown(n) => n,
+        }
+    }
+}
+
 pub const IFR_TIANO_GUID: Guid = Guid {
     data1: 0xf0b1735,
     data2: 0x87a0,
@@ -2113,22 +2433,29 @@ pub const IFR_TIANO_GUID: Guid = Guid {
     data4: [0xb2, 0x66, 0x53, 0x8c, 0x38, 0xaf, 0x48, 0xce],
 };
 
+// Questions inside of a GUID operation scope with this GUID are stored in a bit-field varstore,
+// their VarOffset is a bit offset
+pub const EDKII_IFR_BIT_VARSTORE_GUID: Guid = Guid {
+    data1: 0x82ddd68b,
+    data2: 0x9163,
+    data3: 0x4187,
+    data4: [0x9b, 0x27, 0x20, 0xa8, 0xfd, 0x60, 0xa7, 0x1d],
+};
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct IfrGuidEdk2<'a> {
     pub ExtendedOpCode: IfrEdk2ExtendOpCode,
     pub Data: &'a [u8],
 }
 
-pub fn ifr_guid_edk2(input: &[u8]) -> IResult<&[u8], IfrGuidEdk2> {
-    do_parse!(
-        input,
-        e: le_u8
-            >> d: rest
-            >> (IfrGuidEdk2 {
-                ExtendedOpCode: IfrEdk2ExtendOpCode::from(e),
-                Data: d
-            })
-    )
+pub fn ifr_guid_edk2(input: &[u8]) -> IResult<&[u8], IfrGuidEdk2<'_>> {
+    let mut reader = Reader::new(input);
+    let e = reader.u8("ExtendedOpCode")?;
+    let d = reader.rest();
+    reader.finish(IfrGuidEdk2 {
+        ExtendedOpCode: IfrEdk2ExtendOpCode::from(e),
+        Data: d,
+    })
 }
 // Label, Timeout, Class and Subclass all have one u16 as Data
 
@@ -2140,17 +2467,15 @@ pub struct IfrGuidEdk2Banner {
 }
 
 pub fn ifr_guid_edk2_banner(input: &[u8]) -> IResult<&[u8], IfrGuidEdk2Banner> {
-    do_parse!(
-        input,
-        t: le_u16
-            >> l: le_u16
-            >> a: le_u8
-            >> (IfrGuidEdk2Banner {
-                TitleId: t,
-                LineNumber: l,
-                Alignment: a
-            })
-    )
+    let mut reader = Reader::new(input);
+    let t = reader.u16("TitleId")?;
+    let l = reader.u16("LineNumber")?;
+    let a = reader.u8("Alignment")?;
+    reader.finish(IfrGuidEdk2Banner {
+        TitleId: t,
+        LineNumber: l,
+        Alignment: a,
+    })
 }
 
 //EDK1 GUID types
@@ -2171,6 +2496,16 @@ impl From<u8> for IfrEdkExtendOpCode {
     }
 }
 
+impl From<IfrEdkExtendOpCode> for u8 {
+    fn from(val: IfrEdkExtendOpCode) -> Self {
+        match val {
+            IfrEdkExtendOpCode::OptionKey => 0x00,
+            IfrEdkExtendOpCode::VarEqName => 0x01,
+            IfrEdkExtendOpCode::Unknown(n) => n,
+        }
+    }
+}
+
 pub const IFR_FRAMEWORK_GUID: Guid = Guid {
     data1: 0x31ca5d1a,
     data2: 0xd511,
@@ -2185,18 +2520,16 @@ pub struct IfrGuidEdk<'a> {
     pub Data: &'a [u8],
 }
 
-pub fn ifr_guid_edk(input: &[u8]) -> IResult<&[u8], IfrGuidEdk> {
-    do_parse!(
-        input,
-        e: le_u8
-            >> t: le_u16
-            >> d: rest
-            >> (IfrGuidEdk {
-                ExtendedOpCode: IfrEdkExtendOpCode::from(e),
-                QuestionId: t,
-                Data: d,
-            })
-    )
+pub fn ifr_guid_edk(input: &[u8]) -> IResult<&[u8], IfrGuidEdk<'_>> {
+    let mut reader = Reader::new(input);
+    let e = reader.u8("ExtendedOpCode")?;
+    let t = reader.u16("QuestionId")?;
+    let d = reader.rest();
+    reader.finish(IfrGuidEdk {
+        ExtendedOpCode: IfrEdkExtendOpCode::from(e),
+        QuestionId: t,
+        Data: d,
+    })
 }
 // VarEqName has NameId as Data
 
@@ -2209,7 +2542,9 @@ pub struct IfrSecurity {
 }
 
 pub fn ifr_security(input: &[u8]) -> IResult<&[u8], IfrSecurity> {
-    do_parse!(input, g: guid >> (IfrSecurity { Guid: g }))
+    let mut reader = Reader::new(input);
+    let g = reader.parse(guid)?;
+    reader.finish(IfrSecurity { Guid: g })
 }
 
 //0x61 => IfrOpcode::ModalTag
@@ -2223,7 +2558,9 @@ pub struct IfrRefreshId {
 }
 
 pub fn ifr_refresh_id(input: &[u8]) -> IResult<&[u8], IfrRefreshId> {
-    do_parse!(input, g: guid >> (IfrRefreshId { Guid: g }))
+    let mut reader = Reader::new(input);
+    let g = reader.parse(guid)?;
+    reader.finish(IfrRefreshId { Guid: g })
 }
 
 //
@@ -2236,15 +2573,13 @@ pub struct IfrWarningIf {
 }
 
 pub fn ifr_warning_if(input: &[u8]) -> IResult<&[u8], IfrWarningIf> {
-    do_parse!(
-        input,
-        wsid: le_u16
-            >> t: le_u8
-            >> (IfrWarningIf {
-                WarningStringId: wsid,
-                Timeout: t,
-            })
-    )
+    let mut reader = Reader::new(input);
+    let wsid = reader.u16("WarningStringId")?;
+    let t = reader.u8("Timeout")?;
+    reader.finish(IfrWarningIf {
+        WarningStringId: wsid,
+        Timeout: t,
+    })
 }
 
 //
@@ -2256,5 +2591,7 @@ pub struct IfrMatch2 {
 }
 
 pub fn ifr_match_2(input: &[u8]) -> IResult<&[u8], IfrMatch2> {
-    do_parse!(input, g: guid >> (IfrMatch2 { Guid: g }))
+    let mut reader = Reader::new(input);
+    let g = reader.parse(guid)?;
+    reader.finish(IfrMatch2 { Guid: g })
 }
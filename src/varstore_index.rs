@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use uefi_model::{FormSet, Question, VarStore, VarStoreKind};
+use uefi_parser::Guid;
+
+//
+// Image-wide index of variables referenced by varstores
+//
+pub struct VarStoreReference<'a> {
+    pub package_index: usize,
+    pub form_set: &'a FormSet,
+    pub var_store: &'a VarStore,
+    pub strings_map: &'a HashMap<u16, String>,
+}
+
+pub struct QuestionReference<'a> {
+    pub package_index: usize,
+    pub question: &'a Question,
+    pub strings_map: &'a HashMap<u16, String>,
+}
+
+pub struct Variable<'a> {
+    pub name: String,
+    pub guid: Guid,
+    pub kind: VarStoreKind,
+    pub var_stores: Vec<VarStoreReference<'a>>,
+    pub questions: Vec<QuestionReference<'a>>,
+}
+
+impl Variable<'_> {
+    // Largest size of all varstores, they can disagree if some drivers only use the beginning of a variable
+    pub fn size(&self) -> Option<usize> {
+        self.var_stores
+            .iter()
+            .filter_map(|r| r.var_store.size)
+            .max()
+            .map(|s| s as usize)
+    }
+}
+
+// Merges varstores with the same name and GUID from all form sets, given with their form package indices
+pub fn variables<'a>(form_sets: &[(usize, &'a FormSet, &'a HashMap<u16, String>)]) -> Vec<Variable<'a>> {
+    let mut result: Vec<Variable> = Vec::new();
+
+    for &(package_index, form_set, strings_map) in form_sets {
+        for var_store in &form_set.var_stores {
+            // Buffer and EFI varstores can refer to the same variable, name/value varstores are different
+            let name_value = var_store.kind == VarStoreKind::NameValue;
+            let index = match result.iter().position(|v| {
+                v.name == var_store.name
+                    && v.guid == var_store.guid
+                    && (v.kind == VarStoreKind::NameValue) == name_value
+            }) {
+                Some(index) => index,
+                None => {
+                    result.push(Variable {
+                        name: var_store.name.clone(),
+                        guid: var_store.guid,
+                        kind: var_store.kind,
+                        var_stores: Vec::new(),
+                        questions: Vec::new(),
+                    });
+                    result.len() - 1
+                }
+            };
+
+            let variable = &mut result[index];
+            variable.var_stores.push(VarStoreReference {
+                package_index,
+                form_set,
+                var_store,
+                strings_map,
+            });
+            for question in &form_set.questions {
+                if question.var_store_id == var_store.var_store_id {
+                    variable.questions.push(QuestionReference {
+                        package_index,
+                        question,
+                        strings_map,
+                    });
+                }
+            }
+        }
+    }
+
+    for variable in &mut result {
+        variable
+            .questions
+            .sort_by_key(|r| (r.question.bit_range().0, r.package_index, r.question.offset));
+    }
+    result
+}
+
+fn storage_text(variable: &Variable, reference: &QuestionReference) -> String {
+    let question = reference.question;
+    if variable.kind == VarStoreKind::NameValue {
+        return format!(
+            "Name: \"{}\"",
+            reference
+                .strings_map
+                .get(&question.var_offset)
+                .unwrap_or(&String::from("InvalidId"))
+        );
+    }
+    match question.bit_width {
+        Some(bits) => format!(
+            "0x{:04X} bit {}, {} bit(s)",
+            question.byte_offset(),
+            question.var_offset % 8,
+            bits
+        ),
+        None => format!("0x{:04X}, {} byte(s)", question.var_offset, question.width),
+    }
+}
+
+pub fn variable_report(variable: &Variable) -> String {
+    let mut report = String::new();
+
+    let mut packages: Vec<usize> = variable.var_stores.iter().map(|r| r.package_index).collect();
+    packages.dedup();
+    let packages: Vec<String> = packages.iter().map(|p| p.to_string()).collect();
+    write!(
+        &mut report,
+        "{:?} variable \"{}\", Guid: {}, Form packages: [{}]",
+        variable.kind,
+        variable.name,
+        variable.guid,
+        packages.join(", ")
+    )
+    .unwrap();
+    if let Some(size) = variable.size() {
+        write!(&mut report, ", Size: 0x{size:X}").unwrap();
+    }
+    writeln!(&mut report).unwrap();
+
+    for reference in &variable.var_stores {
+        write!(
+            &mut report,
+            "\tVarStore in package {} at offset 0x{:X}, FormSet: \"{}\", VarStoreId: 0x{:X}",
+            reference.package_index,
+            reference.var_store.offset,
+            reference
+                .strings_map
+                .get(&reference.form_set.title_string_id)
+                .unwrap_or(&String::from("InvalidId")),
+            reference.var_store.var_store_id
+        )
+        .unwrap();
+        if let Some(size) = reference.var_store.size {
+            write!(&mut report, ", Size: 0x{size:X}").unwrap();
+        }
+        writeln!(&mut report).unwrap();
+    }
+
+    for reference in &variable.questions {
+        writeln!(
+            &mut report,
+            "\t{}: {:?} \"{}\", QuestionId: 0x{:X}, Package: {}, Offset: 0x{:X}",
+            storage_text(variable, reference),
+            reference.question.kind,
+            reference
+                .strings_map
+                .get(&reference.question.prompt_string_id)
+                .unwrap_or(&String::from("InvalidId")),
+            reference.question.question_id,
+            reference.package_index,
+            reference.question.offset
+        )
+        .unwrap();
+    }
+
+    if let (Some(size), true) = (variable.size(), variable.kind != VarStoreKind::NameValue) {
+        let mut claimed = vec![false; size];
+        for reference in &variable.questions {
+            let start = reference.question.byte_offset();
+            for byte in claimed.iter_mut().skip(start).take(reference.question.width) {
+                *byte = true;
+            }
+        }
+        let used = claimed.iter().filter(|b| **b).count();
+        writeln!(
+            &mut report,
+            "\tUsed by all packages: 0x{:X} of 0x{:X} bytes ({}%)",
+            used,
+            size,
+            (used * 100).checked_div(size).unwrap_or(0)
+        )
+        .unwrap();
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uefi_model::test_models::{buffer, form_set, guid, question};
+    use uefi_model::QuestionKind;
+
+    #[test]
+    fn varstores_of_the_same_variable_are_merged() {
+        let mut efi = buffer(2, "Setup", 0x10);
+        efi.kind = VarStoreKind::Efi;
+        let first = form_set(vec![buffer(1, "Setup", 8)], vec![question(QuestionKind::Numeric, 1, 1, 4, 2)]);
+        let second = form_set(vec![efi], vec![question(QuestionKind::CheckBox, 2, 2, 0, 1)]);
+        let strings = HashMap::new();
+        let variables = variables(&[(0, &first, &strings), (3, &second, &strings)]);
+
+        assert_eq!(variables.len(), 1);
+        let variable = &variables[0];
+        assert_eq!(variable.size(), Some(0x10));
+        assert_eq!(
+            variable.var_stores.iter().map(|r| r.package_index).collect::<Vec<usize>>(),
+            vec![0, 3]
+        );
+        // Questions are sorted by their storage
+        assert_eq!(
+            variable.questions.iter().map(|r| (r.package_index, r.question.question_id)).collect::<Vec<(usize, u16)>>(),
+            vec![(3, 2), (0, 1)]
+        );
+    }
+
+    #[test]
+    fn variables_differ_by_guid_and_name_value_kind() {
+        let mut other_guid = buffer(2, "Setup", 8);
+        other_guid.guid = guid(0x20);
+        let mut name_value = buffer(3, "Setup", 8);
+        name_value.kind = VarStoreKind::NameValue;
+        name_value.size = None;
+        let form_set = form_set(vec![buffer(1, "Setup", 8), other_guid, name_value], Vec::new());
+        let strings = HashMap::new();
+        let variables = variables(&[(0, &form_set, &strings)]);
+
+        assert_eq!(
+            variables.iter().map(|v| (v.guid.data1, v.kind)).collect::<Vec<(u32, VarStoreKind)>>(),
+            vec![(0x10, VarStoreKind::Buffer), (0x20, VarStoreKind::Buffer), (0x10, VarStoreKind::NameValue)]
+        );
+    }
+
+    #[test]
+    fn report_counts_bytes_used_by_all_packages() {
+        let first = form_set(vec![buffer(1, "Setup", 4)], vec![question(QuestionKind::Numeric, 1, 1, 0, 2)]);
+        let second = form_set(vec![buffer(1, "Setup", 4)], vec![question(QuestionKind::CheckBox, 2, 1, 1, 1)]);
+        let strings: HashMap<u16, String> = vec![(1, String::from("Delay"))].into_iter().collect();
+        let variables = variables(&[(0, &first, &strings), (1, &second, &strings)]);
+        let report = variable_report(&variables[0]);
+
+        let lines: Vec<&str> = report.lines().collect();
+        assert!(lines[0].starts_with("Buffer variable \"Setup\""));
+        assert!(lines[0].ends_with("Form packages: [0, 1], Size: 0x4"));
+        assert_eq!(lines[3], "\t0x0000, 2 byte(s): Numeric \"Delay\", QuestionId: 0x1, Package: 0, Offset: 0x0");
+        assert_eq!(lines[4], "\t0x0001, 1 byte(s): CheckBox \"InvalidId\", QuestionId: 0x2, Package: 1, Offset: 0x0");
+        assert_eq!(lines[5], "\tUsed by all packages: 0x2 of 0x4 bytes (50%)");
+    }
+}
@@ -0,0 +1,521 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use uefi_model::{FormSet, Question, VarStore, VarStoreKind};
+
+//
+// Varstore layout and coverage maps
+//
+pub struct Layout<'a> {
+    pub var_store: &'a VarStore,
+    pub size: usize,
+    pub questions: Vec<&'a Question>, // Sorted by the first bit of their storage
+}
+
+// Builds layouts of all varstores with a known size
+pub fn layouts(form_set: &FormSet) -> Vec<Layout<'_>> {
+    let mut result = Vec::new();
+    for var_store in &form_set.var_stores {
+        let size = match (var_store.kind, var_store.size) {
+            (VarStoreKind::NameValue, _) | (_, None) => continue,
+            (_, Some(size)) => size as usize,
+        };
+        let mut questions: Vec<&Question> = form_set
+            .questions
+            .iter()
+            .filter(|q| q.var_store_id == var_store.var_store_id && q.width > 0)
+            .collect();
+        // Wider questions go first if several of them start at the same bit
+        questions.sort_by_key(|q| (q.bit_range().0, std::cmp::Reverse(q.bit_range().1), q.offset));
+        result.push(Layout {
+            var_store,
+            size,
+            questions,
+        });
+    }
+    result
+}
+
+fn prompt<'a>(question: &Question, strings_map: &'a HashMap<u16, String>) -> &'a str {
+    strings_map
+        .get(&question.prompt_string_id)
+        .map(|s| s.as_str())
+        .unwrap_or("InvalidId")
+}
+
+fn question_text(question: &Question, strings_map: &HashMap<u16, String>) -> String {
+    match question.bit_width {
+        Some(bits) => format!(
+            "{:?} \"{}\" (QuestionId: 0x{:X}, Bits: {}..{})",
+            question.kind,
+            prompt(question, strings_map),
+            question.question_id,
+            question.var_offset % 8,
+            question.var_offset as usize % 8 + bits as usize - 1
+        ),
+        None => format!(
+            "{:?} \"{}\" (QuestionId: 0x{:X})",
+            question.kind,
+            prompt(question, strings_map),
+            question.question_id
+        ),
+    }
+}
+
+// Text map of the varstore, consecutive bytes claimed by the same questions are merged into one region
+pub fn layout_map(layout: &Layout, strings_map: &HashMap<u16, String>) -> String {
+    let mut map = String::new();
+    writeln!(
+        &mut map,
+        "VarStore \"{}\", Guid: {}, VarStoreId: 0x{:X}, Size: 0x{:X}",
+        layout.var_store.name, layout.var_store.guid, layout.var_store.var_store_id, layout.size
+    )
+    .unwrap();
+
+    // Indices of the questions claiming each byte
+    let mut claims: Vec<Vec<usize>> = vec![Vec::new(); layout.size];
+    for (index, question) in layout.questions.iter().enumerate() {
+        let start = question.byte_offset();
+        for claim in claims.iter_mut().skip(start).take(question.width) {
+            claim.push(index);
+        }
+    }
+
+    let mut used = 0;
+    let mut start = 0;
+    while start < layout.size {
+        let mut end = start + 1;
+        while end < layout.size && claims[end] == claims[start] {
+            end += 1;
+        }
+        write!(
+            &mut map,
+            "\t0x{:04X}-0x{:04X} ({} byte(s)): ",
+            start,
+            end - 1,
+            end - start
+        )
+        .unwrap();
+        if claims[start].is_empty() {
+            writeln!(&mut map, "unused").unwrap();
+        } else {
+            used += end - start;
+            let questions: Vec<String> = claims[start]
+                .iter()
+                .map(|&i| question_text(layout.questions[i], strings_map))
+                .collect();
+            writeln!(&mut map, "{}", questions.join(", ")).unwrap();
+        }
+        start = end;
+    }
+
+    for question in &layout.questions {
+        if question.byte_offset() + question.width > layout.size {
+            writeln!(
+                &mut map,
+                "\tOut of bounds at 0x{:X} ({} byte(s)): {}",
+                question.byte_offset(),
+                question.width,
+                question_text(question, strings_map)
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(
+        &mut map,
+        "\tUsed: 0x{:X} of 0x{:X} bytes ({}%)",
+        used,
+        layout.size,
+        (used * 100).checked_div(layout.size).unwrap_or(0)
+    )
+    .unwrap();
+    map
+}
+
+// C identifier made from the prompt of a question, unique within a struct
+fn field_name(question: &Question, strings_map: &HashMap<u16, String>, used_names: &mut HashSet<String>) -> String {
+    let mut name: String = prompt(question, strings_map)
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    if name.is_empty() || name == "InvalidId" {
+        name = format!("Question_0x{:X}", question.question_id);
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    if used_names.contains(&name) {
+        name = format!("{}_0x{:X}", name, question.question_id);
+    }
+    used_names.insert(name.clone());
+    name
+}
+
+// Name of the struct in EDK2 style, i.e. SETUP_DATA for Setup
+fn struct_name(var_store_name: &str) -> String {
+    let mut name = String::new();
+    let mut previous_lower = false;
+    for c in var_store_name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !name.ends_with('_') {
+                name.push('_');
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && previous_lower {
+            name.push('_');
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        name.push(c.to_ascii_uppercase());
+    }
+    let name = name.trim_matches('_');
+    if name.is_empty() {
+        String::from("VARSTORE_DATA")
+    } else {
+        format!("{name}_DATA")
+    }
+}
+
+fn type_name(bytes: usize) -> Option<&'static str> {
+    match bytes {
+        1 => Some("UINT8"),
+        2 => Some("UINT16"),
+        4 => Some("UINT32"),
+        8 => Some("UINT64"),
+        _ => None,
+    }
+}
+
+// Splits the bytes of a group of bit fields into storage units of C types that no field crosses,
+// None if there is no such split, i.e. for a field of 3 bytes
+fn bit_field_units(group: &[&Question], start: usize, end: usize) -> Option<Vec<(usize, usize)>> {
+    let can_end = |position: usize| {
+        position == end
+            || !group
+                .iter()
+                .any(|q| q.byte_offset() < position && q.byte_offset() + q.width > position)
+    };
+    let mut units = Vec::new();
+    let mut position = start;
+    while position < end {
+        let size = [8, 4, 2, 1]
+            .iter()
+            .cloned()
+            .find(|&size| position + size <= end && can_end(position + size))?;
+        units.push((position, position + size));
+        position += size;
+    }
+    Some(units)
+}
+
+// Approximate C struct definition of the varstore
+// Unused regions become reserved arrays, questions that alias already described storage are added as comments
+pub fn layout_struct(layout: &Layout, strings_map: &HashMap<u16, String>) -> String {
+    let mut text = String::new();
+    let mut used_names = HashSet::new();
+    let mut reserved = 0;
+    let mut reserved_bits = 0;
+
+    writeln!(
+        &mut text,
+        "// VarStore \"{}\", Guid: {}, VarStoreId: 0x{:X}, Size: 0x{:X}",
+        layout.var_store.name, layout.var_store.guid, layout.var_store.var_store_id, layout.size
+    )
+    .unwrap();
+    writeln!(&mut text, "#pragma pack(1)\ntypedef struct {{").unwrap();
+
+    let mut add_reserved = |text: &mut String, start: usize, end: usize| {
+        writeln!(
+            text,
+            "  UINT8 Reserved{}[0x{:X}]; // 0x{:X}, unused",
+            reserved,
+            end - start,
+            start
+        )
+        .unwrap();
+        reserved += 1;
+    };
+
+    let mut position = 0;
+    let mut index = 0;
+    while index < layout.questions.len() {
+        let question = layout.questions[index];
+        let start = question.byte_offset();
+        if start + question.width > layout.size {
+            index += 1;
+            continue;
+        }
+        if start > position {
+            add_reserved(&mut text, position, start);
+            position = start;
+        }
+        if start < position {
+            writeln!(
+                &mut text,
+                "  // 0x{:X}: {} aliases a previous field",
+                start,
+                question_text(question, strings_map)
+            )
+            .unwrap();
+            index += 1;
+            continue;
+        }
+
+        if question.bit_width.is_some() {
+            // All bit-field questions sharing bytes are placed into a single group of bit fields
+            let mut end = start + question.width;
+            let mut group = vec![question];
+            index += 1;
+            while index < layout.questions.len() {
+                let next = layout.questions[index];
+                if next.bit_width.is_none() || next.byte_offset() >= end {
+                    break;
+                }
+                end = end.max(next.byte_offset() + next.width);
+                group.push(next);
+                index += 1;
+            }
+            let units = match bit_field_units(&group, start, end) {
+                Some(units) => units,
+                None => {
+                    // Bit fields crossing the boundaries of all C types are described in comments of a byte array
+                    writeln!(
+                        &mut text,
+                        "  UINT8 {}[0x{:X}]; // 0x{:X}, bit fields:",
+                        field_name(question, strings_map, &mut used_names),
+                        end - start,
+                        start
+                    )
+                    .unwrap();
+                    for q in group {
+                        writeln!(&mut text, "  //   0x{:X}: {}", q.byte_offset(), question_text(q, strings_map)).unwrap();
+                    }
+                    position = end;
+                    continue;
+                }
+            };
+            let mut bit = start * 8;
+            let mut group = group.into_iter().peekable();
+            for (unit_start, unit_end) in units {
+                let base_type = type_name(unit_end - unit_start).unwrap_or("UINT8");
+                while let Some(q) = group.next_if(|q| q.bit_range().0 < unit_end * 8) {
+                    let (first_bit, bits) = q.bit_range();
+                    if first_bit < bit {
+                        writeln!(
+                            &mut text,
+                            "  // 0x{:X}: {} aliases a previous field",
+                            q.byte_offset(),
+                            question_text(q, strings_map)
+                        )
+                        .unwrap();
+                        continue;
+                    }
+                    if first_bit > bit {
+                        writeln!(&mut text, "  {} ReservedBits{} : {};", base_type, reserved_bits, first_bit - bit).unwrap();
+                        reserved_bits += 1;
+                    }
+                    writeln!(
+                        &mut text,
+                        "  {} {} : {}; // 0x{:X} bit {}, QuestionId: 0x{:X}, {:?} \"{}\"",
+                        base_type,
+                        field_name(q, strings_map, &mut used_names),
+                        bits,
+                        q.byte_offset(),
+                        q.var_offset % 8,
+                        q.question_id,
+                        q.kind,
+                        prompt(q, strings_map)
+                    )
+                    .unwrap();
+                    bit = first_bit + bits;
+                }
+                if bit < unit_end * 8 {
+                    writeln!(&mut text, "  {} ReservedBits{} : {};", base_type, reserved_bits, unit_end * 8 - bit).unwrap();
+                    reserved_bits += 1;
+                    bit = unit_end * 8;
+                }
+            }
+            position = end;
+            continue;
+        }
+
+        let name = field_name(question, strings_map, &mut used_names);
+        match type_name(question.width) {
+            Some(t) => write!(&mut text, "  {t} {name};").unwrap(),
+            None => write!(&mut text, "  UINT8 {}[0x{:X}];", name, question.width).unwrap(),
+        }
+        writeln!(
+            &mut text,
+            " // 0x{:X}, QuestionId: 0x{:X}, {:?} \"{}\"",
+            start,
+            question.question_id,
+            question.kind,
+            prompt(question, strings_map)
+        )
+        .unwrap();
+        position = start + question.width;
+        index += 1;
+    }
+
+    if position < layout.size {
+        add_reserved(&mut text, position, layout.size);
+    }
+
+    writeln!(
+        &mut text,
+        "}} {};\n#pragma pack()",
+        struct_name(&layout.var_store.name)
+    )
+    .unwrap();
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uefi_model::test_models::{bit_field, buffer, form_set, question};
+    use uefi_model::QuestionKind;
+
+    fn strings(pairs: &[(u16, &str)]) -> HashMap<u16, String> {
+        pairs.iter().map(|&(id, s)| (id, s.to_string())).collect()
+    }
+
+    // Fields of the struct of the first layout without their trailing comments
+    fn struct_fields(form_set: &FormSet, strings_map: &HashMap<u16, String>) -> Vec<String> {
+        let layouts = layouts(form_set);
+        layout_struct(&layouts[0], strings_map)
+            .lines()
+            .filter(|line| line.starts_with("  "))
+            .map(|line| match line.trim().starts_with("//") {
+                true => line.trim().to_string(),
+                false => line.split(" //").next().unwrap_or_default().trim().to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn layout_map_merges_bytes_of_the_same_questions() {
+        let form_set = form_set(
+            vec![buffer(1, "Setup", 8)],
+            vec![
+                question(QuestionKind::Numeric, 1, 1, 0, 2),
+                question(QuestionKind::CheckBox, 2, 1, 1, 1),
+                question(QuestionKind::Numeric, 3, 1, 6, 4),
+            ],
+        );
+        let strings = strings(&[(1, "Delay"), (2, "Enable"), (3, "Address")]);
+        let map = layout_map(&layouts(&form_set)[0], &strings);
+        assert_eq!(
+            map.lines().skip(1).collect::<Vec<&str>>(),
+            vec![
+                "\t0x0000-0x0000 (1 byte(s)): Numeric \"Delay\" (QuestionId: 0x1)",
+                "\t0x0001-0x0001 (1 byte(s)): Numeric \"Delay\" (QuestionId: 0x1), CheckBox \"Enable\" (QuestionId: 0x2)",
+                "\t0x0002-0x0005 (4 byte(s)): unused",
+                "\t0x0006-0x0007 (2 byte(s)): Numeric \"Address\" (QuestionId: 0x3)",
+                "\tOut of bounds at 0x6 (4 byte(s)): Numeric \"Address\" (QuestionId: 0x3)",
+                "\tUsed: 0x4 of 0x8 bytes (50%)",
+            ]
+        );
+    }
+
+    #[test]
+    fn struct_with_reserved_bytes_and_aliases() {
+        let form_set = form_set(
+            vec![buffer(1, "SetupVolatile", 8)],
+            vec![
+                question(QuestionKind::Numeric, 1, 1, 1, 2),
+                question(QuestionKind::CheckBox, 2, 1, 2, 1),
+                question(QuestionKind::String, 3, 1, 4, 3),
+            ],
+        );
+        let strings = strings(&[(1, "boot delay"), (2, "Boot Delay"), (3, "3rd name")]);
+        let layout = layout_struct(&layouts(&form_set)[0], &strings);
+        assert!(layout.contains("} SETUP_VOLATILE_DATA;"));
+        assert_eq!(
+            struct_fields(&form_set, &strings),
+            vec![
+                "UINT8 Reserved0[0x1];",
+                "UINT16 BootDelay;",
+                "// 0x2: CheckBox \"Boot Delay\" (QuestionId: 0x2) aliases a previous field",
+                "UINT8 Reserved1[0x1];",
+                "UINT8 _3rdName[0x3];",
+                "UINT8 Reserved2[0x1];",
+            ]
+        );
+    }
+
+    #[test]
+    fn reserved_bits_are_unique_in_the_struct() {
+        let form_set = form_set(
+            vec![buffer(1, "Setup", 2)],
+            vec![
+                bit_field(question(QuestionKind::CheckBox, 1, 1, 1, 1), 1),
+                bit_field(question(QuestionKind::CheckBox, 2, 1, 8, 1), 1),
+            ],
+        );
+        let strings = strings(&[(1, "First"), (2, "Second")]);
+        assert_eq!(
+            struct_fields(&form_set, &strings),
+            vec![
+                "UINT8 ReservedBits0 : 1;",
+                "UINT8 First : 1;",
+                "UINT8 ReservedBits1 : 6;",
+                "UINT8 Second : 1;",
+                "UINT8 ReservedBits2 : 7;",
+            ]
+        );
+    }
+
+    #[test]
+    fn three_byte_bit_field_groups_are_split_into_units() {
+        let form_set = form_set(
+            vec![buffer(1, "Setup", 3)],
+            vec![
+                bit_field(question(QuestionKind::Numeric, 1, 1, 0, 1), 12),
+                bit_field(question(QuestionKind::Numeric, 2, 1, 12, 1), 4),
+                bit_field(question(QuestionKind::Numeric, 3, 1, 16, 1), 8),
+            ],
+        );
+        let strings = strings(&[(1, "Low"), (2, "Middle"), (3, "High")]);
+        assert_eq!(
+            struct_fields(&form_set, &strings),
+            vec!["UINT16 Low : 12;", "UINT16 Middle : 4;", "UINT8 High : 8;"]
+        );
+
+    }
+
+    #[test]
+    fn bit_fields_crossing_all_units_become_a_byte_array() {
+        let form_set = form_set(
+            vec![buffer(1, "Setup", 3)],
+            vec![bit_field(question(QuestionKind::Numeric, 1, 1, 4, 1), 16)],
+        );
+        let strings = strings(&[(1, "Wide")]);
+        let layout = layout_struct(&layouts(&form_set)[0], &strings);
+        let fields: Vec<&str> = layout.lines().filter(|line| line.starts_with("  ")).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "  UINT8 Wide[0x3]; // 0x0, bit fields:",
+                "  //   0x0: Numeric \"Wide\" (QuestionId: 0x1, Bits: 4..19)",
+            ]
+        );
+    }
+
+    #[test]
+    fn struct_names() {
+        assert_eq!(struct_name("Setup"), "SETUP_DATA");
+        assert_eq!(struct_name("SaSetup"), "SA_SETUP_DATA");
+        assert_eq!(struct_name("Pch-Setup 2"), "PCH_SETUP_2_DATA");
+        assert_eq!(struct_name("--"), "VARSTORE_DATA");
+    }
+}
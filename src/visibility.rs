@@ -0,0 +1,475 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use uefi_model::{ConditionKind, ExpressionOp, FormSet, Question, QuestionKind, VarStoreKind};
+use uefi_parser::Guid;
+
+//
+// IFR expression evaluation
+//
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Value {
+    Boolean(bool),
+    Uint(u64),
+    Undefined,
+}
+
+impl Value {
+    pub(crate) fn as_bool(self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(b),
+            Value::Uint(n) => Some(n != 0),
+            Value::Undefined => None,
+        }
+    }
+
+    fn as_uint(self) -> Option<u64> {
+        match self {
+            Value::Boolean(b) => Some(b as u64),
+            Value::Uint(n) => Some(n),
+            Value::Undefined => None,
+        }
+    }
+}
+
+// Source of question values, variable contents take precedence over defaults
+pub struct Context<'a> {
+    pub form_set: &'a FormSet,
+    pub buffers: &'a HashMap<(String, Guid), Vec<u8>>, // Variable contents by varstore name and GUID
+    pub default_id: u16,
+}
+
+// Reads a little-endian value from a range of bits
+pub fn read_bits(buffer: &[u8], bit_offset: usize, bit_width: usize) -> Option<u64> {
+    if bit_width == 0 || bit_width > 64 || bit_offset + bit_width > buffer.len() * 8 {
+        return None;
+    }
+    let mut value = 0u64;
+    for i in (0..bit_width).rev() {
+        let bit = bit_offset + i;
+        value = (value << 1) | ((buffer[bit / 8] >> (bit % 8)) & 1) as u64;
+    }
+    Some(value)
+}
+
+impl Context<'_> {
+    fn buffer(&self, var_store_id: u16) -> Option<&Vec<u8>> {
+        let var_store = self.form_set.var_store(var_store_id)?;
+        if var_store.kind == VarStoreKind::NameValue {
+            return None;
+        }
+        self.buffers.get(&(var_store.name.clone(), var_store.guid))
+    }
+
+    fn question(&self, question_id: u16) -> Option<&Question> {
+        self.form_set
+            .questions
+            .iter()
+            .find(|q| q.question_id == question_id && q.kind != QuestionKind::Ref)
+    }
+
+    pub fn question_value(&self, question_id: u16) -> Value {
+        let question = match self.question(question_id) {
+            Some(q) => q,
+            None => return Value::Undefined,
+        };
+        if let Some(buffer) = self.buffer(question.var_store_id) {
+            let (bit_offset, bit_width) = question.bit_range();
+            if let Some(value) = read_bits(buffer, bit_offset, bit_width) {
+                return Value::Uint(value);
+            }
+        }
+        question
+            .defaults
+            .iter()
+            .find(|d| d.default_id == self.default_id)
+            .or_else(|| question.defaults.first())
+            .map(|d| Value::Uint(d.value))
+            .unwrap_or(Value::Undefined)
+    }
+
+    fn storage_value(&self, var_store_id: u16, var_offset: u16, width: usize) -> Value {
+        if let Some(buffer) = self.buffer(var_store_id) {
+            if let Some(value) = read_bits(buffer, var_offset as usize * 8, width * 8) {
+                return Value::Uint(value);
+            }
+        }
+        // Use the default of a question stored at the same place
+        match self
+            .form_set
+            .questions
+            .iter()
+            .find(|q| q.var_store_id == var_store_id && q.var_offset == var_offset && q.bit_width.is_none())
+        {
+            Some(q) => self.question_value(q.question_id),
+            None => Value::Undefined,
+        }
+    }
+}
+
+fn compare(a: Value, b: Value, f: fn(u64, u64) -> bool) -> Value {
+    match (a.as_uint(), b.as_uint()) {
+        (Some(a), Some(b)) => Value::Boolean(f(a, b)),
+        _ => Value::Undefined,
+    }
+}
+
+fn arithmetic(a: Value, b: Value, f: fn(u64, u64) -> Option<u64>) -> Value {
+    match (a.as_uint(), b.as_uint()) {
+        (Some(a), Some(b)) => f(a, b).map(Value::Uint).unwrap_or(Value::Undefined),
+        _ => Value::Undefined,
+    }
+}
+
+// Evaluates an expression in postfix order, malformed expressions evaluate to Undefined
+pub fn evaluate(context: &Context, expression: &[ExpressionOp]) -> Value {
+    let mut stack: Vec<Value> = Vec::new();
+
+    for op in expression {
+        let value = match *op {
+            ExpressionOp::EqIdVal { question_id, value } => {
+                compare(context.question_value(question_id), Value::Uint(value), |a, b| a == b)
+            }
+            ExpressionOp::EqIdId {
+                question_id,
+                other_question_id,
+            } => compare(
+                context.question_value(question_id),
+                context.question_value(other_question_id),
+                |a, b| a == b,
+            ),
+            ExpressionOp::EqIdValList {
+                question_id,
+                ref values,
+            } => match context.question_value(question_id).as_uint() {
+                Some(v) => Value::Boolean(values.contains(&v)),
+                None => Value::Undefined,
+            },
+            ExpressionOp::QuestionRef(question_id) => context.question_value(question_id),
+            ExpressionOp::Get {
+                var_store_id,
+                var_offset,
+                width,
+            } => context.storage_value(var_store_id, var_offset, width),
+            ExpressionOp::Uint(n) => Value::Uint(n),
+            ExpressionOp::Boolean(b) => Value::Boolean(b),
+            ExpressionOp::Undefined => Value::Undefined,
+            ExpressionOp::Dup => match stack.last() {
+                Some(v) => *v,
+                None => return Value::Undefined,
+            },
+            ExpressionOp::Other { operands, .. } => {
+                if stack.len() < operands {
+                    return Value::Undefined;
+                }
+                stack.truncate(stack.len() - operands);
+                Value::Undefined
+            }
+            // Unary operations
+            ExpressionOp::QuestionRefFromStack
+            | ExpressionOp::Not
+            | ExpressionOp::BitwiseNot
+            | ExpressionOp::ToBoolean
+            | ExpressionOp::ToUint => {
+                let a = match stack.pop() {
+                    Some(a) => a,
+                    None => return Value::Undefined,
+                };
+                match *op {
+                    ExpressionOp::QuestionRefFromStack => match a.as_uint() {
+                        Some(id) if id <= 0xFFFF => context.question_value(id as u16),
+                        _ => Value::Undefined,
+                    },
+                    ExpressionOp::Not => a.as_bool().map(|b| Value::Boolean(!b)).unwrap_or(Value::Undefined),
+                    ExpressionOp::BitwiseNot => a.as_uint().map(|n| Value::Uint(!n)).unwrap_or(Value::Undefined),
+                    ExpressionOp::ToBoolean => a.as_bool().map(Value::Boolean).unwrap_or(Value::Undefined),
+                    _ => a.as_uint().map(Value::Uint).unwrap_or(Value::Undefined),
+                }
+            }
+            ExpressionOp::Conditional => {
+                if stack.len() < 3 {
+                    return Value::Undefined;
+                }
+                let if_false = stack.pop().unwrap();
+                let if_true = stack.pop().unwrap();
+                match stack.pop().unwrap().as_bool() {
+                    Some(true) => if_true,
+                    Some(false) => if_false,
+                    None => Value::Undefined,
+                }
+            }
+            // Binary operations, the second operand is on the top of the stack
+            _ => {
+                if stack.len() < 2 {
+                    return Value::Undefined;
+                }
+                let b = stack.pop().unwrap();
+                let a = stack.pop().unwrap();
+                match *op {
+                    // False And Undefined is still False, True Or Undefined is still True
+                    ExpressionOp::And => match (a.as_bool(), b.as_bool()) {
+                        (Some(false), _) | (_, Some(false)) => Value::Boolean(false),
+                        (Some(true), Some(true)) => Value::Boolean(true),
+                        _ => Value::Undefined,
+                    },
+                    ExpressionOp::Or => match (a.as_bool(), b.as_bool()) {
+                        (Some(true), _) | (_, Some(true)) => Value::Boolean(true),
+                        (Some(false), Some(false)) => Value::Boolean(false),
+                        _ => Value::Undefined,
+                    },
+                    ExpressionOp::Equal => compare(a, b, |a, b| a == b),
+                    ExpressionOp::NotEqual => compare(a, b, |a, b| a != b),
+                    ExpressionOp::GreaterThan => compare(a, b, |a, b| a > b),
+                    ExpressionOp::GreaterEqual => compare(a, b, |a, b| a >= b),
+                    ExpressionOp::LessThan => compare(a, b, |a, b| a < b),
+                    ExpressionOp::LessEqual => compare(a, b, |a, b| a <= b),
+                    ExpressionOp::BitwiseAnd => arithmetic(a, b, |a, b| Some(a & b)),
+                    ExpressionOp::BitwiseOr => arithmetic(a, b, |a, b| Some(a | b)),
+                    ExpressionOp::ShiftLeft => arithmetic(a, b, |a, b| Some(a.checked_shl(b as u32).unwrap_or(0))),
+                    ExpressionOp::ShiftRight => arithmetic(a, b, |a, b| Some(a.checked_shr(b as u32).unwrap_or(0))),
+                    ExpressionOp::Add => arithmetic(a, b, |a, b| Some(a.wrapping_add(b))),
+                    ExpressionOp::Subtract => arithmetic(a, b, |a, b| Some(a.wrapping_sub(b))),
+                    ExpressionOp::Multiply => arithmetic(a, b, |a, b| Some(a.wrapping_mul(b))),
+                    ExpressionOp::Divide => arithmetic(a, b, |a, b| a.checked_div(b)),
+                    ExpressionOp::Modulo => arithmetic(a, b, |a, b| a.checked_rem(b)),
+                    _ => Value::Undefined,
+                }
+            }
+        };
+        stack.push(value);
+    }
+
+    if stack.len() == 1 {
+        stack[0]
+    } else {
+        Value::Undefined
+    }
+}
+
+//
+// Effective visibility of questions
+//
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Visibility {
+    Visible,
+    GrayedOut,
+    Suppressed,
+    Disabled,
+}
+
+pub struct QuestionVisibility {
+    pub visibility: Visibility,
+    pub undetermined: usize, // Number of conditions that evaluated to Undefined and were treated as false
+}
+
+pub fn question_visibility(context: &Context, question: &Question) -> QuestionVisibility {
+    conditions_visibility(context, &question.conditions)
+}
+
+// Combined effect of a list of conditions given by their indices, also used for statements
+pub fn conditions_visibility(context: &Context, conditions: &[usize]) -> QuestionVisibility {
+    let mut visibility = Visibility::Visible;
+    let mut undetermined = 0;
+
+    for &index in conditions {
+        let condition = match context.form_set.conditions.get(index) {
+            Some(c) => c,
+            None => continue,
+        };
+        let result = match evaluate(context, &condition.expression).as_bool() {
+            Some(b) => b,
+            None => {
+                undetermined += 1;
+                false
+            }
+        };
+        if !result {
+            continue;
+        }
+        // DisableIf is stronger than SuppressIf, which is stronger than GrayOutIf
+        let new = match condition.kind {
+            ConditionKind::DisableIf => Visibility::Disabled,
+            ConditionKind::SuppressIf => Visibility::Suppressed,
+            ConditionKind::GrayOutIf => Visibility::GrayedOut,
+        };
+        let rank = |v: Visibility| match v {
+            Visibility::Visible => 0,
+            Visibility::GrayedOut => 1,
+            Visibility::Suppressed => 2,
+            Visibility::Disabled => 3,
+        };
+        if rank(new) > rank(visibility) {
+            visibility = new;
+        }
+    }
+
+    QuestionVisibility {
+        visibility,
+        undetermined,
+    }
+}
+
+pub fn visibility_report(context: &Context, strings_map: &HashMap<u16, String>) -> String {
+    let mut report = String::new();
+    writeln!(
+        &mut report,
+        "FormSet Guid: {}, Title: \"{}\"",
+        context.form_set.guid,
+        strings_map
+            .get(&context.form_set.title_string_id)
+            .unwrap_or(&String::from("InvalidId"))
+    )
+    .unwrap();
+
+    for question in &context.form_set.questions {
+        let state = question_visibility(context, question);
+        write!(
+            &mut report,
+            "\t{:<10} {:?} \"{}\", QuestionId: 0x{:X}, FormId: 0x{:X}",
+            format!("{:?}", state.visibility),
+            question.kind,
+            strings_map
+                .get(&question.prompt_string_id)
+                .unwrap_or(&String::from("InvalidId")),
+            question.question_id,
+            question.form_id
+        )
+        .unwrap();
+        if state.undetermined > 0 {
+            write!(&mut report, " ({} undetermined condition(s))", state.undetermined).unwrap();
+        }
+        writeln!(&mut report).unwrap();
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uefi_model::test_models::{buffer, default, form_set, guid, question};
+    use uefi_model::{Condition, ExpressionOp as Op};
+
+    // Form set with a OneOf at byte 0 with the default 1 and a CheckBox at bit 0 of byte 1 with the default 0
+    fn test_form_set() -> FormSet {
+        let mut one_of = question(QuestionKind::OneOf, 1, 1, 0, 1);
+        one_of.defaults.push(default(0, 1));
+        let mut check_box = question(QuestionKind::CheckBox, 2, 1, 1, 1);
+        check_box.defaults.push(default(0, 0));
+        form_set(vec![buffer(1, "Setup", 2)], vec![one_of, check_box])
+    }
+
+    fn evaluate_with(buffers: &HashMap<(String, Guid), Vec<u8>>, expression: &[Op]) -> Value {
+        let form_set = test_form_set();
+        let context = Context {
+            form_set: &form_set,
+            buffers,
+            default_id: 0,
+        };
+        evaluate(&context, expression)
+    }
+
+    #[test]
+    fn bits_are_read_little_endian() {
+        let buffer = [0x34, 0x12, 0xF0];
+        assert_eq!(read_bits(&buffer, 0, 16), Some(0x1234));
+        assert_eq!(read_bits(&buffer, 4, 8), Some(0x23));
+        assert_eq!(read_bits(&buffer, 20, 4), Some(0xF));
+        assert_eq!(read_bits(&buffer, 20, 5), None);
+        assert_eq!(read_bits(&buffer, 0, 0), None);
+    }
+
+    #[test]
+    fn question_values_come_from_variables_then_defaults() {
+        let no_buffers = HashMap::new();
+        let expression = [Op::EqIdVal { question_id: 1, value: 1 }];
+        assert_eq!(evaluate_with(&no_buffers, &expression), Value::Boolean(true));
+
+        // Variables of varstores with the same name and another GUID are not used
+        let mut buffers = HashMap::new();
+        buffers.insert((String::from("Setup"), guid(0x20)), vec![2, 0]);
+        assert_eq!(evaluate_with(&buffers, &expression), Value::Boolean(true));
+        buffers.insert((String::from("Setup"), guid(0x10)), vec![2, 0]);
+        assert_eq!(evaluate_with(&buffers, &expression), Value::Boolean(false));
+        assert_eq!(evaluate_with(&buffers, &[Op::QuestionRef(1)]), Value::Uint(2));
+
+        // Get falls back to the default of a question stored at the same place
+        let get = [Op::Get {
+            var_store_id: 1,
+            var_offset: 0,
+            width: 1,
+        }];
+        assert_eq!(evaluate_with(&no_buffers, &get), Value::Uint(1));
+        assert_eq!(evaluate_with(&buffers, &get), Value::Uint(2));
+        assert_eq!(evaluate_with(&no_buffers, &[Op::QuestionRef(3)]), Value::Undefined);
+    }
+
+    #[test]
+    fn undefined_operands() {
+        let buffers = HashMap::new();
+        assert_eq!(evaluate_with(&buffers, &[Op::Undefined, Op::Boolean(false), Op::And]), Value::Boolean(false));
+        assert_eq!(evaluate_with(&buffers, &[Op::Undefined, Op::Boolean(true), Op::Or]), Value::Boolean(true));
+        assert_eq!(evaluate_with(&buffers, &[Op::Undefined, Op::Boolean(true), Op::And]), Value::Undefined);
+        assert_eq!(evaluate_with(&buffers, &[Op::Undefined, Op::Not]), Value::Undefined);
+        assert_eq!(evaluate_with(&buffers, &[Op::Uint(1), Op::Uint(0), Op::Divide]), Value::Undefined);
+        assert_eq!(
+            evaluate_with(&buffers, &[Op::Uint(1), Op::Other { opcode: 0x50, operands: 1 }]),
+            Value::Undefined
+        );
+    }
+
+    #[test]
+    fn operations_take_their_operands_from_the_stack() {
+        let buffers = HashMap::new();
+        assert_eq!(evaluate_with(&buffers, &[Op::Uint(7), Op::Uint(2), Op::Subtract]), Value::Uint(5));
+        assert_eq!(evaluate_with(&buffers, &[Op::Uint(1), Op::Uint(4), Op::ShiftLeft]), Value::Uint(0x10));
+        assert_eq!(evaluate_with(&buffers, &[Op::Uint(2), Op::Uint(3), Op::LessThan]), Value::Boolean(true));
+        assert_eq!(
+            evaluate_with(&buffers, &[Op::Boolean(false), Op::Uint(1), Op::Uint(2), Op::Conditional]),
+            Value::Uint(2)
+        );
+        assert_eq!(evaluate_with(&buffers, &[Op::Uint(1), Op::QuestionRefFromStack]), Value::Uint(1));
+        assert_eq!(evaluate_with(&buffers, &[Op::Uint(3), Op::Dup, Op::Equal]), Value::Boolean(true));
+    }
+
+    #[test]
+    fn malformed_expressions_are_undefined() {
+        let buffers = HashMap::new();
+        assert_eq!(evaluate_with(&buffers, &[]), Value::Undefined);
+        assert_eq!(evaluate_with(&buffers, &[Op::And]), Value::Undefined);
+        assert_eq!(evaluate_with(&buffers, &[Op::Uint(1), Op::Uint(2)]), Value::Undefined);
+        assert_eq!(evaluate_with(&buffers, &[Op::Dup]), Value::Undefined);
+    }
+
+    #[test]
+    fn strongest_condition_wins() {
+        let mut form_set = test_form_set();
+        let condition = |kind, expression| Condition {
+            kind,
+            expression,
+            offset: 0,
+        };
+        form_set.conditions = vec![
+            condition(ConditionKind::GrayOutIf, vec![Op::Boolean(true)]),
+            condition(ConditionKind::SuppressIf, vec![Op::EqIdVal { question_id: 2, value: 1 }]),
+            condition(ConditionKind::DisableIf, vec![Op::Undefined]),
+            condition(ConditionKind::SuppressIf, vec![Op::EqIdVal { question_id: 1, value: 1 }]),
+        ];
+        let buffers = HashMap::new();
+        let context = Context {
+            form_set: &form_set,
+            buffers: &buffers,
+            default_id: 0,
+        };
+
+        let mut check_box = question(QuestionKind::CheckBox, 3, 1, 1, 1);
+        check_box.conditions = vec![0, 1, 2];
+        let state = question_visibility(&context, &check_box);
+        assert_eq!(state.visibility, Visibility::GrayedOut);
+        assert_eq!(state.undetermined, 1);
+        check_box.conditions = vec![3, 0, 5];
+        let state = question_visibility(&context, &check_box);
+        assert_eq!(state.visibility, Visibility::Suppressed);
+        assert_eq!(state.undetermined, 0);
+    }
+}
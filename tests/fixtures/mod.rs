@@ -0,0 +1,720 @@
+// Synthetic UEFI and Framework HII images for tests
+#![allow(dead_code)]
+
+//
+// Little-endian field builder
+//
+#[derive(Default, Clone)]
+pub struct Bytes(pub Vec<u8>);
+
+impl Bytes {
+    pub fn new() -> Bytes {
+        Bytes(Vec::new())
+    }
+
+    pub fn u8(mut self, value: u8) -> Bytes {
+        self.0.push(value);
+        self
+    }
+
+    pub fn u16(mut self, value: u16) -> Bytes {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn u32(mut self, value: u32) -> Bytes {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn u64(mut self, value: u64) -> Bytes {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn raw(mut self, bytes: &[u8]) -> Bytes {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn guid(self, guid: Guid) -> Bytes {
+        self.u32(guid.0).u16(guid.1).u16(guid.2).raw(&guid.3)
+    }
+
+    // Zero-terminated 8-bit string
+    pub fn ascii(self, string: &str) -> Bytes {
+        self.raw(string.as_bytes()).u8(0)
+    }
+
+    // Zero-terminated UCS2 string
+    pub fn ucs2(mut self, string: &str) -> Bytes {
+        for c in string.encode_utf16() {
+            self = self.u16(c);
+        }
+        self.u16(0)
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct Guid(pub u32, pub u16, pub u16, pub [u8; 8]);
+
+pub const FORM_SET_GUID: Guid = Guid(0x11111111, 0x2222, 0x3333, [0x44, 0x44, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55]);
+pub const CLASS_GUID: Guid = Guid(0x93039971, 0x8545, 0x4B04, [0xB4, 0x5E, 0x32, 0xEB, 0x83, 0x26, 0x04, 0x0E]);
+pub const VAR_STORE_GUID: Guid = Guid(0xEC87D643, 0xEBA4, 0x4BB5, [0xA1, 0xE5, 0x3F, 0x3E, 0x36, 0xB2, 0x0D, 0xA9]);
+pub const OTHER_GUID: Guid = Guid(0xA0A1A2A3, 0xB0B1, 0xC0C1, [0xD0, 0xD1, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7]);
+pub const TIANO_GUID: Guid = Guid(0x0F0B1735, 0x87A0, 0x4193, [0xB2, 0x66, 0x53, 0x8C, 0x38, 0xAF, 0x48, 0xCE]);
+pub const FRAMEWORK_GUID: Guid = Guid(0x31CA5D1A, 0xD511, 0x4931, [0xB7, 0x82, 0xAE, 0x6B, 0x2B, 0x17, 0x8C, 0xD7]);
+pub const BIT_VAR_STORE_GUID: Guid = Guid(0x82DDD68B, 0x9163, 0x4187, [0x9B, 0x27, 0x20, 0xA8, 0xFD, 0x60, 0xA7, 0x1D]);
+
+//
+// IFR operation stream
+//
+pub struct Ifr {
+    pub bytes: Vec<u8>,
+    scope_bit: bool, // UEFI operations open scopes with the high bit of their length
+}
+
+impl Ifr {
+    pub fn uefi() -> Ifr {
+        Ifr {
+            bytes: Vec::new(),
+            scope_bit: true,
+        }
+    }
+
+    pub fn framework() -> Ifr {
+        Ifr {
+            bytes: Vec::new(),
+            scope_bit: false,
+        }
+    }
+
+    fn push(&mut self, opcode: u8, scope: bool, data: Bytes) -> &mut Ifr {
+        let length = data.0.len() + 2;
+        assert!(length < 0x80, "operation 0x{:X} is too long", opcode);
+        self.bytes.push(opcode);
+        self.bytes.push(if scope && self.scope_bit { length as u8 | 0x80 } else { length as u8 });
+        self.bytes.extend_from_slice(&data.0);
+        self
+    }
+
+    pub fn op(&mut self, opcode: u8, data: Bytes) -> &mut Ifr {
+        self.push(opcode, false, data)
+    }
+
+    // Operation that opens a scope, closed by end()
+    pub fn scope(&mut self, opcode: u8, data: Bytes) -> &mut Ifr {
+        self.push(opcode, true, data)
+    }
+
+    pub fn end(&mut self) -> &mut Ifr {
+        self.push(0x29, false, Bytes::new())
+    }
+}
+
+//
+// UEFI HII packages
+//
+pub fn uefi_package(package_type: u8, data: &[u8]) -> Vec<u8> {
+    let length = data.len() as u32 + 4;
+    let mut package = (length | ((package_type as u32) << 24)).to_le_bytes().to_vec();
+    package.extend_from_slice(data);
+    package
+}
+
+pub fn uefi_string_package(language: &str, blocks: &[u8]) -> Vec<u8> {
+    let header_size = 0x2E + language.len() as u32 + 1;
+    let data = Bytes::new()
+        .u32(header_size) // HdrSize
+        .u32(header_size) // StringInfoOffset
+        .raw(&[0; 32]) // LanguageWindow
+        .u16(1) // LanguageName
+        .ascii(language)
+        .raw(blocks);
+    uefi_package(0x04, &data.0)
+}
+
+pub fn uefi_form_package(operations: &Ifr) -> Vec<u8> {
+    uefi_package(0x02, &operations.bytes)
+}
+
+// StringIds of UEFI strings below, every SIBT block that holds strings is used once
+pub const UEFI_STRINGS: &[&str] = &[
+    "Golden Setup",        // 0x01: StringUcs2
+    "Form set help",       // 0x02: StringUcs2
+    "Scsu string",         // 0x03: StringScsu
+    "Scsu font string",    // 0x04: StringScsuFont
+    "Scsu strings 1",      // 0x05: StringsScsu
+    "Scsu strings 2",      // 0x06
+    "Scsu font strings 1", // 0x07: StringsScsuFont
+    "Scsu font strings 2", // 0x08
+    "Ucs2 font string",    // 0x09: StringUcs2Font
+    "Ucs2 strings 1",      // 0x0A: StringsUcs2
+    "Ucs2 strings 2",      // 0x0B
+    "Ucs2 font strings 1", // 0x0C: StringsUcs2Font
+    "Ucs2 font strings 2", // 0x0D
+    "Scsu string",         // 0x0E: Duplicate of 0x03
+    "",                    // 0x0F: Skip2
+    "",                    // 0x10
+    "",                    // 0x11: Skip1
+    "Main Form",           // 0x12: StringUcs2 from here on
+    "Question",            // 0x13
+    "Question help",       // 0x14
+    "Enabled",             // 0x15
+    "Disabled",            // 0x16
+    "Banner title",        // 0x17
+    "Reset",               // 0x18
+    "Error",               // 0x19
+    "Warning",             // 0x1A
+    "Standard",            // 0x1B
+    "Method",              // 0x1C
+    "NameOfVar",           // 0x1D
+];
+
+pub fn uefi_sibt_blocks() -> Vec<u8> {
+    let s = UEFI_STRINGS;
+    let mut blocks = Bytes::new()
+        .u8(0x14)
+        .ucs2(s[0])
+        .u8(0x14)
+        .ucs2(s[1])
+        .u8(0x10)
+        .ascii(s[2])
+        .u8(0x11)
+        .u8(0x01) // FontIdentifier
+        .ascii(s[3])
+        .u8(0x12)
+        .u16(2)
+        .ascii(s[4])
+        .ascii(s[5])
+        .u8(0x13)
+        .u8(0x01)
+        .u16(2)
+        .ascii(s[6])
+        .ascii(s[7])
+        .u8(0x15)
+        .u8(0x01)
+        .ucs2(s[8])
+        .u8(0x16)
+        .u16(2)
+        .ucs2(s[9])
+        .ucs2(s[10])
+        .u8(0x17)
+        .u8(0x01)
+        .u16(2)
+        .ucs2(s[11])
+        .ucs2(s[12])
+        .u8(0x20)
+        .u16(0x03) // Duplicate of StringId 0x03
+        .u8(0x21)
+        .u16(2) // Skip2
+        .u8(0x22)
+        .u8(1) // Skip1
+        .u8(0x30)
+        .u8(0x01) // BlockType2
+        .u8(5) // Length of the whole Ext1 block
+        .raw(&[0xA1, 0xA2])
+        .u8(0x31)
+        .u8(0x02)
+        .u16(6) // Length of the whole Ext2 block
+        .raw(&[0xB1, 0xB2])
+        .u8(0x32)
+        .u8(0x03)
+        .u32(8) // Length of the whole Ext4 block
+        .raw(&[0xC1, 0xC2]);
+    for string in &s[0x11..] {
+        blocks = blocks.u8(0x14).ucs2(string);
+    }
+    blocks.u8(0x00).0 // End
+}
+
+// Form set using every UEFI IfrOpcode
+pub fn uefi_operations() -> Ifr {
+    let mut ifr = Ifr::uefi();
+    let question = |qid: u16, var_store_id: u16, var_offset: u16, flags: u8| {
+        Bytes::new().u16(0x13).u16(0x14).u16(qid).u16(var_store_id).u16(var_offset).u8(flags)
+    };
+    ifr.scope(0x0E, Bytes::new().guid(FORM_SET_GUID).u16(0x01).u16(0x02).u8(0x01).guid(CLASS_GUID)) // FormSet
+        .op(0x5C, Bytes::new().u16(0x1B).u16(0x0000)) // DefaultStore
+        .op(0x24, Bytes::new().guid(VAR_STORE_GUID).u16(1).u16(0x20).ascii("Setup")) // VarStore
+        .op(0x26, Bytes::new().u16(2).guid(VAR_STORE_GUID).u32(0x07).u16(0x10).ascii("EfiVar")) // VarStoreEfi
+        .op(0x25, Bytes::new().u16(3).guid(VAR_STORE_GUID)) // VarStoreNameValue
+        .op(0x27, Bytes::new().u16(0x00)) // VarStoreDevice
+        .op(0x5F, Bytes::new().guid(TIANO_GUID).u8(0x03).u16(0x0102)) // Guid: Class
+        .op(0x5F, Bytes::new().guid(TIANO_GUID).u8(0x04).u16(0x0304)) // Guid: SubClass
+        .scope(0x01, Bytes::new().u16(1).u16(0x12)) // Form
+        .scope(0x02, Bytes::new().u16(0x03).u16(0x00).u8(0x00)) // Subtitle
+        .end()
+        .op(0x03, Bytes::new().u16(0x04).u16(0x05).u16(0x06)) // Text
+        .op(0x03, Bytes::new().u16(0x07).u16(0x08).u16(0x09))
+        .op(0x03, Bytes::new().u16(0x0A).u16(0x0B).u16(0x0C))
+        .op(0x03, Bytes::new().u16(0x0D).u16(0x0E).u16(0x1D))
+        .op(0x04, Bytes::new().u16(0x01)) // Image
+        .op(0x1F, Bytes::new().u16(0x01)) // Animation
+        .op(0x61, Bytes::new()) // ModalTag
+        .op(0x5F, Bytes::new().guid(TIANO_GUID).u8(0x00).u16(0x1234)) // Guid: Label
+        .op(0x5F, Bytes::new().guid(TIANO_GUID).u8(0x01).u16(0x17).u16(0x0001).u8(0x00)) // Guid: Banner
+        .op(0x5F, Bytes::new().guid(TIANO_GUID).u8(0x02).u16(0x000A)) // Guid: Timeout
+        .op(0x5F, Bytes::new().guid(FRAMEWORK_GUID).u8(0x00).u16(0x0001).raw(&[0x01, 0x02])) // Guid: OptionKey
+        .op(0x5F, Bytes::new().guid(FRAMEWORK_GUID).u8(0x01).u16(0x0001).u16(0x1D)) // Guid: VarEqName
+        .op(0x5F, Bytes::new().guid(OTHER_GUID).raw(&[0xAA, 0xBB])) // Guid: unknown
+        .scope(0x05, question(0x0001, 1, 0x00, 0x04).u8(0x10).u8(0x00).u8(0x01).u8(0x00)) // OneOf, UINT8
+        .op(0x09, Bytes::new().u16(0x15).u8(0x10).u8(0x00).u8(1)) // OneOfOption, default
+        .op(0x09, Bytes::new().u16(0x16).u8(0x20).u8(0x00).u8(0)) // OneOfOption, manufacturing default
+        .op(0x2D, Bytes::new()) // Read
+        .op(0x2E, Bytes::new()) // Write
+        .op(0x0B, Bytes::new()) // Locked
+        .op(0x1D, Bytes::new().u8(5)) // Refresh
+        .op(0x62, Bytes::new().guid(OTHER_GUID)) // RefreshId
+        .end()
+        .scope(0x06, question(0x0002, 1, 0x01, 0x00).u8(0x01)) // CheckBox
+        .op(0x5B, Bytes::new().u16(0x0000).u8(0x04).u8(1)) // Default, BOOLEAN
+        .end()
+        .scope(0x07, question(0x0003, 1, 0x02, 0x00).u8(0x01).u16(1).u16(100).u16(1)) // Numeric, UINT16
+        .op(0x5B, Bytes::new().u16(0x0000).u8(0x01).u16(10)) // Default, UINT16
+        .end()
+        .scope(0x07, question(0x0004, 1, 0x04, 0x00).u8(0x02).u32(0).u32(0xFFFF).u32(16)) // Numeric, UINT32
+        .scope(0x5B, Bytes::new().u16(0x0000).u8(0x02).u32(0)) // Default with a value expression
+        .scope(0x5A, Bytes::new()) // Value
+        .op(0x44, Bytes::new().u32(0x20)) // Uint32
+        .end()
+        .end()
+        .end()
+        .scope(0x07, question(0x0005, 1, 0x08, 0x00).u8(0x03).u64(0).u64(u64::MAX).u64(0)) // Numeric, UINT64
+        .op(0x5B, Bytes::new().u16(0x0000).u8(0x03).u64(0x1122334455667788)) // Default, UINT64
+        .end()
+        .scope(0x08, question(0x0006, 2, 0x00, 0x00).u16(1).u16(8)) // Password
+        .end()
+        .scope(0x1C, question(0x0007, 3, 0x1D, 0x00).u8(1).u8(10).u8(0x00)) // String
+        .scope(0x10, Bytes::new().u16(0x19)) // NoSubmitIf
+        .op(0x47, Bytes::new()) // False
+        .end()
+        .scope(0x11, Bytes::new().u16(0x19)) // InconsistentIf
+        .op(0x47, Bytes::new())
+        .end()
+        .scope(0x63, Bytes::new().u16(0x1A).u8(5)) // WarningIf
+        .op(0x47, Bytes::new())
+        .end()
+        .end()
+        .scope(0x23, question(0x0008, 1, 0x10, 0x00).u8(2).u8(0x00)) // OrderedList
+        .op(0x09, Bytes::new().u16(0x15).u8(0x00).u8(0x00).u8(1))
+        .op(0x09, Bytes::new().u16(0x16).u8(0x00).u8(0x00).u8(2))
+        .end()
+        .scope(0x1A, question(0x0009, 0, 0x00, 0x00).u8(0x00)) // Date
+        .op(0x5B, Bytes::new().u16(0x0000).u8(0x06).u16(2024).u8(2).u8(29)) // Default, DATE
+        .end()
+        .scope(0x1B, question(0x000A, 0, 0x00, 0x00).u8(0x00)) // Time
+        .op(0x5B, Bytes::new().u16(0x0000).u8(0x05).u8(12).u8(34).u8(56)) // Default, TIME
+        .end()
+        .scope(0x0C, question(0x000B, 0, 0x00, 0x04).u16(0x18)) // Action
+        .end()
+        .scope(0x0D, Bytes::new().u16(0x18).u16(0x14).u16(0x0000)) // ResetButton
+        .end()
+        .op(0x0F, question(0x000C, 0, 0x00, 0x00)) // Ref
+        .op(0x0F, question(0x000D, 0, 0x00, 0x00).u16(2)) // Ref to a form
+        .op(0x0F, question(0x000E, 0, 0x00, 0x00).u16(2).u16(0x0001)) // Ref to a question
+        .op(0x0F, question(0x000F, 0, 0x00, 0x00).u16(2).u16(0x0001).guid(FORM_SET_GUID)) // Ref to a form set
+        .op(0x0F, question(0x0010, 0, 0x00, 0x00).u16(2).u16(0x0001).guid(FORM_SET_GUID).u16(0x00)) // Ref to a device path
+        .scope(0x0A, Bytes::new()) // SuppressIf
+        .op(0x12, Bytes::new().u16(0x0001).u16(1)) // EqIdVal
+        .op(0x13, Bytes::new().u16(0x0001).u16(0x0002)) // EqIdId
+        .op(0x14, Bytes::new().u16(0x0001).u16(2).u16(0).u16(1)) // EqIdValList
+        .op(0x15, Bytes::new()) // And
+        .op(0x16, Bytes::new()) // Or
+        .op(0x17, Bytes::new()) // Not
+        .op(0x03, Bytes::new().u16(0x13).u16(0x14).u16(0x00))
+        .end()
+        .scope(0x19, Bytes::new()) // GrayOutIf
+        .op(0x40, Bytes::new().u16(0x0001)) // QuestionRef1
+        .op(0x41, Bytes::new()) // QuestionRef2
+        .op(0x51, Bytes::new()) // QuestionRef3
+        .op(0x51, Bytes::new().u16(0x00)) // QuestionRef3 with a device path
+        .op(0x51, Bytes::new().u16(0x00).guid(FORM_SET_GUID)) // QuestionRef3 with a device path and a form set
+        .op(0x42, Bytes::new().u8(0x01)) // Uint8
+        .op(0x43, Bytes::new().u16(0x0203)) // Uint16
+        .op(0x44, Bytes::new().u32(0x04050607)) // Uint32
+        .op(0x45, Bytes::new().u64(0x08090A0B0C0D0E0F)) // Uint64
+        .op(0x46, Bytes::new()) // True
+        .op(0x47, Bytes::new()) // False
+        .op(0x52, Bytes::new()) // Zero
+        .op(0x53, Bytes::new()) // One
+        .op(0x54, Bytes::new()) // Ones
+        .op(0x55, Bytes::new()) // Undefined
+        .op(0x28, Bytes::new()) // Version
+        .op(0x58, Bytes::new()) // This
+        .op(0x57, Bytes::new()) // Dup
+        .op(0x56, Bytes::new()) // Length
+        .op(0x2F, Bytes::new()) // Equal
+        .op(0x30, Bytes::new()) // NotEqual
+        .op(0x31, Bytes::new()) // GreaterThan
+        .op(0x32, Bytes::new()) // GreaterEqual
+        .op(0x33, Bytes::new()) // LessThan
+        .op(0x34, Bytes::new()) // LessEqual
+        .op(0x35, Bytes::new()) // BitwiseAnd
+        .op(0x36, Bytes::new()) // BitwiseOr
+        .op(0x37, Bytes::new()) // BitwiseNot
+        .op(0x38, Bytes::new()) // ShiftLeft
+        .op(0x39, Bytes::new()) // ShiftRight
+        .op(0x3A, Bytes::new()) // Add
+        .op(0x3B, Bytes::new()) // Substract
+        .op(0x3C, Bytes::new()) // Multiply
+        .op(0x3D, Bytes::new()) // Divide
+        .op(0x3E, Bytes::new()) // Modulo
+        .op(0x3F, Bytes::new().u8(1)) // RuleRef
+        .op(0x48, Bytes::new()) // ToUint
+        .op(0x49, Bytes::new().u8(0x00)) // ToString
+        .op(0x4A, Bytes::new()) // ToBoolean
+        .op(0x20, Bytes::new()) // ToLower
+        .op(0x21, Bytes::new()) // ToUpper
+        .op(0x4B, Bytes::new()) // Mid
+        .op(0x4C, Bytes::new().u8(0x00)) // Find
+        .op(0x4D, Bytes::new()) // Token
+        .op(0x4E, Bytes::new().u16(0x03)) // StringRef1
+        .op(0x4F, Bytes::new()) // StringRef2
+        .op(0x50, Bytes::new()) // Conditional
+        .op(0x59, Bytes::new().u8(0x00)) // Span
+        .op(0x5E, Bytes::new()) // Catenate
+        .op(0x2A, Bytes::new()) // Match
+        .op(0x64, Bytes::new().guid(OTHER_GUID)) // Match2
+        .op(0x60, Bytes::new().guid(OTHER_GUID)) // Security
+        .op(0x2B, Bytes::new().u16(1).u16(0x00).u8(0x00)) // Get
+        .op(0x2C, Bytes::new().u16(1).u16(0x00).u8(0x00)) // Set
+        .scope(0x22, Bytes::new()) // Map
+        .op(0x42, Bytes::new().u8(0x01))
+        .op(0x42, Bytes::new().u8(0x02))
+        .end()
+        .op(0x03, Bytes::new().u16(0x13).u16(0x14).u16(0x00))
+        .end()
+        .scope(0x1E, Bytes::new()) // DisableIf
+        .op(0x47, Bytes::new())
+        .op(0x03, Bytes::new().u16(0x13).u16(0x14).u16(0x00))
+        .end()
+        .scope(0x18, Bytes::new().u8(1)) // Rule
+        .op(0x46, Bytes::new())
+        .end()
+        .op(0x7F, Bytes::new().raw(&[0x01, 0x02])) // Unknown
+        .end()
+        .scope(0x5D, Bytes::new().u16(2).u16(0x1C).guid(OTHER_GUID)) // FormMap
+        .scope(0x5F, Bytes::new().guid(BIT_VAR_STORE_GUID)) // Guid: BitVarStore
+        .scope(0x05, question(0x0011, 1, 0x0C * 8, 0x00).u8(0x03).u32(0).u32(7).u32(0)) // OneOf, 3 bits
+        .op(0x09, Bytes::new().u16(0x15).u8(0x10).u8(0x00).u8(1))
+        .end()
+        .scope(0x07, question(0x0012, 1, 0x0C * 8 + 3, 0x00).u8(0x05).u32(0).u32(31).u32(1)) // Numeric, 5 bits
+        .end()
+        .end()
+        .end()
+        .end();
+    ifr
+}
+
+pub fn uefi_image() -> Vec<u8> {
+    let mut image = vec![0xFF; 0x10];
+    image.extend(uefi_string_package("en-US", &uefi_sibt_blocks()));
+    image.extend(vec![0xFF; 0x10]);
+    image.extend(uefi_form_package(&uefi_operations()));
+    image.extend(vec![0xFF; 0x10]);
+    image
+}
+
+//
+// Framework HII packages
+//
+pub fn framework_package(package_type: u16, data: &[u8]) -> Vec<u8> {
+    let length = data.len() as u32 + 6;
+    let mut package = Bytes::new().u32(length).u16(package_type).0;
+    package.extend_from_slice(data);
+    package
+}
+
+// The first two strings are the language name and its printable name
+pub fn framework_string_package(strings: &[&str]) -> Vec<u8> {
+    let mut offset = 6 + 16 + 4 * strings.len() as u32;
+    let mut pointers = Bytes::new();
+    let mut data = Bytes::new();
+    for string in strings {
+        pointers = pointers.u32(offset);
+        data = data.ucs2(string);
+        offset = 6 + 16 + 4 * strings.len() as u32 + data.0.len() as u32;
+    }
+    let package = Bytes::new()
+        .u32(6 + 16 + 4 * strings.len() as u32) // LanguageNameStringOffset
+        .u32(6 + 16 + 4 * strings.len() as u32 + (strings[0].len() as u32 + 1) * 2) // PrintableLanguageNameOffset
+        .u32(strings.len() as u32) // NumStringPointers
+        .u32(0) // Attributes
+        .raw(&pointers.0)
+        .raw(&data.0);
+    framework_package(0x02, &package.0)
+}
+
+pub fn framework_form_package(operations: &Ifr) -> Vec<u8> {
+    framework_package(0x03, &operations.bytes)
+}
+
+// StringIds of Framework strings below
+pub const FRAMEWORK_STRINGS: &[&str] = &[
+    "eng",           // 0x00: Language
+    "English",       // 0x01: Printable language name
+    "Golden Setup",  // 0x02
+    "Form set help", // 0x03
+    "Main Form",     // 0x04
+    "Subtitle",      // 0x05
+    "Text help",     // 0x06
+    "Text",          // 0x07
+    "Text two",      // 0x08
+    "Question",      // 0x09
+    "Question help", // 0x0A
+    "Enabled",       // 0x0B
+    "Disabled",      // 0x0C
+    "Error",         // 0x0D
+    "Banner title",  // 0x0E
+];
+
+// Form set using every Framework IfrOpcode
+pub fn framework_operations() -> Ifr {
+    let mut ifr = Ifr::framework();
+    let question = |qid: u16, width: u8| Bytes::new().u16(qid).u8(width).u16(0x09).u16(0x0A);
+    ifr.op(
+        0x0E,
+        Bytes::new().guid(FORM_SET_GUID).u16(0x02).u16(0x03).u64(0).u16(0x0001).u16(0x0002).u16(0x20),
+    ) // FormSet
+    .op(0x24, Bytes::new().guid(VAR_STORE_GUID).u16(1).u16(0x10).ascii("Setup")) // VarStore
+    .op(0x01, Bytes::new().u16(1).u16(0x04)) // Form
+    .op(0x02, Bytes::new().u16(0x05)) // Subtitle
+    .op(0x03, Bytes::new().u16(0x06).u16(0x07).u16(0x08).u8(0x00).u16(0)) // Text
+    .op(0x04, Bytes::new()) // Graphic
+    .op(0x1D, Bytes::new().u16(0x1234)) // Label
+    .op(0x20, Bytes::new().u16(0x0E).u16(1).u8(0)) // Banner
+    .op(0x21, Bytes::new().u16(0x06).u16(0x07).u16(0x08)) // Inventory
+    .op(0x05, question(0x0000, 1)) // OneOf
+    .op(0x09, Bytes::new().u16(0x0B).u16(1).u8(0x02).u16(0)) // OneOfOption, default
+    .op(0x09, Bytes::new().u16(0x0C).u16(0).u8(0x00).u16(0)) // OneOfOption
+    .op(0x10, Bytes::new()) // End
+    .op(0x06, question(0x0001, 1).u8(0x01).u16(0)) // CheckBox
+    .op(0x07, question(0x0002, 2).u8(0x00).u16(0).u16(1).u16(100).u16(1).u16(10)) // Numeric
+    .op(0x08, question(0x0004, 8).u8(0x00).u16(0).u8(1).u8(8).u16(0)) // Password
+    .op(0x1C, question(0x000C, 4).u8(0x00).u16(0).u8(1).u8(4)) // String
+    .op(0x1A, question(0x0010, 2).u8(0x00).u16(0).u16(2000).u16(2099).u16(1).u16(2024)) // Date
+    .op(0x1B, question(0x0012, 1).u8(0x00).u16(0).u16(0).u16(23).u16(1).u16(12)) // Time
+    .op(0x23, Bytes::new().u16(0x0013).u8(2).u16(0x09).u16(0x0A)) // OrderedList
+    .op(0x09, Bytes::new().u16(0x0B).u16(1).u8(0x00).u16(0))
+    .op(0x09, Bytes::new().u16(0x0C).u16(2).u8(0x00).u16(0))
+    .op(0x10, Bytes::new())
+    .op(0x0A, Bytes::new().u8(0x00)) // SuppressIf
+    .op(0x12, Bytes::new().u16(0x0000).u16(1)) // EqIdVal
+    .op(0x13, Bytes::new().u16(0x0000).u16(0x0001)) // EqIdId
+    .op(0x15, Bytes::new()) // And
+    .op(0x14, Bytes::new().u16(0x0000).u8(1).u16(2).u16(0).u16(1)) // EqIdList
+    .op(0x16, Bytes::new()) // Or
+    .op(0x22, Bytes::new().u16(0x0000).u16(1)) // EqVarVal
+    .op(0x17, Bytes::new()) // Not
+    .op(0x03, Bytes::new().u16(0x06).u16(0x07).u16(0x08).u8(0x00).u16(0))
+    .op(0x18, Bytes::new()) // EndIf
+    .op(0x19, Bytes::new().u8(0x00)) // GrayOutIf
+    .op(0x27, Bytes::new()) // True
+    .op(0x28, Bytes::new()) // False
+    .op(0x29, Bytes::new()) // Greater
+    .op(0x2A, Bytes::new()) // GreaterEqual
+    .op(0x0C, Bytes::new().u16(1).u16(0)) // Hidden
+    .op(0x18, Bytes::new())
+    .op(0x11, Bytes::new().u16(0x0D).u8(0x00)) // InconsistentIf
+    .op(0x12, Bytes::new().u16(0x0002).u16(0))
+    .op(0x18, Bytes::new())
+    .op(0x25, Bytes::new().u16(1)) // VarStoreSelect
+    .op(0x26, Bytes::new().u16(1).u16(1)) // VarStoreSelectPair
+    .op(0x1E, Bytes::new().u16(1).u16(0x09).u16(0x0A).u8(0x00).u16(0)) // SaveDefaults
+    .op(0x1F, Bytes::new().u16(1).u16(0x09).u16(0x0A).u8(0x00).u16(0)) // RestoreDefaults
+    .op(0x0F, Bytes::new().u16(1).u16(0x09).u16(0x0A).u8(0x00).u16(0)) // Ref
+    .op(0x2B, Bytes::new()) // OemDefined
+    .op(0xFE, Bytes::new()) // Oem
+    .op(0xFF, Bytes::new()) // NvAccessCommand
+    .op(0x7F, Bytes::new().raw(&[0x01, 0x02])) // Unknown
+    .op(0x0B, Bytes::new()) // EndForm
+    .op(0x0D, Bytes::new()); // EndFormSet
+    ifr
+}
+
+pub fn framework_image() -> Vec<u8> {
+    let mut image = vec![0xFF; 0x10];
+    image.extend(framework_string_package(FRAMEWORK_STRINGS));
+    image.extend(vec![0xFF; 0x10]);
+    image.extend(framework_form_package(&framework_operations()));
+    image.extend(vec![0xFF; 0x10]);
+    image
+}
+
+//
+// Small UEFI setup for settings commands
+//
+pub const SETTINGS_STRINGS: &[&str] = &[
+    "Settings",          // 0x01
+    "Settings help",     // 0x02
+    "Main",              // 0x03
+    "Advanced",          // 0x04
+    "Boot Mode",         // 0x05
+    "Help",              // 0x06
+    "Legacy",            // 0x07
+    "UEFI",              // 0x08
+    "Fast Boot",         // 0x09
+    "Boot Delay",        // 0x0A
+    "Advanced Settings", // 0x0B
+    "Debug Level",       // 0x0C
+    "Hidden Option",     // 0x0D
+    "Secure Boot",       // 0x0E
+];
+
+pub const SETTINGS_VAR_STORE_SIZE: u16 = 0x10;
+
+// Form set with a OneOf, a CheckBox that suppresses a Numeric and a Ref to a second form,
+// the changed version has another default, another range, a removed and an added question
+pub fn uefi_settings_operations(changed: bool) -> Ifr {
+    let mut ifr = Ifr::uefi();
+    let question = |prompt: u16, qid: u16, var_offset: u16, flags: u8| {
+        Bytes::new().u16(prompt).u16(0x06).u16(qid).u16(1).u16(var_offset).u8(flags)
+    };
+    ifr.scope(0x0E, Bytes::new().guid(FORM_SET_GUID).u16(0x01).u16(0x02).u8(0x01).guid(CLASS_GUID)) // FormSet
+        .op(0x5C, Bytes::new().u16(0x06).u16(0x0000)) // DefaultStore
+        .op(0x24, Bytes::new().guid(VAR_STORE_GUID).u16(1).u16(SETTINGS_VAR_STORE_SIZE).ascii("Setup")) // VarStore
+        .scope(0x01, Bytes::new().u16(1).u16(0x03)) // Form
+        .scope(0x05, question(0x05, 0x0001, 0x00, 0x00).u8(0x00).u8(0).u8(1).u8(1)) // OneOf, UINT8
+        .op(0x09, Bytes::new().u16(0x07).u8(if changed { 0x10 } else { 0x00 }).u8(0x00).u8(0))
+        .op(0x09, Bytes::new().u16(0x08).u8(if changed { 0x00 } else { 0x10 }).u8(0x00).u8(1))
+        .end()
+        .scope(0x06, question(0x09, 0x0002, 0x01, 0x00).u8(0x00)) // CheckBox
+        .end()
+        .scope(0x0A, Bytes::new()) // SuppressIf Fast Boot is disabled
+        .op(0x12, Bytes::new().u16(0x0002).u16(0))
+        .scope(0x07, question(0x0A, 0x0003, 0x02, 0x00).u8(0x01).u16(0).u16(10).u16(1)) // Numeric, UINT16
+        .op(0x5B, Bytes::new().u16(0x0000).u8(0x01).u16(3))
+        .end()
+        .end()
+        .op(0x0F, question(0x0B, 0x0004, 0x00, 0x00).u16(2)) // Ref
+        .end()
+        .scope(0x01, Bytes::new().u16(2).u16(0x04)) // Form
+        .scope(0x07, question(0x0C, 0x0005, 0x04, 0x00).u8(0x00).u8(0).u8(if changed { 7 } else { 3 }).u8(1)) // Numeric, UINT8
+        .op(0x5B, Bytes::new().u16(0x0000).u8(0x00).u8(1))
+        .end();
+    if changed {
+        ifr.scope(0x06, question(0x0E, 0x0007, 0x06, 0x00).u8(0x01)) // CheckBox, default
+            .end();
+    } else {
+        ifr.scope(0x0A, Bytes::new()) // SuppressIf
+            .op(0x46, Bytes::new())
+            .scope(0x06, question(0x0D, 0x0006, 0x05, 0x00).u8(0x00)) // CheckBox
+            .end()
+            .end();
+    }
+    ifr.end().end();
+    ifr
+}
+
+pub fn uefi_settings_image(changed: bool) -> Vec<u8> {
+    let mut blocks = Bytes::new();
+    for string in SETTINGS_STRINGS {
+        blocks = blocks.u8(0x14).ucs2(string);
+    }
+    let mut image = uefi_string_package("en-US", &blocks.u8(0x00).0);
+    image.extend(uefi_form_package(&uefi_settings_operations(changed)));
+    image
+}
+
+// Setup variable with UEFI boot mode, Fast Boot enabled, Boot Delay 5 and Debug Level 2
+pub fn settings_variable() -> Vec<u8> {
+    let mut variable = vec![0x01, 0x01, 0x05, 0x00, 0x02];
+    variable.resize(SETTINGS_VAR_STORE_SIZE as usize, 0);
+    variable
+}
+
+//
+// UEFI and Framework images with an issue for every lint check
+//
+pub const LINT_STRINGS: &[&str] = &[
+    "Lint Setup", // 0x01
+    "Help",       // 0x02
+    "Form",       // 0x03
+    "Question",   // 0x04
+    "Option",     // 0x05
+];
+
+pub fn uefi_lint_operations() -> Ifr {
+    let mut ifr = Ifr::uefi();
+    let question = |qid: u16, var_store_id: u16, var_offset: u16| {
+        Bytes::new().u16(0x04).u16(0x02).u16(qid).u16(var_store_id).u16(var_offset).u8(0x00)
+    };
+    ifr.scope(0x0E, Bytes::new().guid(FORM_SET_GUID).u16(0x01).u16(0x40).u8(0x00)) // FormSet with a missing help string
+        .op(0x24, Bytes::new().guid(VAR_STORE_GUID).u16(1).u16(0x04).ascii("Setup")) // VarStore
+        .op(0x25, Bytes::new().u16(2).guid(VAR_STORE_GUID)) // VarStoreNameValue
+        .scope(0x01, Bytes::new().u16(1).u16(0x03)) // Form
+        .op(0x03, Bytes::new().u16(0x41).u16(0x02).u16(0x00)) // Text with a missing prompt
+        .scope(0x05, question(0x0001, 1, 0x00).u8(0x00).u8(0).u8(1).u8(1)) // OneOf without a default
+        .op(0x09, Bytes::new().u16(0x42).u8(0x00).u8(0x00).u8(0)) // OneOfOption with a missing string
+        .end()
+        .scope(0x05, question(0x0002, 1, 0x01).u8(0x00).u8(0).u8(1).u8(1)) // OneOf with a default that is no option
+        .op(0x09, Bytes::new().u16(0x05).u8(0x00).u8(0x00).u8(0))
+        .op(0x5B, Bytes::new().u16(0x0000).u8(0x00).u8(2))
+        .end()
+        .scope(0x06, question(0x0002, 1, 0x02).u8(0x00)) // CheckBox with a duplicate QuestionId
+        .end()
+        .scope(0x07, question(0x0003, 3, 0x00).u8(0x00).u8(0).u8(10).u8(1)) // Numeric with an undefined VarStoreId
+        .end()
+        .scope(0x07, question(0x0004, 1, 0x03).u8(0x01).u16(0).u16(10).u16(1)) // Numeric beyond the varstore
+        .op(0x5B, Bytes::new().u16(0x0000).u8(0x01).u16(11)) // Default outside of the range
+        .end()
+        .scope(0x05, question(0x0008, 1, 0x00).u8(0x00).u8(0).u8(1).u8(1)) // OneOf with two standard defaults
+        .op(0x09, Bytes::new().u16(0x05).u8(0x10).u8(0x00).u8(0))
+        .op(0x09, Bytes::new().u16(0x05).u8(0x10).u8(0x00).u8(1))
+        .end()
+        .scope(0x1C, question(0x0005, 2, 0x43).u8(1).u8(10).u8(0x00)) // String with a missing variable name
+        .end()
+        .op(0x0F, question(0x0006, 0, 0x00).u16(9)) // Ref to a missing form
+        .op(0x0F, question(0x0007, 0, 0x00).u16(1).u16(0).guid(CLASS_GUID)) // Ref to a form set not in the image
+        .end()
+        .end()
+        .end() // End without an open scope
+        .scope(0x01, Bytes::new().u16(2).u16(0x03)) // Form that is never closed
+        .scope(0x02, Bytes::new().u16(0x03).u16(0x00).u8(0x00)) // Subtitle
+        .end();
+    ifr
+}
+
+pub fn uefi_lint_image() -> Vec<u8> {
+    let mut blocks = Bytes::new();
+    for string in LINT_STRINGS {
+        blocks = blocks.u8(0x14).ucs2(string);
+    }
+    let mut image = uefi_string_package("en-US", &blocks.u8(0x00).0);
+    image.extend(uefi_form_package(&uefi_lint_operations()));
+    // Form set with an operation too short to be parsed
+    let mut damaged = Ifr::uefi();
+    damaged
+        .scope(0x0E, Bytes::new().guid(OTHER_GUID).u16(0x01).u16(0x02).u8(0x00))
+        .op(0x03, Bytes::new().u16(0x01).u16(0x02).u16(0x00));
+    damaged.bytes.extend_from_slice(&[0x03, 0x01]);
+    damaged.end();
+    image.extend(uefi_form_package(&damaged));
+    image
+}
+
+pub fn framework_lint_operations() -> Ifr {
+    let mut ifr = Ifr::framework();
+    ifr.op(
+        0x0E,
+        Bytes::new().guid(FORM_SET_GUID).u16(0x02).u16(0x03).u64(0).u16(0x0001).u16(0x0002).u16(0x10),
+    ) // FormSet
+    .op(0x01, Bytes::new().u16(1).u16(0x04)) // Form
+    .op(0x0A, Bytes::new().u8(0x00)) // SuppressIf
+    .op(0x27, Bytes::new()) // True
+    .op(0x0B, Bytes::new()) // EndForm closing the SuppressIf
+    .op(0x18, Bytes::new()) // EndIf
+    .op(0x0B, Bytes::new()) // EndForm
+    .op(0x0D, Bytes::new()) // EndFormSet
+    .op(0x18, Bytes::new()) // EndIf without an open scope
+    .op(0x01, Bytes::new().u16(2).u16(0x04)) // Form that is never closed
+    .op(0x0D, Bytes::new()); // EndFormSet closing the Form
+    ifr
+}
+
+pub fn framework_lint_image() -> Vec<u8> {
+    let mut image = framework_string_package(FRAMEWORK_STRINGS);
+    image.extend(framework_form_package(&framework_lint_operations()));
+    image
+}
@@ -0,0 +1,334 @@
+// Golden-file regression tests: outputs of all commands for synthetic UEFI and Framework images are compared
+// against expected outputs in tests/golden, run with UPDATE_GOLDEN=1 to rewrite them after an intended change
+mod fixtures;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Creates an empty directory of its own for a test with the given input files
+fn test_dir(test: &str, files: &[(&str, Vec<u8>)]) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden").join(test);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(&dir).unwrap();
+    for (file_name, data) in files {
+        fs::write(dir.join(file_name), data).unwrap();
+    }
+    dir
+}
+
+// Runs the extractor in the directory, so the output only mentions file names
+fn run_in(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ifrextractor"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    let version = format!("Program version: {}", env!("CARGO_PKG_VERSION"));
+    format!(
+        "exit code: {}\n--- stdout\n{}--- stderr\n{}",
+        output.status.code().unwrap_or(-1),
+        String::from_utf8_lossy(&output.stdout).replace(&version, "Program version: {version}"),
+        String::from_utf8_lossy(&output.stderr)
+    )
+}
+
+// Runs the extractor on the image in a directory of its own
+fn run(test: &str, file_name: &str, image: &[u8], args: &[&str]) -> String {
+    let dir = test_dir(test, &[(file_name, image.to_vec())]);
+    let mut args = args.to_vec();
+    args.push(file_name);
+    run_in(&dir, &args)
+}
+
+// Contents of a file written by the extractor, binary files are shown as hex bytes
+fn output_file(dir: &Path, file_name: &str) -> String {
+    let data = fs::read(dir.join(file_name)).unwrap_or_default();
+    let text = match String::from_utf8(data.clone()) {
+        Ok(text) if !text.contains('\0') => text,
+        _ => {
+            let bytes: Vec<String> = data.iter().map(|b| format!("{b:02X}")).collect();
+            format!("{}\n", bytes.join(" "))
+        }
+    };
+    format!("--- {file_name}\n{text}")
+}
+
+fn check(test: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{test}.txt"));
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Can't read {}: {e}, run with UPDATE_GOLDEN=1 to create it", path.display()));
+    assert!(
+        expected == actual,
+        "Output differs from {}, run with UPDATE_GOLDEN=1 to update it if the change is intended\n{actual}",
+        path.display()
+    );
+}
+
+fn uefi(test: &str, args: &[&str]) {
+    check(test, &run(test, "uefi.bin", &fixtures::uefi_image(), args));
+}
+
+fn framework(test: &str, args: &[&str]) {
+    check(test, &run(test, "framework.bin", &fixtures::framework_image(), args));
+}
+
+#[test]
+fn uefi_list() {
+    uefi("uefi_list", &["list"]);
+}
+
+#[test]
+fn uefi_candidates() {
+    uefi("uefi_candidates", &["candidates"]);
+}
+
+#[test]
+fn uefi_stats() {
+    uefi("uefi_stats", &["stats"]);
+}
+
+#[test]
+fn uefi_lint() {
+    uefi("uefi_lint", &["lint"]);
+}
+
+#[test]
+fn uefi_extract_text() {
+    uefi("uefi_extract_text", &["extract", "--stdout"]);
+}
+
+#[test]
+fn uefi_extract_verbose() {
+    uefi("uefi_extract_verbose", &["extract", "--stdout", "--verbose"]);
+}
+
+#[test]
+fn uefi_extract_json() {
+    uefi("uefi_extract_json", &["extract", "--stdout", "--format", "json"]);
+}
+
+#[test]
+fn framework_list() {
+    framework("framework_list", &["list"]);
+}
+
+#[test]
+fn framework_candidates() {
+    framework("framework_candidates", &["candidates"]);
+}
+
+#[test]
+fn framework_stats() {
+    framework("framework_stats", &["stats"]);
+}
+
+#[test]
+fn framework_lint() {
+    framework("framework_lint", &["lint"]);
+}
+
+#[test]
+fn framework_extract_text() {
+    framework("framework_extract_text", &["extract", "--stdout"]);
+}
+
+#[test]
+fn framework_extract_verbose() {
+    framework("framework_extract_verbose", &["extract", "--stdout", "--verbose"]);
+}
+
+#[test]
+fn framework_extract_json() {
+    framework("framework_extract_json", &["extract", "--stdout", "--format", "json"]);
+}
+
+#[test]
+fn uefi_overlaps() {
+    uefi("uefi_overlaps", &["overlaps"]);
+}
+
+#[test]
+fn uefi_layout() {
+    uefi("uefi_layout", &["layout"]);
+}
+
+#[test]
+fn uefi_varstores() {
+    uefi("uefi_varstores", &["varstores"]);
+}
+
+#[test]
+fn uefi_visibility() {
+    uefi("uefi_visibility", &["visibility"]);
+}
+
+#[test]
+fn uefi_hidden() {
+    uefi("uefi_hidden", &["hidden"]);
+}
+
+#[test]
+fn uefi_graph() {
+    uefi("uefi_graph", &["graph"]);
+}
+
+#[test]
+fn uefi_graph_mermaid() {
+    uefi("uefi_graph_mermaid", &["graph", "--format", "mermaid"]);
+}
+
+#[test]
+fn uefi_tree() {
+    uefi("uefi_tree", &["tree"]);
+}
+
+#[test]
+fn uefi_search() {
+    let dir = test_dir("uefi_search", &[("uefi.bin", fixtures::uefi_image())]);
+    check("uefi_search", &run_in(&dir, &["search", "-i", "uefi.bin", "^(ENABLED|question help)$"]));
+}
+
+#[test]
+fn framework_visibility() {
+    framework("framework_visibility", &["visibility"]);
+}
+
+#[test]
+fn framework_hidden() {
+    framework("framework_hidden", &["hidden"]);
+}
+
+#[test]
+fn framework_graph() {
+    framework("framework_graph", &["graph"]);
+}
+
+#[test]
+fn framework_tree() {
+    framework("framework_tree", &["tree"]);
+}
+
+#[test]
+fn framework_search() {
+    let dir = test_dir("framework_search", &[("framework.bin", fixtures::framework_image())]);
+    check("framework_search", &run_in(&dir, &["search", "framework.bin", "^(Question|Text)$"]));
+}
+
+//
+// Settings commands with variable files
+//
+fn settings_dir(test: &str) -> PathBuf {
+    test_dir(
+        test,
+        &[
+            ("settings.bin", fixtures::uefi_settings_image(false)),
+            ("changed.bin", fixtures::uefi_settings_image(true)),
+            ("Setup.bin", fixtures::settings_variable()),
+        ],
+    )
+}
+
+#[test]
+fn settings_visibility() {
+    let dir = settings_dir("settings_visibility");
+    check("settings_visibility", &run_in(&dir, &["visibility", "settings.bin", "Setup=Setup.bin"]));
+}
+
+#[test]
+fn settings_tree() {
+    let dir = settings_dir("settings_tree");
+    check("settings_tree", &run_in(&dir, &["tree", "settings.bin", "Setup.bin"]));
+}
+
+#[test]
+fn settings_hidden() {
+    let dir = settings_dir("settings_hidden");
+    check("settings_hidden", &run_in(&dir, &["hidden", "settings.bin"]));
+}
+
+#[test]
+fn settings_search() {
+    let dir = settings_dir("settings_search");
+    check("settings_search", &run_in(&dir, &["search", "--ignore-case", "settings.bin", "boot"]));
+}
+
+#[test]
+fn settings_diff() {
+    let dir = settings_dir("settings_diff");
+    check("settings_diff", &run_in(&dir, &["diff", "settings.bin", "changed.bin"]));
+}
+
+#[test]
+fn settings_apply_toml() {
+    let dir = settings_dir("settings_apply_toml");
+    fs::write(
+        dir.join("profile.toml"),
+        "# Boot settings\n\"Boot Mode\" = \"Legacy\"\n\"Boot Delay\" = 7\n0x5 = 3\n",
+    )
+    .unwrap();
+    let mut actual = run_in(&dir, &["apply", "settings.bin", "profile.toml", "Setup.bin"]);
+    actual.push_str(&output_file(&dir, "Setup.bin.modified"));
+    check("settings_apply_toml", &actual);
+}
+
+#[test]
+fn settings_apply_json() {
+    let dir = settings_dir("settings_apply_json");
+    fs::write(dir.join("profile.json"), "{\"Fast Boot\": \"Disabled\", \"Debug Level\": 4}\n").unwrap();
+    let mut actual = run_in(&dir, &["apply", "settings.bin", "profile.json", "Setup.bin"]);
+    actual.push_str(&output_file(&dir, "Setup.bin.modified"));
+    check("settings_apply_json", &actual);
+}
+
+//
+// Batch extraction of all images
+//
+#[test]
+fn batch() {
+    let dir = test_dir(
+        "batch",
+        &[
+            ("uefi.bin", fixtures::uefi_image()),
+            ("framework.bin", fixtures::framework_image()),
+            ("empty.bin", vec![0xFF; 0x100]),
+        ],
+    );
+    // Images are processed in parallel, so progress lines are left out
+    let actual = run_in(&dir, &["batch", "out", "*.bin"]);
+    let mut actual: String = actual
+        .lines()
+        .filter(|line| !line.starts_with('['))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    actual.push_str(&output_file(&dir, "out/index.csv"));
+    actual.push_str(&output_file(&dir, "out/index.json"));
+    check("batch", &actual);
+}
+
+//
+// Lint checks
+//
+#[test]
+fn uefi_lint_checks() {
+    check("uefi_lint_checks", &run("uefi_lint_checks", "lint.bin", &fixtures::uefi_lint_image(), &["lint"]));
+}
+
+#[test]
+fn framework_lint_checks() {
+    check(
+        "framework_lint_checks",
+        &run("framework_lint_checks", "lint.bin", &fixtures::framework_lint_image(), &["lint"]),
+    );
+}
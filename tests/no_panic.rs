@@ -0,0 +1,144 @@
+// Parsers and extraction must not panic on malformed images: the synthetic images are damaged in many
+// pseudo-random ways and everything the fuzz targets in fuzz/ exercise is run on the results
+extern crate ifrextractor;
+mod fixtures;
+
+use ifrextractor::{ifr_extract, model_json, package_discovery, uefi_parser};
+use ifrextractor::framework_parser;
+
+// Deterministic xorshift generator, so a failure can be reproduced by its iteration number
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+// Values that tend to hit length, count and scope boundaries
+const INTERESTING: &[u8] = &[0x00, 0x01, 0x02, 0x7F, 0x80, 0x81, 0xFE, 0xFF];
+
+fn mutate(rng: &mut Rng, image: &[u8]) -> Vec<u8> {
+    let mut data = image.to_vec();
+    for _ in 0..1 + rng.below(8) {
+        if data.is_empty() {
+            break;
+        }
+        let position = rng.below(data.len());
+        match rng.below(5) {
+            0 => data[position] = rng.next() as u8,
+            1 => data[position] = INTERESTING[rng.below(INTERESTING.len())],
+            2 => data[position] ^= 1 << rng.below(8),
+            3 => data.truncate(position),
+            _ => {
+                data.remove(position);
+            }
+        }
+    }
+    data
+}
+
+// Everything extraction does with an image, in both discovery modes and all output formats
+fn extract(data: &[u8]) {
+    for &relaxed in &[false, true] {
+        let candidates = package_discovery::candidates(data, relaxed);
+        package_discovery::near_misses(data);
+
+        let (strings, forms) = ifr_extract::uefi_find_string_and_form_packages(&candidates);
+        for form in &forms {
+            let parsed = ifr_extract::uefi_parse_form_package(data, form);
+            for string in &strings {
+                for &verbose in &[false, true] {
+                    ifr_extract::uefi_ifr_extract(parsed.as_ref().ok(), string, verbose, &mut Vec::new());
+                }
+                if let Ok(parsed) = &parsed {
+                    model_json::form_sets_json(&parsed.form_sets, &string.string_id_map);
+                }
+            }
+        }
+
+        let (strings, forms) = ifr_extract::framework_find_string_and_form_packages(&candidates);
+        for form in &forms {
+            let parsed = ifr_extract::framework_parse_form_package(data, form);
+            for string in &strings {
+                for &verbose in &[false, true] {
+                    ifr_extract::framework_ifr_extract(parsed.as_ref().ok(), string, verbose, &mut Vec::new());
+                }
+                if let Ok(parsed) = &parsed {
+                    model_json::form_sets_json(&parsed.form_sets, &string.string_id_map);
+                }
+            }
+        }
+    }
+}
+
+// Parsers the scanners and extraction are built on, run on every suffix of the data
+fn parse(data: &[u8]) {
+    for start in 0..data.len() {
+        let input = &data[start..];
+        let _ = uefi_parser::hii_package(input);
+        let _ = uefi_parser::hii_sibt_blocks(input);
+        let _ = uefi_parser::ifr_operations(input);
+        let _ = uefi_parser::ifr_operations_resilient(input);
+        let _ = framework_parser::hii_package(input);
+        let _ = framework_parser::ifr_operations(input);
+        let _ = framework_parser::ifr_operations_resilient(input);
+    }
+}
+
+fn check_mutations(seed: u64, image: &[u8], iterations: usize) {
+    let mut rng = Rng(seed);
+    for iteration in 0..iterations {
+        let data = mutate(&mut rng, image);
+        if std::panic::catch_unwind(|| extract(&data)).is_err() {
+            panic!("Extraction panics on mutation #{} of the image with seed {}", iteration, seed);
+        }
+    }
+}
+
+#[test]
+fn uefi_image_mutations() {
+    check_mutations(0x5EED_0001, &fixtures::uefi_image(), 300);
+}
+
+#[test]
+fn framework_image_mutations() {
+    check_mutations(0x5EED_0002, &fixtures::framework_image(), 300);
+}
+
+#[test]
+fn uefi_parsers_on_mutations() {
+    let mut rng = Rng(0x5EED_0003);
+    let blocks = fixtures::uefi_sibt_blocks();
+    let operations = fixtures::uefi_operations().bytes;
+    for _ in 0..100 {
+        parse(&mutate(&mut rng, &blocks));
+        parse(&mutate(&mut rng, &operations));
+    }
+}
+
+#[test]
+fn framework_parsers_on_mutations() {
+    let mut rng = Rng(0x5EED_0004);
+    let operations = fixtures::framework_operations().bytes;
+    for _ in 0..100 {
+        parse(&mutate(&mut rng, &operations));
+    }
+}
+
+#[test]
+fn random_data() {
+    let mut rng = Rng(0x5EED_0005);
+    for _ in 0..200 {
+        let data: Vec<u8> = (0..rng.below(512)).map(|_| rng.next() as u8).collect();
+        parse(&data);
+        extract(&data);
+    }
+}
@@ -0,0 +1,417 @@
+// Encoding parsed UEFI HII structures gives back the bytes they were parsed from, and edited structures
+// can be written out and parsed again
+extern crate ifrextractor;
+extern crate proptest;
+mod fixtures;
+
+use ifrextractor::uefi_encoder as encoder;
+use ifrextractor::uefi_parser as parser;
+use ifrextractor::uefi_parser::{IfrOpcode, IfrOperation, IfrTypeValue};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+// Parses data with a parser, that must consume all of it, and encodes the result again
+macro_rules! reencode {
+    ($parse:path, $encode:path, $data:expr) => {{
+        let (rest, value) = $parse($data).unwrap();
+        assert!(rest.is_empty(), "{} leaves {} byte(s)", stringify!($parse), rest.len());
+        $encode(&value).unwrap()
+    }};
+}
+
+// Data of an operation encoded from the structure its parser produces
+fn reencode_operation(operation: &IfrOperation, bit_var_store: bool) -> Vec<u8> {
+    let data = operation.Data.unwrap_or_default();
+    match operation.OpCode {
+        IfrOpcode::Form => reencode!(parser::ifr_form, encoder::ifr_form, data),
+        IfrOpcode::Subtitle => reencode!(parser::ifr_subtitle, encoder::ifr_subtitle, data),
+        IfrOpcode::Text => reencode!(parser::ifr_text, encoder::ifr_text, data),
+        IfrOpcode::Image => reencode!(parser::ifr_image, encoder::ifr_image, data),
+        IfrOpcode::OneOf if bit_var_store => reencode!(parser::ifr_one_of_bit, encoder::ifr_one_of_bit, data),
+        IfrOpcode::OneOf => reencode!(parser::ifr_one_of, encoder::ifr_one_of, data),
+        IfrOpcode::CheckBox => reencode!(parser::ifr_check_box, encoder::ifr_check_box, data),
+        IfrOpcode::Numeric if bit_var_store => reencode!(parser::ifr_numeric_bit, encoder::ifr_numeric_bit, data),
+        IfrOpcode::Numeric => reencode!(parser::ifr_numeric, encoder::ifr_numeric, data),
+        IfrOpcode::Password => reencode!(parser::ifr_password, encoder::ifr_password, data),
+        IfrOpcode::OneOfOption => reencode!(parser::ifr_one_of_option, encoder::ifr_one_of_option, data),
+        IfrOpcode::Action => reencode!(parser::ifr_action, encoder::ifr_action, data),
+        IfrOpcode::ResetButton => reencode!(parser::ifr_reset_button, encoder::ifr_reset_button, data),
+        IfrOpcode::FormSet => reencode!(parser::ifr_form_set, encoder::ifr_form_set, data),
+        IfrOpcode::Ref => reencode!(parser::ifr_ref, encoder::ifr_ref, data),
+        IfrOpcode::NoSubmitIf => reencode!(parser::ifr_no_submit_if, encoder::ifr_no_submit_if, data),
+        IfrOpcode::InconsistentIf => reencode!(parser::ifr_inconsistent_if, encoder::ifr_inconsistent_if, data),
+        IfrOpcode::EqIdVal => reencode!(parser::ifr_eq_id_val, encoder::ifr_eq_id_val, data),
+        IfrOpcode::EqIdId => reencode!(parser::ifr_eq_id_id, encoder::ifr_eq_id_id, data),
+        IfrOpcode::EqIdValList => reencode!(parser::ifr_eq_id_val_list, encoder::ifr_eq_id_val_list, data),
+        IfrOpcode::Rule => reencode!(parser::ifr_rule, encoder::ifr_rule, data),
+        IfrOpcode::Date => reencode!(parser::ifr_date, encoder::ifr_date, data),
+        IfrOpcode::Time => reencode!(parser::ifr_time, encoder::ifr_time, data),
+        IfrOpcode::String => reencode!(parser::ifr_string, encoder::ifr_string, data),
+        IfrOpcode::Refresh => reencode!(parser::ifr_refresh, encoder::ifr_refresh, data),
+        IfrOpcode::Animation => reencode!(parser::ifr_animation, encoder::ifr_animation, data),
+        IfrOpcode::OrderedList => reencode!(parser::ifr_ordered_list, encoder::ifr_ordered_list, data),
+        IfrOpcode::VarStore => reencode!(parser::ifr_var_store, encoder::ifr_var_store, data),
+        IfrOpcode::VarStoreNameValue => {
+            reencode!(parser::ifr_var_store_name_value, encoder::ifr_var_store_name_value, data)
+        }
+        IfrOpcode::VarStoreEfi => reencode!(parser::ifr_var_store_efi, encoder::ifr_var_store_efi, data),
+        IfrOpcode::VarStoreDevice => reencode!(parser::ifr_var_store_device, encoder::ifr_var_store_device, data),
+        IfrOpcode::Get => reencode!(parser::ifr_get, encoder::ifr_get, data),
+        IfrOpcode::Set => reencode!(parser::ifr_set, encoder::ifr_set, data),
+        IfrOpcode::RuleRef => reencode!(parser::ifr_rule_ref, encoder::ifr_rule_ref, data),
+        IfrOpcode::QuestionRef1 => reencode!(parser::ifr_question_ref_1, encoder::ifr_question_ref_1, data),
+        IfrOpcode::Uint8 => reencode!(parser::ifr_uint8, encoder::ifr_uint8, data),
+        IfrOpcode::Uint16 => reencode!(parser::ifr_uint16, encoder::ifr_uint16, data),
+        IfrOpcode::Uint32 => reencode!(parser::ifr_uint32, encoder::ifr_uint32, data),
+        IfrOpcode::Uint64 => reencode!(parser::ifr_uint64, encoder::ifr_uint64, data),
+        IfrOpcode::ToString => reencode!(parser::ifr_to_string, encoder::ifr_to_string, data),
+        IfrOpcode::Find => reencode!(parser::ifr_find, encoder::ifr_find, data),
+        IfrOpcode::StringRef1 => reencode!(parser::ifr_string_ref_1, encoder::ifr_string_ref_1, data),
+        IfrOpcode::QuestionRef3 => reencode!(parser::ifr_question_ref_3, encoder::ifr_question_ref_3, data),
+        IfrOpcode::Span => reencode!(parser::ifr_span, encoder::ifr_span, data),
+        IfrOpcode::Default => reencode!(parser::ifr_default, encoder::ifr_default, data),
+        IfrOpcode::DefaultStore => reencode!(parser::ifr_default_store, encoder::ifr_default_store, data),
+        IfrOpcode::FormMap => reencode!(parser::ifr_form_map, encoder::ifr_form_map, data),
+        IfrOpcode::Guid => {
+            let (_, guid) = parser::ifr_guid(data).unwrap();
+            // Extended operations of known GUIDs are encoded from their own structures
+            let extended = if guid.Guid == parser::IFR_TIANO_GUID {
+                let (_, edk2) = parser::ifr_guid_edk2(guid.Data).unwrap();
+                if edk2.ExtendedOpCode == parser::IfrEdk2ExtendOpCode::Banner {
+                    let banner = reencode!(parser::ifr_guid_edk2_banner, encoder::ifr_guid_edk2_banner, edk2.Data);
+                    assert_eq!(banner, edk2.Data);
+                }
+                reencode!(parser::ifr_guid_edk2, encoder::ifr_guid_edk2, guid.Data)
+            } else if guid.Guid == parser::IFR_FRAMEWORK_GUID {
+                reencode!(parser::ifr_guid_edk, encoder::ifr_guid_edk, guid.Data)
+            } else {
+                guid.Data.to_vec()
+            };
+            assert_eq!(extended, guid.Data);
+            reencode!(parser::ifr_guid, encoder::ifr_guid, data)
+        }
+        IfrOpcode::Security => reencode!(parser::ifr_security, encoder::ifr_security, data),
+        IfrOpcode::RefreshId => reencode!(parser::ifr_refresh_id, encoder::ifr_refresh_id, data),
+        IfrOpcode::WarningIf => reencode!(parser::ifr_warning_if, encoder::ifr_warning_if, data),
+        IfrOpcode::Match2 => reencode!(parser::ifr_match_2, encoder::ifr_match_2, data),
+        // Operations without data of their own
+        _ => data.to_vec(),
+    }
+}
+
+// Operations of a form package, re-encoded one by one from their structures
+fn reencode_operations(operations: &[IfrOperation]) -> Vec<u8> {
+    let mut scopes = Vec::new(); // True for scopes of bit-field varstore GUID operations
+    let mut bytes = Vec::new();
+    for operation in operations {
+        let data = reencode_operation(operation, scopes.contains(&true));
+        assert_eq!(Some(&data[..]), operation.Data.or(Some(&[])), "{:?}", operation);
+        let encoded = encoder::ifr_operation(&IfrOperation {
+            Data: Some(&data),
+            ..*operation
+        })
+        .unwrap();
+        bytes.extend(encoded);
+
+        if operation.OpCode == IfrOpcode::End {
+            scopes.pop();
+        }
+        if operation.ScopeStart {
+            let bit_var_store = operation.OpCode == IfrOpcode::Guid
+                && parser::ifr_guid(operation.Data.unwrap_or_default()).unwrap().1.Guid
+                    == parser::EDKII_IFR_BIT_VARSTORE_GUID;
+            scopes.push(bit_var_store);
+        }
+    }
+    bytes
+}
+
+#[test]
+fn uefi_form_package() {
+    let package_bytes = fixtures::uefi_form_package(&fixtures::uefi_operations());
+    let (_, package) = parser::hii_package(&package_bytes).unwrap();
+    let (_, operations) = parser::ifr_operations(package.Data.unwrap()).unwrap();
+    let data = reencode_operations(&operations);
+    let encoded = encoder::hii_package(&parser::HiiPackage {
+        Data: Some(&data),
+        ..package
+    })
+    .unwrap();
+    assert_eq!(encoded, package_bytes);
+}
+
+#[test]
+fn uefi_string_package() {
+    let package_bytes = fixtures::uefi_string_package("en-US", &fixtures::uefi_sibt_blocks());
+    let (_, package) = parser::hii_package(&package_bytes).unwrap();
+    let (_, string_package) = parser::hii_string_package(package.Data.unwrap()).unwrap();
+    let (_, blocks) = parser::hii_sibt_blocks(string_package.Data).unwrap();
+    for block in &blocks {
+        // Strings of a block are encoded from the parsed strings, font identifiers come first
+        let data = block.Data.unwrap_or_default();
+        let font = data.first().cloned().unwrap_or_default();
+        let strings = match block.Type {
+            parser::HiiSibtType::StringScsu => Some(encoder::sibt_string_scsu(&parser::sibt_string_scsu(data).unwrap().1)),
+            parser::HiiSibtType::StringScsuFont => {
+                Some(encoder::sibt_string_scsu_font(font, &parser::sibt_string_scsu_font(data).unwrap().1))
+            }
+            parser::HiiSibtType::StringsScsu => Some(encoder::sibt_strings_scsu(&parser::sibt_strings_scsu(data).unwrap().1)),
+            parser::HiiSibtType::StringsScsuFont => {
+                Some(encoder::sibt_strings_scsu_font(font, &parser::sibt_strings_scsu_font(data).unwrap().1))
+            }
+            parser::HiiSibtType::StringUcs2 => Some(encoder::sibt_string_ucs2(&parser::sibt_string_ucs2(data).unwrap().1)),
+            parser::HiiSibtType::StringUcs2Font => {
+                Some(encoder::sibt_string_ucs2_font(font, &parser::sibt_string_ucs2_font(data).unwrap().1))
+            }
+            parser::HiiSibtType::StringsUcs2 => Some(encoder::sibt_strings_ucs2(&parser::sibt_strings_ucs2(data).unwrap().1)),
+            parser::HiiSibtType::StringsUcs2Font => {
+                Some(encoder::sibt_strings_ucs2_font(font, &parser::sibt_strings_ucs2_font(data).unwrap().1))
+            }
+            _ => None,
+        };
+        if let Some(strings) = strings {
+            assert_eq!(strings.unwrap(), data, "{:?}", block.Type);
+        }
+    }
+
+    let blocks_bytes = encoder::hii_sibt_blocks(&blocks).unwrap();
+    let data = encoder::hii_string_package(&parser::HiiStringPackage {
+        Data: &blocks_bytes,
+        ..string_package
+    })
+    .unwrap();
+    let encoded = encoder::hii_package(&parser::HiiPackage {
+        Data: Some(&data),
+        ..package
+    })
+    .unwrap();
+    assert_eq!(encoded, package_bytes);
+}
+
+// Edits that change the size of operations are written out with updated lengths
+#[test]
+fn edited_form_package() {
+    let package_bytes = fixtures::uefi_form_package(&fixtures::uefi_operations());
+    let (_, package) = parser::hii_package(&package_bytes).unwrap();
+    let (_, operations) = parser::ifr_operations(package.Data.unwrap()).unwrap();
+
+    let mut edited = Vec::new();
+    for operation in &operations {
+        let data = operation.Data.unwrap_or_default();
+        let data = match operation.OpCode {
+            IfrOpcode::VarStore => {
+                let (_, mut var_store) = parser::ifr_var_store(data).unwrap();
+                var_store.Name = String::from("SetupVolatile");
+                encoder::ifr_var_store(&var_store).unwrap()
+            }
+            IfrOpcode::Default => {
+                let (_, mut default) = parser::ifr_default(data).unwrap();
+                if default.Value == IfrTypeValue::Boolean(true) {
+                    default.Value = IfrTypeValue::Boolean(false);
+                }
+                encoder::ifr_default(&default).unwrap()
+            }
+            _ => data.to_vec(),
+        };
+        edited.push(data);
+    }
+    let operations_edited: Vec<IfrOperation> = operations
+        .iter()
+        .zip(&edited)
+        .map(|(operation, data)| IfrOperation {
+            Data: Some(data),
+            ..*operation
+        })
+        .collect();
+    let data = encoder::ifr_operations(&operations_edited).unwrap();
+    let encoded = encoder::hii_package(&parser::HiiPackage {
+        Data: Some(&data),
+        ..package
+    })
+    .unwrap();
+    assert_eq!(encoded.len(), package_bytes.len() + "Volatile".len());
+
+    let (_, package) = parser::hii_package(&encoded).unwrap();
+    let (_, reparsed) = parser::ifr_operations(package.Data.unwrap()).unwrap();
+    assert_eq!(reparsed.len(), operations.len());
+    for (operation, original) in reparsed.iter().zip(&operations) {
+        let data = operation.Data.unwrap_or_default();
+        match operation.OpCode {
+            IfrOpcode::VarStore => assert_eq!(parser::ifr_var_store(data).unwrap().1.Name, "SetupVolatile"),
+            IfrOpcode::Default => assert_ne!(parser::ifr_default(data).unwrap().1.Value, IfrTypeValue::Boolean(true)),
+            _ => assert_eq!(operation, original),
+        }
+    }
+}
+
+#[test]
+fn lengths_that_do_not_fit() {
+    let data = vec![0; 0x7E];
+    let operation = IfrOperation {
+        OpCode: IfrOpcode::Guid,
+        Length: 0,
+        ScopeStart: false,
+        Data: Some(&data),
+    };
+    let error = encoder::ifr_operation(&operation).unwrap_err();
+    assert_eq!(error.to_string(), "Length of 128 doesn't fit, at most 127 is possible");
+
+    let values = vec![0; 0x10000];
+    let list = parser::IfrEqIdValList {
+        QuestionId: 1,
+        Values: values,
+    };
+    assert_eq!(encoder::ifr_eq_id_val_list(&list).unwrap_err().field, "ListLength");
+}
+
+//
+// Properties
+//
+// Fixture data of the first operation with the given opcode
+fn operation_data(opcode: u8) -> Vec<u8> {
+    let operations = fixtures::uefi_operations().bytes;
+    let (_, operations) = parser::ifr_operations(&operations).unwrap();
+    operations
+        .iter()
+        .find(|o| u8::from(o.OpCode) == opcode)
+        .and_then(|o| o.Data)
+        .unwrap_or_default()
+        .to_vec()
+}
+
+// Arbitrary bytes, or the given bytes with some of them changed and cut or extended at the end
+fn inputs(base: Vec<u8>) -> BoxedStrategy<Vec<u8>> {
+    let mutated = (vec((any::<usize>(), any::<u8>()), 0..4), 0..=base.len(), vec(any::<u8>(), 0..16)).prop_map(
+        move |(changes, length, extra)| {
+            let mut bytes = base.clone();
+            for (index, value) in changes {
+                if !bytes.is_empty() {
+                    let index = index % bytes.len();
+                    bytes[index] = value;
+                }
+            }
+            if length < bytes.len() {
+                bytes.truncate(length);
+            } else {
+                bytes.extend(extra);
+            }
+            bytes
+        },
+    );
+    prop_oneof![vec(any::<u8>(), 0..96), mutated].boxed()
+}
+
+// Whatever a parser accepts is encoded into bytes it parses again into the same structure.
+// Lossless parsers also give back exactly the bytes they consumed, the others normalize
+// strings to UTF-8, booleans to 0 and 1 and drop trailing bytes of values
+macro_rules! round_trip {
+    ($($name:ident: $base:expr, $parse:path, $encode:path, $lossless:expr;)*) => {
+        proptest! {
+            $(
+                #[test]
+                fn $name(input in inputs($base)) {
+                    if let Ok((rest, value)) = $parse(&input) {
+                        let encoded = $encode(&value).unwrap();
+                        if $lossless {
+                            prop_assert_eq!(&encoded[..], &input[..input.len() - rest.len()]);
+                        }
+                        let (rest, reparsed) = $parse(&encoded).unwrap();
+                        prop_assert!(rest.is_empty());
+                        prop_assert_eq!(reparsed, value);
+                    }
+                }
+            )*
+        }
+    };
+}
+
+round_trip! {
+    guid: Vec::new(), parser::guid, encoder::guid, true;
+    hii_package: fixtures::uefi_form_package(&fixtures::uefi_operations())[..0x40].to_vec(), parser::hii_package, encoder::hii_package, true;
+    hii_sibt_blocks: fixtures::uefi_sibt_blocks(), parser::hii_sibt_blocks, encoder::hii_sibt_blocks, true;
+    sibt_string_scsu: b"String\0".to_vec(), parser::sibt_string_scsu, encoder::sibt_string_scsu, false;
+    sibt_strings_scsu: b"\x02\x00One\0Two\0".to_vec(), parser::sibt_strings_scsu, encoder::sibt_strings_scsu, false;
+    sibt_string_ucs2: b"S\0t\0r\0\0\0".to_vec(), parser::sibt_string_ucs2, encoder::sibt_string_ucs2, false;
+    sibt_strings_ucs2: b"\x02\x00O\0n\0e\0\0\0T\0w\0o\0\0\0".to_vec(), parser::sibt_strings_ucs2, encoder::sibt_strings_ucs2, false;
+    ifr_operations: fixtures::uefi_operations().bytes[..0x80].to_vec(), parser::ifr_operations, encoder::ifr_operations, true;
+    ifr_form: operation_data(0x01), parser::ifr_form, encoder::ifr_form, true;
+    ifr_subtitle: operation_data(0x02), parser::ifr_subtitle, encoder::ifr_subtitle, true;
+    ifr_text: operation_data(0x03), parser::ifr_text, encoder::ifr_text, true;
+    ifr_image: operation_data(0x04), parser::ifr_image, encoder::ifr_image, true;
+    ifr_one_of: operation_data(0x05), parser::ifr_one_of, encoder::ifr_one_of, true;
+    ifr_check_box: operation_data(0x06), parser::ifr_check_box, encoder::ifr_check_box, true;
+    ifr_numeric: operation_data(0x07), parser::ifr_numeric, encoder::ifr_numeric, true;
+    ifr_password: operation_data(0x08), parser::ifr_password, encoder::ifr_password, true;
+    hii_time: Vec::new(), parser::hii_time, encoder::hii_time, true;
+    hii_date: Vec::new(), parser::hii_date, encoder::hii_date, true;
+    hii_ref: Vec::new(), parser::hii_ref, encoder::hii_ref, true;
+    ifr_one_of_option: operation_data(0x09), parser::ifr_one_of_option, encoder::ifr_one_of_option, false;
+    ifr_action: operation_data(0x0C), parser::ifr_action, encoder::ifr_action, true;
+    ifr_reset_button: operation_data(0x0D), parser::ifr_reset_button, encoder::ifr_reset_button, true;
+    ifr_form_set: operation_data(0x0E), parser::ifr_form_set, encoder::ifr_form_set, true;
+    ifr_ref: operation_data(0x0F), parser::ifr_ref, encoder::ifr_ref, true;
+    ifr_no_submit_if: operation_data(0x10), parser::ifr_no_submit_if, encoder::ifr_no_submit_if, true;
+    ifr_inconsistent_if: operation_data(0x11), parser::ifr_inconsistent_if, encoder::ifr_inconsistent_if, true;
+    ifr_eq_id_val: operation_data(0x12), parser::ifr_eq_id_val, encoder::ifr_eq_id_val, true;
+    ifr_eq_id_id: operation_data(0x13), parser::ifr_eq_id_id, encoder::ifr_eq_id_id, true;
+    ifr_eq_id_val_list: operation_data(0x14), parser::ifr_eq_id_val_list, encoder::ifr_eq_id_val_list, true;
+    ifr_rule: operation_data(0x18), parser::ifr_rule, encoder::ifr_rule, true;
+    ifr_date: operation_data(0x1A), parser::ifr_date, encoder::ifr_date, true;
+    ifr_time: operation_data(0x1B), parser::ifr_time, encoder::ifr_time, true;
+    ifr_string: operation_data(0x1C), parser::ifr_string, encoder::ifr_string, true;
+    ifr_refresh: operation_data(0x1D), parser::ifr_refresh, encoder::ifr_refresh, true;
+    ifr_animation: operation_data(0x1F), parser::ifr_animation, encoder::ifr_animation, true;
+    ifr_ordered_list: operation_data(0x23), parser::ifr_ordered_list, encoder::ifr_ordered_list, true;
+    ifr_var_store: operation_data(0x24), parser::ifr_var_store, encoder::ifr_var_store, false;
+    ifr_var_store_name_value: operation_data(0x25), parser::ifr_var_store_name_value, encoder::ifr_var_store_name_value, true;
+    ifr_var_store_efi: operation_data(0x26), parser::ifr_var_store_efi, encoder::ifr_var_store_efi, false;
+    ifr_var_store_device: operation_data(0x27), parser::ifr_var_store_device, encoder::ifr_var_store_device, true;
+    ifr_get: operation_data(0x2B), parser::ifr_get, encoder::ifr_get, true;
+    ifr_set: operation_data(0x2C), parser::ifr_set, encoder::ifr_set, true;
+    ifr_rule_ref: operation_data(0x3F), parser::ifr_rule_ref, encoder::ifr_rule_ref, true;
+    ifr_question_ref_1: operation_data(0x40), parser::ifr_question_ref_1, encoder::ifr_question_ref_1, true;
+    ifr_uint8: operation_data(0x42), parser::ifr_uint8, encoder::ifr_uint8, true;
+    ifr_uint16: operation_data(0x43), parser::ifr_uint16, encoder::ifr_uint16, true;
+    ifr_uint32: operation_data(0x44), parser::ifr_uint32, encoder::ifr_uint32, true;
+    ifr_uint64: operation_data(0x45), parser::ifr_uint64, encoder::ifr_uint64, true;
+    ifr_to_string: operation_data(0x49), parser::ifr_to_string, encoder::ifr_to_string, true;
+    ifr_find: operation_data(0x4C), parser::ifr_find, encoder::ifr_find, true;
+    ifr_string_ref_1: operation_data(0x4E), parser::ifr_string_ref_1, encoder::ifr_string_ref_1, true;
+    ifr_question_ref_3: operation_data(0x51), parser::ifr_question_ref_3, encoder::ifr_question_ref_3, true;
+    ifr_span: operation_data(0x59), parser::ifr_span, encoder::ifr_span, true;
+    ifr_default: operation_data(0x5B), parser::ifr_default, encoder::ifr_default, false;
+    ifr_default_store: operation_data(0x5C), parser::ifr_default_store, encoder::ifr_default_store, true;
+    ifr_form_map: operation_data(0x5D), parser::ifr_form_map, encoder::ifr_form_map, true;
+    ifr_guid: operation_data(0x5F), parser::ifr_guid, encoder::ifr_guid, true;
+    ifr_one_of_bit: operation_data(0x05), parser::ifr_one_of_bit, encoder::ifr_one_of_bit, true;
+    ifr_numeric_bit: operation_data(0x07), parser::ifr_numeric_bit, encoder::ifr_numeric_bit, true;
+    ifr_guid_edk2: operation_data(0x5F)[16..].to_vec(), parser::ifr_guid_edk2, encoder::ifr_guid_edk2, true;
+    ifr_guid_edk2_banner: Vec::new(), parser::ifr_guid_edk2_banner, encoder::ifr_guid_edk2_banner, true;
+    ifr_guid_edk: operation_data(0x5F)[16..].to_vec(), parser::ifr_guid_edk, encoder::ifr_guid_edk, true;
+    ifr_security: operation_data(0x60), parser::ifr_security, encoder::ifr_security, true;
+    ifr_refresh_id: operation_data(0x62), parser::ifr_refresh_id, encoder::ifr_refresh_id, true;
+    ifr_warning_if: operation_data(0x63), parser::ifr_warning_if, encoder::ifr_warning_if, true;
+    ifr_match_2: operation_data(0x64), parser::ifr_match_2, encoder::ifr_match_2, true;
+}
+
+// UTF-8 strings are kept as they are, the string package header only grows if the language doesn't fit
+proptest! {
+    #[test]
+    fn hii_string_package(input in inputs(fixtures::uefi_string_package("en-US", &[0x00])[4..].to_vec())) {
+        if let Ok((_, value)) = parser::hii_string_package(&input) {
+            prop_assume!(!value.Language.contains('\u{FFFD}'));
+            let encoded = encoder::hii_string_package(&value).unwrap();
+            let (_, reparsed) = parser::hii_string_package(&encoded).unwrap();
+            prop_assert_eq!(reparsed, value);
+        }
+    }
+
+    #[test]
+    fn edited_strings(strings in vec("[ -~]{0,16}|\\PC{0,8}", 0..4), font in any::<u8>()) {
+        let encoded = encoder::sibt_strings_ucs2_font(font, &strings).unwrap();
+        prop_assert_eq!(parser::sibt_strings_ucs2_font(&encoded).unwrap(), (&[][..], strings.clone()));
+        let strings: Vec<String> = strings.into_iter().filter(|s| !s.contains('\0')).collect();
+        let encoded = encoder::sibt_strings_scsu(&strings).unwrap();
+        prop_assert_eq!(parser::sibt_strings_scsu(&encoded).unwrap(), (&[][..], strings));
+    }
+}